@@ -30,25 +30,49 @@ impl YamlNode {
     }
 }
 
+/// Where files are read from and written to, StdFileSystem is std::fs.
+pub trait FileSystem {
+    fn read(&self, path: &str) -> Result<Vec<u8>, StdError>;
+    fn write(&self, path: &str, bytes: &[u8]) -> Result<(), StdError>;
+}
+
+pub struct StdFileSystem;
+impl FileSystem for StdFileSystem {
+    fn read(&self, path: &str) -> Result<Vec<u8>, StdError> {
+        Ok(std::fs::read(path)?)
+    }
+
+    fn write(&self, path: &str, bytes: &[u8]) -> Result<(), StdError> {
+        Ok(std::fs::write(path, bytes)?)
+    }
+}
+
 pub fn serialize<T: Serialize>(value: &T, path: &str, name: &str) -> Result<(), StdError> {
     let path = format!("{}{}.yaml", path, name);
-    std::fs::write(path, serde_yaml::to_string(value)?)?;
-    Ok(())
+    serialize_with(&StdFileSystem, value, &path)
 }
 pub fn serialize_full<T: Serialize>(value: &T, path: &str) -> Result<(), StdError> {
-    std::fs::write(path, serde_yaml::to_string(value)?)?;
-    Ok(())
+    serialize_with(&StdFileSystem, value, path)
+}
+pub fn serialize_with<T: Serialize>(fs: &dyn FileSystem, value: &T, path: &str) -> Result<(), StdError> {
+    fs.write(path, serde_yaml::to_string(value)?.as_bytes())
 }
 pub fn deserialize<T: DeserializeOwned>(path: &str, name: &str) -> Result<T, StdError> {
     let path = format!("{}{}.yaml", path, name);
-    let content = std::fs::read_to_string(path)?;
-    let result: T = serde_yaml::from_str(&content)?;
-
-    Ok(result)
+    deserialize_with(&StdFileSystem, &path)
 }
 pub fn deserialize_full_path<T: DeserializeOwned>(path: &str) -> Result<T, StdError> {
-    // let content = std::path::Path::new(path);
-    let file = std::fs::File::open(path)?;
-    Ok(serde_yaml::from_reader::<std::fs::File, T>(file)?)
-    // Ok(serde_yaml::from_str(&content)?)
-}
\ No newline at end of file
+    deserialize_with(&StdFileSystem, path)
+}
+pub fn deserialize_with<T: DeserializeOwned>(fs: &dyn FileSystem, path: &str) -> Result<T, StdError> {
+    deserialize_bytes(&fs.read(path)?)
+}
+pub fn serialize_str<T: Serialize>(value: &T) -> Result<String, StdError> {
+    Ok(serde_yaml::to_string(value)?)
+}
+pub fn deserialize_str<T: DeserializeOwned>(content: &str) -> Result<T, StdError> {
+    Ok(serde_yaml::from_str(content)?)
+}
+pub fn deserialize_bytes<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, StdError> {
+    Ok(serde_yaml::from_slice(bytes)?)
+}
@@ -0,0 +1,302 @@
+//! Inspects the asset database without a window or a GPU.
+//!
+//! Exits with 1 when a command fails, e.g. when validate finds a broken material.
+
+use std::process::ExitCode;
+use l3gion_rust::{lg_core::{asset_manager::{AssetKind, AssetManager, AssetPath, Compression, PackBuilder, DEFAULT_ASSETS_ROOT, LGMAT_EXTENSION}, renderer::{material::Material, mesh::{Mesh, Topology}, shader::{Shader, ShaderStage}, texture::Texture}}, StdError, UUID};
+
+const USAGE: &str = "\
+Usage: lgasset [--root <dir>] <command>
+
+Commands:
+    list                        Every asset with its UUID and kind
+    resolve <uuid>              Path of the asset with that UUID
+    dependents <uuid>           Assets that reference it, directly or not
+    validate                    Checks the shaders and textures of every .lgmat file, shaders are compiled,
+                                and that no asset references a missing one
+    mesh-stats [<asset>...]     Vertices, triangles, sub-meshes and bounds, every mesh without arguments
+    pack <output> [--deflate]   Packs every asset of the root";
+
+fn main() -> ExitCode {
+    // Warnings and errors only.
+    if std::env::var_os("LOG").is_none() {
+        std::env::set_var("LOG", "3");
+    }
+
+    match run(std::env::args().skip(1).collect()) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("lgasset: {e}");
+            ExitCode::FAILURE
+        },
+    }
+}
+
+/// Ok(false) when the command ran but found problems.
+fn run(mut args: Vec<String>) -> Result<bool, StdError> {
+    let mut root = DEFAULT_ASSETS_ROOT.to_string();
+    if let Some(i) = args.iter().position(|a| a == "--root") {
+        if i + 1 >= args.len() {
+            return Err("--root needs a directory!".into());
+        }
+        root = args.remove(i + 1);
+        args.remove(i);
+    }
+
+    if args.is_empty() {
+        return Err(USAGE.into());
+    }
+    let command = args.remove(0);
+
+    // Packing reads the files as they are.
+    if command == "pack" {
+        return pack(&root, &args);
+    }
+
+    if !std::path::Path::new(&root).is_dir() {
+        return Err(std::format!("{} is not a directory!", root).into());
+    }
+
+    // The checked tree is never modified, no .meta file is created and no material is migrated on disk.
+    let mut manager = AssetManager::new(&root);
+    manager.init_read_only()?;
+
+    match command.as_str() {
+        "list" => list(&manager),
+        "resolve" => resolve(&manager, &args),
+        "dependents" => dependents(&manager, &args),
+        "validate" => Ok(validate(&mut manager)),
+        "mesh-stats" => mesh_stats(&mut manager, &args),
+        "help" | "--help" | "-h" => {
+            println!("{USAGE}");
+            Ok(true)
+        },
+        _ => Err(std::format!("Unknown command {}!\n\n{USAGE}", command).into()),
+    }
+}
+
+fn list(manager: &AssetManager) -> Result<bool, StdError> {
+    let mut assets = manager.assets().collect::<Vec<_>>();
+    assets.sort_by_key(|(_, path)| path.to_string());
+
+    for (uuid, path) in assets {
+        let kind = manager.kind_of(path).map_or("Unknown".to_string(), |k| std::format!("{:?}", k));
+        println!("{uuid}\t{kind}\t{path}");
+    }
+
+    Ok(true)
+}
+
+fn resolve(manager: &AssetManager, args: &[String]) -> Result<bool, StdError> {
+    let uuid = parse_uuid(args.first().ok_or("resolve needs a UUID!")?)?;
+
+    match manager.path_of(&uuid) {
+        Some(path) => {
+            println!("{path}");
+            Ok(true)
+        },
+        None => Err(std::format!("There is no asset with UUID {}!", uuid).into()),
+    }
+}
+
+fn dependents(manager: &AssetManager, args: &[String]) -> Result<bool, StdError> {
+    let uuid = parse_uuid(args.first().ok_or("dependents needs a UUID!")?)?;
+
+    let mut dependents = manager.dependency_graph()
+        .transitive_dependents(&uuid)
+        .into_iter()
+        .map(|d| (d, manager.path_of(&d).map_or("?".to_string(), |p| p.to_string())))
+        .collect::<Vec<_>>();
+    dependents.sort_by(|a, b| a.1.cmp(&b.1));
+
+    for (uuid, path) in dependents {
+        println!("{uuid}\t{path}");
+    }
+
+    Ok(true)
+}
+
+/// Every problem is printed, false if there was one.
+fn validate(manager: &mut AssetManager) -> bool {
+    let mut materials = manager.assets()
+        .filter(|(_, path)| path.extension() == Some(LGMAT_EXTENSION))
+        .map(|(uuid, path)| (*uuid, path.clone()))
+        .collect::<Vec<_>>();
+    materials.sort_by_key(|(_, path)| path.to_string());
+
+    let mut valid = true;
+    for (uuid, path) in &materials {
+        let errors = validate_material(manager, uuid);
+
+        match errors.is_empty() {
+            true => println!("ok\t{path}"),
+            false => {
+                valid = false;
+                println!("FAILED\t{path}");
+                errors.iter().for_each(|e| println!("\t{e}"));
+            },
+        }
+    }
+
+    for (asset, reference) in manager.dangling_references() {
+        valid = false;
+
+        let path = manager.path_of(&asset).map_or(asset.to_string(), |p| p.to_string());
+        println!("FAILED\t{path} references {reference}, which doesn't exist");
+    }
+
+    println!("{} materials, {}", materials.len(), if valid { "all valid" } else { "some are invalid" });
+
+    valid
+}
+
+fn validate_material(manager: &mut AssetManager, uuid: &UUID) -> Vec<String> {
+    let material = match manager.handle::<Material>(uuid) {
+        Ok(material) => material,
+        Err(e) => return vec![e.to_string()],
+    };
+    // Copied, the manager loads the shaders and textures while they are checked.
+    let (shaders, textures) = match manager.resolve(&material) {
+        Some(material) => (material.shaders().to_vec(), material.texture().to_vec()),
+        None => return vec![std::format!("{} isn't loaded!", uuid)],
+    };
+
+    let mut errors = Vec::new();
+    let reference = |manager: &AssetManager, uuid: &UUID, kind: AssetKind| -> Result<AssetPath, String> {
+        let path = manager.path_of(uuid).ok_or(std::format!("{:?} {} doesn't exist!", kind, uuid))?;
+
+        match manager.kind_of(path) {
+            Some(k) if k == kind => Ok(path.clone()),
+            k => Err(std::format!("{} is a {:?}, not a {:?}!", path, k, kind)),
+        }
+    };
+
+    if shaders.len() != 2 {
+        errors.push(std::format!("Has {} shaders instead of a vertex and a fragment one!", shaders.len()));
+    }
+
+    for (i, shader_uuid) in shaders.iter().enumerate() {
+        let path = match reference(manager, shader_uuid, AssetKind::Shader) {
+            Ok(path) => path,
+            Err(e) => { errors.push(e); continue; },
+        };
+
+        let shader = match manager.handle::<Shader>(shader_uuid) {
+            Ok(shader) => shader,
+            Err(e) => { errors.push(std::format!("{path}: {e}")); continue; },
+        };
+        let Some(shader) = manager.resolve(&shader) else {
+            errors.push(std::format!("{path} isn't loaded!"));
+            continue;
+        };
+
+        match (i, shader.stage()) {
+            (0, ShaderStage::VERTEX) | (1, ShaderStage::FRAGMENT) => (),
+            (_, stage) => errors.push(std::format!("{path} is a {:?} shader in the wrong slot!", stage)),
+        }
+
+        if let Err(e) = shader.compile_spirv() {
+            errors.push(std::format!("{path} doesn't compile: {e}"));
+        }
+    }
+
+    for texture_uuid in &textures {
+        let path = match reference(manager, texture_uuid, AssetKind::Texture) {
+            Ok(path) => path,
+            Err(e) => { errors.push(e); continue; },
+        };
+
+        if let Err(e) = manager.handle::<Texture>(texture_uuid) {
+            errors.push(std::format!("{path}: {e}"));
+        }
+    }
+
+    errors
+}
+
+/// assets are paths or UUIDs.
+fn mesh_stats(manager: &mut AssetManager, assets: &[String]) -> Result<bool, StdError> {
+    let mut meshes = match assets.is_empty() {
+        true => manager.assets()
+            .filter(|(_, path)| manager.kind_of(path) == Some(AssetKind::Mesh))
+            .map(|(uuid, path)| (*uuid, path.to_string()))
+            .collect::<Vec<_>>(),
+        false => assets
+            .iter()
+            .map(|a| {
+                let uuid = match parse_uuid(a) {
+                    Ok(uuid) => uuid,
+                    Err(_) => manager.uuid_of(&AssetPath::new(a)?).ok_or(std::format!("{} is not an asset!", a))?,
+                };
+                Ok((uuid, a.clone()))
+            })
+            .collect::<Result<Vec<_>, StdError>>()?,
+    };
+    meshes.sort_by(|a, b| a.1.cmp(&b.1));
+
+    let mut valid = true;
+    for (uuid, path) in meshes {
+        let mesh = match manager.handle::<Mesh>(&uuid) {
+            Ok(mesh) => mesh,
+            Err(e) => {
+                valid = false;
+                println!("FAILED\t{path}: {e}");
+                continue;
+            },
+        };
+
+        match manager.resolve(&mesh) {
+            Some(mesh) => print_mesh(&path, mesh),
+            None => {
+                valid = false;
+                println!("FAILED\t{path} isn't loaded");
+            },
+        }
+    }
+
+    Ok(valid)
+}
+
+fn print_mesh(path: &str, mesh: &Mesh) {
+    let primitives = match mesh.topology() {
+        Topology::Triangles => std::format!("{} triangles", mesh.indices().len() / 3),
+        Topology::Lines => std::format!("{} lines", mesh.indices().len() / 2),
+        Topology::Points => std::format!("{} points", mesh.indices().len()),
+    };
+    let aabb = mesh.aabb();
+
+    println!("{path}");
+    println!("\t{} vertices, {} indices, {primitives}", mesh.vertices().len(), mesh.indices().len());
+    println!("\tbounds: ({}, {}, {}) to ({}, {}, {})", aabb.min.x, aabb.min.y, aabb.min.z, aabb.max.x, aabb.max.y, aabb.max.z);
+    println!("\ttangents: {}", if mesh.tangents().is_empty() { "no" } else { "yes" });
+
+    for sub_mesh in mesh.sub_meshes() {
+        let material = sub_mesh.material.map_or("none".to_string(), |m| m.to_string());
+        println!("\tsub-mesh {}: {} indices from {}, material {material}", sub_mesh.name, sub_mesh.index_count, sub_mesh.first_index);
+    }
+}
+
+fn pack(root: &str, args: &[String]) -> Result<bool, StdError> {
+    let output = args
+        .iter()
+        .find(|a| !a.starts_with("--"))
+        .ok_or("pack needs an output file!")?;
+    let compression = match args.iter().any(|a| a == "--deflate") {
+        true => Compression::Deflate,
+        false => Compression::None,
+    };
+
+    PackBuilder::new()
+        .compression(compression)
+        .add_dir(root)?
+        .write(output)?;
+
+    println!("Packed {} into {}", root, output);
+
+    Ok(true)
+}
+
+fn parse_uuid(value: &str) -> Result<UUID, StdError> {
+    Ok(UUID::from_u128(value.trim().parse::<u128>()?))
+}
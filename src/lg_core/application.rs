@@ -1,17 +1,61 @@
 use crate::{glm, lg_core::{frame_time::FrameTime, renderer::Renderer}, profile_function, profile_scope, StdError};
-use super::{event::{KeyEvent, LgEvent, MouseButton, MouseButtonEvent, MouseEvent, MouseMoveEvent, MouseScrollEvent}, input::LgInput, layer::Layer, lg_types::reference::Rfc, renderer::CreationWindowInfo,  window::LgWindow};
+use super::{asset_manager::{AssetImporter, AssetManager, Mount, DEFAULT_ASSETS_ROOT, ROOT_PRIORITY}, event::{KeyEvent, LgEvent, MouseButton, MouseButtonEvent, MouseEvent, MouseMoveEvent, MouseScrollEvent}, input::LgInput, layer::Layer, lg_types::reference::Rfc, renderer::CreationWindowInfo,  window::LgWindow};
 
 pub struct ApplicationCreateInfo<'a> {
     pub window_info: CreationWindowInfo<'a>,
+    /// Runs without a window.
+    pub headless: Option<HeadlessInfo>,
+    /// Directory every asset is read from, asset:// paths are relative to it. None is ./assets.
+    pub assets_root: Option<std::path::PathBuf>,
+    /// .lgpak files mounted above assets_root, the later ones win.
+    pub asset_packs: Vec<std::path::PathBuf>,
+    /// Other mounts with their priority, e.g. a MemoryMount with embedded assets.
+    pub asset_mounts: Vec<(std::sync::Arc<dyn Mount>, i32)>,
+    /// Importers for other file extensions, registered before anything is mounted.
+    pub asset_importers: Vec<(String, std::sync::Arc<dyn AssetImporter>)>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum HeadlessBackend {
+    /// Nothing is rendered, frames are recorded by a RecordingBackend.
+    #[default]
+    Recording,
+    /// Renders for real through an EGL pbuffer (or surfaceless) context, falls back to Software when there is none.
+    /// 
+    /// Linux only, works with Mesa's software OpenGL.
+    OpenGl,
+    /// Renders on the CPU with the SoftwareBackend.
+    Software,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HeadlessInfo {
+    /// Runs until a layer calls ApplicationCore::request_exit() when None.
+    pub max_frames: Option<u64>,
+    pub backend: HeadlessBackend,
 }
 
 pub struct L3gion {
     app: Application,
-    _event_loop: winit::event_loop::EventLoop<()>,
+    _event_loop: Option<winit::event_loop::EventLoop<()>>,
+    headless: Option<HeadlessInfo>,
 }
 impl L3gion {
-    pub fn new(mut info: ApplicationCreateInfo) -> Result<Self, StdError> {
+    pub fn new(info: ApplicationCreateInfo) -> Result<Self, StdError> {
         profile_function!();
+        let headless = info.headless;
+
+        if headless.is_some() {
+            let mut application = Application::new(info)?;
+            application.init()?;
+
+            return Ok(Self {
+                app: application,
+                _event_loop: None,
+                headless,
+            });
+        }
+
         let event_loop = winit::event_loop::EventLoop::new()?;
         event_loop.set_control_flow(winit::event_loop::ControlFlow::Poll);
 
@@ -23,7 +67,8 @@ impl L3gion {
 
         Ok(Self {
             app: application,
-            _event_loop: event_loop,
+            _event_loop: Some(event_loop),
+            headless,
         })
     }
 
@@ -35,13 +80,18 @@ impl L3gion {
     }
 
     pub fn run(mut self) -> Result<(), StdError> {
+        if let Some(headless) = self.headless {
+            return self.run_headless(headless);
+        }
+
         let mut last_frame = std::time::Instant::now();
+        let event_loop = self._event_loop.take().ok_or("Missing event loop! (L3gion)")?;
 
-        self._event_loop.run(move |event, window_target| {
+        event_loop.run(move |event, window_target| {
             match &event {
                 event => {
                     self.app.core.renderer.borrow().handle_imgui_event(
-                        &self.app.core.window.borrow().window, 
+                        self.app.core.window.borrow().window.as_ref().unwrap(), 
                         event
                     );
                 }
@@ -119,8 +169,14 @@ impl L3gion {
                     _ => ()
                 },
                 winit::event::Event::AboutToWait => {
+                    if self.app.core.exit_requested() {
+                        self.app.shutdown().unwrap();
+                        window_target.exit();
+                        return;
+                    }
+
                     self.app.core.renderer.borrow_mut().prepare_imgui_frame(
-                        &self.app.core.window.borrow().window
+                        self.app.core.window.borrow().window.as_ref().unwrap()
                     );
                     self.app.core.window.borrow().request_redraw();
                 },
@@ -131,11 +187,53 @@ impl L3gion {
         Ok(())
     }
 }
+// Private
+impl L3gion {
+    fn run_headless(mut self, info: HeadlessInfo) -> Result<(), StdError> {
+        let mut last_frame = std::time::Instant::now();
+        let mut frame_count = 0;
+
+        // Mimics the first event a window would send.
+        let size = self.app.core.window.borrow().size();
+        self.app.on_event(LgEvent::WindowEvent(super::event::WindowEvent::Resize(size.x as u32, size.y as u32)));
+
+        while !self.app.core.exit_requested() {
+            if info.max_frames.is_some_and(|max| frame_count >= max) {
+                break;
+            }
+
+            optick::next_frame();
+            let now = std::time::Instant::now();
+
+            self.app.core.renderer
+                .borrow()
+                .update_imgui_delta_time(now.duration_since(last_frame));
+
+            last_frame = now;
+
+            self.app.on_update()?;
+            frame_count += 1;
+        }
+
+        self.app.shutdown()
+    }
+}
 
 #[derive(Clone)]
 pub struct ApplicationCore {
     pub window: Rfc<LgWindow>,
     pub renderer: Rfc<Renderer>,
+    exit: Rfc<bool>,
+}
+impl ApplicationCore {
+    /// The application will shutdown at the end of the current frame.
+    pub fn request_exit(&self) {
+        *self.exit.borrow_mut() = true;
+    }
+
+    pub fn exit_requested(&self) -> bool {
+        *self.exit.borrow()
+    }
 }
 pub struct Application {
     core: ApplicationCore,
@@ -176,13 +274,32 @@ impl Application {
 impl Application {
     fn new(info: ApplicationCreateInfo) -> Result<Self, StdError> {
         profile_function!();
-        let (renderer, window) = Renderer::new(info.window_info)?;
+        let mut asset_manager = AssetManager::new(info.assets_root.unwrap_or(DEFAULT_ASSETS_ROOT.into()));
+        for (extension, importer) in info.asset_importers {
+            asset_manager.register_importer(&extension, importer)?;
+        }
+        for pack in &info.asset_packs {
+            asset_manager.mount_pack(pack, ROOT_PRIORITY + 1)?;
+        }
+        for (mount, priority) in info.asset_mounts {
+            asset_manager.mount(mount, priority)?;
+        }
+
+        let (renderer, window) = match info.headless {
+            Some(headless) => match headless.backend {
+                HeadlessBackend::Recording => Renderer::new_headless(info.window_info, asset_manager)?,
+                HeadlessBackend::OpenGl => Renderer::new_offscreen(info.window_info, asset_manager)?,
+                HeadlessBackend::Software => Renderer::new_software(info.window_info, asset_manager)?,
+            },
+            None => Renderer::new(info.window_info, asset_manager)?,
+        };
         let renderer = Rfc::new(renderer);
         let window = Rfc::new(window);
 
         let core = ApplicationCore {
             window,
             renderer,
+            exit: Rfc::new(false),
         };
 
         Ok(Self {
@@ -250,15 +367,18 @@ impl Application {
                 layer.borrow_mut().on_imgui(ui);
             }
             
-            self.core
-                .renderer
-                .borrow()
-                .core()
-                .imgui()
-                .prepare_to_render(ui, &self.core.window.borrow().window);
+            if let Some(window) = &self.core.window.borrow().window {
+                self.core
+                    .renderer
+                    .borrow()
+                    .core()
+                    .imgui()
+                    .prepare_to_render(ui, window);
+            }
         }
 
         let renderer = self.core.renderer.borrow_mut();
+        renderer.flush_render_graph();
         // TODO: Deal with editor / runtime builds
         // renderer.send(crate::lg_core::renderer::command::SendRendererCommand::_DRAW_BACKBUFFER);
         renderer.draw_imgui();
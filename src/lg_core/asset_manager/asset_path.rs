@@ -0,0 +1,170 @@
+use std::path::{Component, Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::StdError;
+
+pub const ASSET_SCHEME: &str = "asset://";
+/// Separates a file from an asset inside it, e.g. asset://objects/cube.mtl#Red.
+pub const LABEL_SEPARATOR: char = '#';
+/// Root of the paths written before AssetPath existed, e.g. assets\shaders\src\BP_BRDF_v.vert.
+const LEGACY_ROOT: &str = "assets";
+
+/// Platform independent path of an asset, relative to the asset root.
+///
+/// Written as asset://shaders/src/BP_BRDF_v.vert, always with / separators.
+/// Sub-assets (e.g. the materials of an .mtl file) have a label after LABEL_SEPARATOR.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct AssetPath(String);
+impl AssetPath {
+    /// Accepts asset://..., paths relative to the root, and project relative paths starting with assets/ or assets\,
+    /// which is how they were written before (e.g. in .lgmat files).
+    pub fn new(path: &str) -> Result<Self, StdError> {
+        let (file, label) = match path.split_once(LABEL_SEPARATOR) {
+            Some((file, label)) => (file, Some(label)),
+            None => (path, None),
+        };
+
+        let (relative, virtual_path) = match file.strip_prefix(ASSET_SCHEME) {
+            Some(relative) => (relative, true),
+            None => (file, false),
+        };
+
+        let mut components = Vec::new();
+        for component in relative.split(['/', '\\']) {
+            match component {
+                "" | "." => (),
+                ".." => if components.pop().is_none() {
+                    return Err(std::format!("{} is outside of the asset root! (AssetPath)", path).into());
+                },
+                _ => components.push(component),
+            }
+        }
+
+        if !virtual_path && components.first() == Some(&LEGACY_ROOT) {
+            components.remove(0);
+        }
+
+        if components.is_empty() {
+            return Err(std::format!("{} is an empty asset path! (AssetPath)", path).into());
+        }
+
+        Ok(match label {
+            Some(label) if !label.is_empty() => Self(std::format!("{}{LABEL_SEPARATOR}{}", components.join("/"), label)),
+            _ => Self(components.join("/")),
+        })
+    }
+
+    /// None if path is not inside root.
+    pub fn from_fs(root: &Path, path: &Path) -> Option<Self> {
+        let relative = path.strip_prefix(root).ok()?;
+
+        let components = relative.components()
+            .map(|c| match c {
+                Component::Normal(c) => c.to_str(),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        if components.is_empty() { return None; }
+
+        Some(Self(components.join("/")))
+    }
+
+    /// Of the file, the label is ignored.
+    pub fn to_fs(&self, root: &Path) -> PathBuf {
+        let mut result = root.to_path_buf();
+        result.extend(self.file_str().split('/'));
+
+        result
+    }
+
+    /// Without the scheme, e.g. shaders/src/BP_BRDF_v.vert.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn file_name(&self) -> &str {
+        let file = self.file_str();
+
+        file.rsplit('/').next().unwrap_or(file)
+    }
+
+    pub fn file_stem(&self) -> &str {
+        let name = self.file_name();
+
+        match name.rfind('.') {
+            Some(0) | None => name,
+            Some(i) => &name[..i],
+        }
+    }
+
+    pub fn extension(&self) -> Option<&str> {
+        let name = self.file_name();
+
+        match name.rfind('.') {
+            Some(0) | None => None,
+            Some(i) => Some(&name[i + 1..]),
+        }
+    }
+
+    /// Same path with .ext appended, e.g. for sidecar files.
+    pub fn with_added_extension(&self, extension: &str) -> Self {
+        Self(std::format!("{}.{}", self.0, extension))
+    }
+
+    pub fn label(&self) -> Option<&str> {
+        self.0
+            .split_once(LABEL_SEPARATOR)
+            .map(|(_, label)| label)
+    }
+
+    /// Without the label.
+    pub fn file(&self) -> Self {
+        Self(self.file_str().to_string())
+    }
+
+    /// The sub-asset label of the file, e.g. objects/cube.mtl -> objects/cube.mtl#Red.
+    pub fn with_label(&self, label: &str) -> Self {
+        Self(std::format!("{}{LABEL_SEPARATOR}{}", self.file_str(), label))
+    }
+
+    /// relative is resolved from the directory of this file, e.g. for the textures of an .mtl file.
+    pub fn sibling(&self, relative: &str) -> Result<Self, StdError> {
+        match self.file_str().rsplit_once('/') {
+            Some((dir, _)) => Self::new(&std::format!("{ASSET_SCHEME}{dir}/{relative}")),
+            None => Self::new(&std::format!("{ASSET_SCHEME}{relative}")),
+        }
+    }
+
+    /// Inverse of with_added_extension, None if path doesn't end with .ext.
+    pub fn strip_extension(&self, extension: &str) -> Option<Self> {
+        self.0
+            .strip_suffix(extension)
+            .and_then(|p| p.strip_suffix('.'))
+            .filter(|p| !p.is_empty() && !p.ends_with('/'))
+            .map(|p| Self(p.to_string()))
+    }
+
+    fn file_str(&self) -> &str {
+        self.0
+            .split_once(LABEL_SEPARATOR)
+            .map_or(&self.0, |(file, _)| file)
+    }
+}
+impl std::fmt::Display for AssetPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{ASSET_SCHEME}{}", self.0)
+    }
+}
+impl TryFrom<String> for AssetPath {
+    type Error = StdError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::new(&value)
+    }
+}
+impl From<AssetPath> for String {
+    fn from(value: AssetPath) -> Self {
+        value.to_string()
+    }
+}
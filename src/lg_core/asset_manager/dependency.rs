@@ -0,0 +1,119 @@
+use std::collections::{HashMap, HashSet};
+use crate::lg_core::{renderer::{material::Material, mesh::Mesh}, uuid::UUID};
+use super::AssetManager;
+
+/// Which assets reference which, by UUID, e.g. a material depends on its shaders and textures.
+///
+/// Kept by the AssetManager: .lgmat files when they are discovered, every material and mesh when it is loaded.
+#[derive(Debug, Default, Clone)]
+pub struct DependencyGraph {
+    dependencies: HashMap<UUID, HashSet<UUID>>,
+    dependents: HashMap<UUID, HashSet<UUID>>,
+}
+impl DependencyGraph {
+    /// Assets uuid references directly.
+    pub fn dependencies(&self, uuid: &UUID) -> impl Iterator<Item = &UUID> {
+        self.dependencies.get(uuid).into_iter().flatten()
+    }
+
+    /// Assets referencing uuid directly.
+    pub fn dependents(&self, uuid: &UUID) -> impl Iterator<Item = &UUID> {
+        self.dependents.get(uuid).into_iter().flatten()
+    }
+
+    /// Dependencies of the dependencies too, uuid is only in it if there is a cycle.
+    pub fn transitive_dependencies(&self, uuid: &UUID) -> HashSet<UUID> {
+        Self::walk(&self.dependencies, uuid)
+    }
+
+    /// Dependents of the dependents too, e.g. everything that breaks if uuid is deleted.
+    pub fn transitive_dependents(&self, uuid: &UUID) -> HashSet<UUID> {
+        Self::walk(&self.dependents, uuid)
+    }
+
+    /// (asset, reference) for every reference exists returns false for.
+    pub fn dangling(&self, exists: impl Fn(&UUID) -> bool) -> Vec<(UUID, UUID)> {
+        self.dependencies
+            .iter()
+            .flat_map(|(uuid, dependencies)| dependencies.iter().map(|d| (*uuid, *d)))
+            .filter(|(_, dependency)| !exists(dependency))
+            .collect()
+    }
+}
+// Public(crate)
+impl DependencyGraph {
+    /// Its shaders and textures.
+    pub(crate) fn set_material(&mut self, material: &Material) {
+        let dependencies = material.shaders()
+            .iter()
+            .chain(material.texture())
+            .copied();
+
+        self.set_dependencies(*material.uuid(), dependencies);
+    }
+
+    /// The materials of its sub-meshes.
+    pub(crate) fn set_mesh(&mut self, mesh: &Mesh) {
+        let dependencies = mesh.sub_meshes()
+            .iter()
+            .filter_map(|s| s.material);
+
+        self.set_dependencies(*mesh.uuid(), dependencies);
+    }
+}
+// Private
+impl DependencyGraph {
+    /// Replaces what uuid references.
+    fn set_dependencies(&mut self, uuid: UUID, dependencies: impl IntoIterator<Item = UUID>) {
+        for old in self.dependencies.remove(&uuid).unwrap_or_default() {
+            if let Some(dependents) = self.dependents.get_mut(&old) {
+                dependents.remove(&uuid);
+                if dependents.is_empty() {
+                    self.dependents.remove(&old);
+                }
+            }
+        }
+
+        let dependencies = dependencies.into_iter().collect::<HashSet<_>>();
+        if dependencies.is_empty() { return; }
+
+        for dependency in &dependencies {
+            self.dependents.entry(*dependency).or_default().insert(uuid);
+        }
+        self.dependencies.insert(uuid, dependencies);
+    }
+
+    fn walk(edges: &HashMap<UUID, HashSet<UUID>>, uuid: &UUID) -> HashSet<UUID> {
+        let mut result = HashSet::new();
+        let mut stack = vec![*uuid];
+
+        while let Some(next) = stack.pop() {
+            for edge in edges.get(&next).into_iter().flatten() {
+                if result.insert(*edge) {
+                    stack.push(*edge);
+                }
+            }
+        }
+
+        result
+    }
+}
+
+// Public
+impl AssetManager {
+    pub fn dependency_graph(&self) -> &DependencyGraph {
+        &self.dependency_graph
+    }
+
+    /// (asset, reference) for the references to assets that are neither registered nor loaded.
+    pub fn dangling_references(&self) -> Vec<(UUID, UUID)> {
+        self.dependency_graph.dangling(|uuid| {
+            self.assets_path.asset_paths.contains_key(uuid)
+                || self.assets_path.primitives.contains_key(uuid)
+                || self.textures.contains_key(uuid)
+                || self.meshes.contains_key(uuid)
+                || self.shaders.contains_key(uuid)
+                || self.materials.contains_key(uuid)
+        })
+    }
+}
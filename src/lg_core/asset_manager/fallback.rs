@@ -0,0 +1,199 @@
+use sllog::warn;
+use crate::lg_core::{renderer::{material::Material, mesh::Mesh, primitives::Primitive, sampler::SamplerSpecs, shader::{Shader, ShaderStage}, texture::{Texture, TextureFilter, TextureFormat, TextureKind, TextureSpecs}}, uuid::UUID};
+use super::AssetManager;
+
+/// Magenta and black checker, used for the textures that fail to load.
+pub const FALLBACK_TEXTURE: UUID = UUID::from_u128(0x4c47_4641_4c4c_4241_434b_0000_0000_0001);
+/// Unit cube, used for the meshes that fail to load.
+pub const FALLBACK_MESH: UUID = UUID::from_u128(0x4c47_4641_4c4c_4241_434b_0000_0000_0002);
+pub const FALLBACK_VERTEX_SHADER: UUID = UUID::from_u128(0x4c47_4641_4c4c_4241_434b_0000_0000_0003);
+pub const FALLBACK_FRAGMENT_SHADER: UUID = UUID::from_u128(0x4c47_4641_4c4c_4241_434b_0000_0000_0004);
+/// Flat magenta, used for the materials and shaders that fail to load.
+pub const FALLBACK_MATERIAL: UUID = UUID::from_u128(0x4c47_4641_4c4c_4241_434b_0000_0000_0005);
+
+/// [vertex, fragment] of the error material.
+pub(super) const FALLBACK_SHADERS: [UUID; 2] = [FALLBACK_VERTEX_SHADER, FALLBACK_FRAGMENT_SHADER];
+const FALLBACKS: [UUID; 5] = [FALLBACK_TEXTURE, FALLBACK_MESH, FALLBACK_VERTEX_SHADER, FALLBACK_FRAGMENT_SHADER, FALLBACK_MATERIAL];
+
+const CHECKER_SIZE: u32 = 64;
+const CHECKER_CELL: u32 = 8;
+
+/// Same inputs as instancing_v.vert and BP_BRDF_v.vert.
+const ERROR_VERTEX_SRC: &str = r#"#version 450
+
+layout(location = 0) in vec3 position;
+layout(location = 3) in vec4 row_0;
+layout(location = 4) in vec4 row_1;
+layout(location = 5) in vec4 row_2;
+
+layout(binding = 0) uniform ViewModel {
+    mat4 view;
+    mat4 proj;
+} view_model;
+
+void main() {
+    mat4 model = mat4(
+        vec4(row_0.x, row_1.x, row_2.x, 0),
+        vec4(row_0.y, row_1.y, row_2.y, 0),
+        vec4(row_0.z, row_1.z, row_2.z, 0),
+        vec4(row_0.w, row_1.w, row_2.w, 1)
+    );
+
+    gl_Position = view_model.proj * view_model.view * model * vec4(position, 1.0);
+}"#;
+
+const ERROR_FRAGMENT_SRC: &str = r#"#version 450
+
+out vec4 frag_color;
+
+void main() {
+    frag_color = vec4(1.0, 0.0, 1.0, 1.0);
+}"#;
+
+// Public
+impl AssetManager {
+    /// The fallback texture if uuid fails to load, warns once per UUID.
+    pub fn get_texture_or_fallback(&mut self, uuid: &UUID) -> &Texture {
+        let uuid = match self.get_texture(uuid) {
+            Ok(_) => *uuid,
+            Err(e) => {
+                self.warn_fallback(uuid, "texture", e.to_string());
+                FALLBACK_TEXTURE
+            },
+        };
+
+        &self.textures[&uuid]
+    }
+
+    /// The fallback cube if uuid fails to load, warns once per UUID.
+    pub fn get_mesh_or_fallback(&mut self, uuid: &UUID) -> &Mesh {
+        let uuid = match self.get_mesh(uuid) {
+            Ok(_) => *uuid,
+            Err(e) => {
+                self.warn_fallback(uuid, "mesh", e.to_string());
+                FALLBACK_MESH
+            },
+        };
+
+        &self.meshes[&uuid]
+    }
+
+    /// The fallback texture if uuid fails to load or is not a kind texture, warns once per UUID.
+    ///
+    /// The fallback is 2D, it samples as black where a cube map or an array is expected.
+    pub fn get_texture_of_kind_or_fallback(&mut self, uuid: &UUID, kind: TextureKind) -> &Texture {
+        let texture = self.get_texture_or_fallback(uuid);
+        let (texture_uuid, texture_kind) = (*texture.uuid(), texture.specs().tex_kind);
+
+        if texture_kind != kind && texture_uuid != FALLBACK_TEXTURE {
+            self.warn_fallback(uuid, "texture", std::format!("It is a {:?}, not a {:?}!", texture_kind, kind));
+            return &self.textures[&FALLBACK_TEXTURE];
+        }
+
+        &self.textures[&texture_uuid]
+    }
+
+    /// The error material if uuid fails to load, warns once per UUID.
+    pub fn get_material_or_fallback(&mut self, uuid: &UUID) -> &Material {
+        let uuid = match self.get_material(uuid) {
+            Ok(_) => *uuid,
+            Err(e) => {
+                self.warn_fallback(uuid, "material", e.to_string());
+                FALLBACK_MATERIAL
+            },
+        };
+
+        &self.materials[&uuid]
+    }
+}
+
+// Public(crate)
+impl AssetManager {
+    /// [vertex, fragment] of material, the error shaders if one of them fails to load.
+    pub(crate) fn material_shaders_or_fallback(&mut self, material: &UUID) -> [&Shader; 2] {
+        self.material_shader_uuids_or_fallback(material)
+            .map(|uuid| &self.shaders[&uuid])
+    }
+
+    /// Same as material_shaders_or_fallback, the shaders are in self.shaders.
+    pub(super) fn material_shader_uuids_or_fallback(&mut self, material: &UUID) -> [UUID; 2] {
+        match self.material_shader_uuids(material) {
+            Ok(shaders) => shaders,
+            Err(e) => {
+                self.warn_fallback(material, "shaders of the material", e.to_string());
+                FALLBACK_SHADERS
+            },
+        }
+    }
+
+    /// Loaded by get_texture_or_fallback, the fallback texture if it isn't.
+    pub(crate) fn loaded_texture_or_fallback(&self, uuid: &UUID) -> &Texture {
+        self.textures.get(uuid).unwrap_or(&self.textures[&FALLBACK_TEXTURE])
+    }
+
+    /// Loaded by get_texture_of_kind_or_fallback, the fallback texture if it isn't or isn't a kind texture.
+    pub(crate) fn loaded_texture_of_kind_or_fallback(&self, uuid: &UUID, kind: TextureKind) -> &Texture {
+        match self.textures.get(uuid) {
+            Some(texture) if texture.specs().tex_kind == kind => texture,
+            _ => &self.textures[&FALLBACK_TEXTURE],
+        }
+    }
+
+    /// Loaded by get_mesh_or_fallback, the fallback cube if it isn't.
+    pub(crate) fn loaded_mesh_or_fallback(&self, uuid: &UUID) -> &Mesh {
+        self.meshes.get(uuid).unwrap_or(&self.meshes[&FALLBACK_MESH])
+    }
+
+    /// Loaded by get_material_or_fallback, the error material if it isn't.
+    pub(crate) fn loaded_material_or_fallback(&self, uuid: &UUID) -> &Material {
+        self.materials.get(uuid).unwrap_or(&self.materials[&FALLBACK_MATERIAL])
+    }
+
+    /// Fallbacks are never destroyed, even when a handle to one of them drops.
+    pub(super) fn is_fallback(uuid: &UUID) -> bool {
+        FALLBACKS.contains(uuid)
+    }
+
+    /// Live as long as the AssetManager, see is_fallback.
+    pub(super) fn insert_fallbacks(&mut self) {
+        let checker = image::RgbaImage::from_fn(CHECKER_SIZE, CHECKER_SIZE, |x, y| {
+            match (x / CHECKER_CELL + y / CHECKER_CELL) % 2 {
+                0 => image::Rgba([255, 0, 255, 255]),
+                _ => image::Rgba([0, 0, 0, 255]),
+            }
+        });
+        let texture = Texture::from_image(FALLBACK_TEXTURE, "fallback_checker", &checker.into(), TextureSpecs {
+            tex_format: TextureFormat::RGBA,
+            tex_type: TextureFormat::RGBA.texture_type(),
+            sampler: SamplerSpecs::from_filter(TextureFilter::NEAREST),
+            ..Default::default()
+        });
+
+        let cube = Primitive::CUBE.mesh()
+            .expect("The fallback cube is always valid!");
+        let mesh = Mesh::new(FALLBACK_MESH, "fallback_cube", cube.vertices().to_vec(), cube.indices().to_vec());
+
+        let vertex = Shader::new(FALLBACK_VERTEX_SHADER, "error_v".to_string(), vec![], ShaderStage::VERTEX, ERROR_VERTEX_SRC.to_string());
+        let fragment = Shader::new(FALLBACK_FRAGMENT_SHADER, "error_f".to_string(), vec![], ShaderStage::FRAGMENT, ERROR_FRAGMENT_SRC.to_string());
+        let material = Material::new(FALLBACK_MATERIAL, "error", vec![FALLBACK_VERTEX_SHADER, FALLBACK_FRAGMENT_SHADER], vec![], vec![]);
+
+        self.textures.insert(FALLBACK_TEXTURE, texture);
+        self.meshes.insert(FALLBACK_MESH, mesh);
+        self.shaders.insert(FALLBACK_VERTEX_SHADER, vertex);
+        self.shaders.insert(FALLBACK_FRAGMENT_SHADER, fragment);
+        self.materials.insert(FALLBACK_MATERIAL, material);
+
+        self.to_init_gl.textures.push(FALLBACK_TEXTURE);
+        self.to_init_gl.meshes.push(FALLBACK_MESH);
+        self.to_init_gl.materials.push(FALLBACK_MATERIAL);
+    }
+}
+
+// Private
+impl AssetManager {
+    fn warn_fallback(&mut self, uuid: &UUID, kind: &str, error: String) {
+        if self.replaced.insert(*uuid) {
+            warn!("Using the fallback {kind} for {uuid}: {error} (AssetManager)");
+        }
+    }
+}
@@ -0,0 +1,730 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use serde::Deserialize;
+use crate::{glm, lg_core::{entity::{LgEntity, LgEntityNode}, renderer::{material::{Material, UniformValue}, mesh::{Mesh, SubMesh}, mesh_processing, texture::Texture, vertex::Vertex}, uuid::UUID}, profile_function, StdError};
+use super::{meta::{sub_asset_uuid, NormalGeneration}, AssetKind, AssetManager, AssetMeta, AssetPath, ImportContext, Vfs, IMPORTED_FRAGMENT_SHADER, IMPORTED_VERTEX_SHADER};
+
+/// External buffers of .gltf files, they are not assets.
+pub(super) const GLTF_BUFFER_EXTENSION: &str = "bin";
+
+const GLB_MAGIC: &[u8; 4] = b"glTF";
+const GLB_JSON_CHUNK: u32 = 0x4E4F534A;
+const GLB_BIN_CHUNK: u32 = 0x004E4942;
+const TRIANGLES: u32 = 4;
+
+pub(super) fn is_gltf_extension(extension: &str) -> bool {
+    matches!(extension, "gltf" | "glb")
+}
+
+/// Sub-assets of a glTF file: mesh{m}/primitive{p}, material{m} and image{i}.
+pub(super) fn label_kind(label: &str) -> Option<AssetKind> {
+    if label.starts_with("mesh") { Some(AssetKind::Mesh) }
+    else if label.starts_with("material") { Some(AssetKind::Material) }
+    else if label.starts_with("image") { Some(AssetKind::Texture) }
+    else { None }
+}
+
+fn primitive_label(mesh: usize, primitive: usize) -> String {
+    std::format!("mesh{mesh}/primitive{primitive}")
+}
+
+fn material_label(material: usize) -> String {
+    std::format!("material{material}")
+}
+
+fn image_label(image: usize) -> String {
+    std::format!("image{image}")
+}
+
+// Only what is imported, the JSON is read with serde_yaml since JSON is valid YAML.
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct Document {
+    scene: Option<usize>,
+    scenes: Vec<GltfScene>,
+    nodes: Vec<GltfNode>,
+    meshes: Vec<GltfMesh>,
+    materials: Vec<GltfMaterial>,
+    textures: Vec<GltfTexture>,
+    images: Vec<GltfImage>,
+    accessors: Vec<GltfAccessor>,
+    buffer_views: Vec<GltfBufferView>,
+    buffers: Vec<GltfBuffer>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct GltfScene {
+    nodes: Vec<usize>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct GltfNode {
+    name: Option<String>,
+    children: Vec<usize>,
+    mesh: Option<usize>,
+    matrix: Option<[f32; 16]>,
+    translation: Option<[f32; 3]>,
+    rotation: Option<[f32; 4]>,
+    scale: Option<[f32; 3]>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct GltfMesh {
+    name: Option<String>,
+    primitives: Vec<GltfPrimitive>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct GltfPrimitive {
+    attributes: HashMap<String, usize>,
+    indices: Option<usize>,
+    material: Option<usize>,
+    mode: Option<u32>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct GltfMaterial {
+    name: Option<String>,
+    pbr_metallic_roughness: Option<GltfPbr>,
+    emissive_texture: Option<GltfTextureInfo>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct GltfPbr {
+    base_color_factor: Option<[f32; 4]>,
+    base_color_texture: Option<GltfTextureInfo>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct GltfTextureInfo {
+    index: usize,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct GltfTexture {
+    source: Option<usize>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct GltfImage {
+    uri: Option<String>,
+    buffer_view: Option<usize>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct GltfAccessor {
+    buffer_view: Option<usize>,
+    byte_offset: usize,
+    component_type: u32,
+    count: usize,
+    #[serde(rename = "type")]
+    accessor_type: String,
+    normalized: bool,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct GltfBufferView {
+    buffer: usize,
+    byte_offset: usize,
+    byte_length: usize,
+    byte_stride: Option<usize>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct GltfBuffer {
+    uri: Option<String>,
+}
+
+/// A parsed .gltf or .glb file with the buffers it was loaded for.
+struct Gltf {
+    document: Document,
+    buffers: Vec<Vec<u8>>,
+}
+impl Gltf {
+    /// Only the JSON, no buffer is read.
+    fn document(bytes: &[u8]) -> Result<Document, StdError> {
+        let (json, _) = split_glb(bytes)?;
+
+        serializer::deserialize_bytes(json)
+    }
+
+    /// Only the buffers of the buffer views returned by views are read, the others stay empty.
+    ///
+    /// External buffers are read through the vfs, relative to path.
+    fn load(path: &AssetPath, bytes: &[u8], vfs: &Vfs, views: impl FnOnce(&Document) -> Vec<usize>) -> Result<Self, StdError> {
+        let (json, mut bin) = split_glb(bytes)?;
+        let document: Document = serializer::deserialize_bytes(json)?;
+
+        let needed = views(&document)
+            .iter()
+            .filter_map(|v| document.buffer_views.get(*v))
+            .map(|v| v.buffer)
+            .collect::<HashSet<_>>();
+
+        let buffers = document.buffers
+            .iter()
+            .enumerate()
+            .map(|(i, b)| match (&b.uri, needed.contains(&i)) {
+                (_, false) => Ok(Vec::new()),
+                (Some(uri), true) => read_uri(path, uri, vfs),
+                // Only the first buffer can be the GLB binary chunk.
+                (None, true) => bin.take().ok_or(std::format!("{} has a buffer without data! (glTF)", path).into()),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self { document, buffers })
+    }
+
+    fn buffer_view(&self, index: usize) -> Result<(&[u8], Option<usize>), StdError> {
+        let view = self.document.buffer_views
+            .get(index)
+            .ok_or(std::format!("Invalid buffer view {}! (glTF)", index))?;
+        let buffer = self.buffers
+            .get(view.buffer)
+            .ok_or(std::format!("Invalid buffer {}! (glTF)", view.buffer))?;
+
+        let data = buffer
+            .get(view.byte_offset..view.byte_offset + view.byte_length)
+            .ok_or(std::format!("Buffer view {} is out of bounds! (glTF)", index))?;
+
+        Ok((data, view.byte_stride))
+    }
+
+    /// Every component as f64 (exact for u32 indices), and the components per element.
+    fn accessor(&self, index: usize) -> Result<(Vec<f64>, usize), StdError> {
+        let accessor = self.document.accessors
+            .get(index)
+            .ok_or(std::format!("Invalid accessor {}! (glTF)", index))?;
+
+        let components = match accessor.accessor_type.as_str() {
+            "SCALAR" => 1,
+            "VEC2" => 2,
+            "VEC3" => 3,
+            "VEC4" => 4,
+            "MAT4" => 16,
+            other => return Err(std::format!("Unsupported accessor type {}! (glTF)", other).into()),
+        };
+        let size = match accessor.component_type {
+            5120 | 5121 => 1,
+            5122 | 5123 => 2,
+            5125 | 5126 => 4,
+            other => return Err(std::format!("Unsupported component type {}! (glTF)", other).into()),
+        };
+
+        // No buffer view means zeros.
+        let (data, stride) = match accessor.buffer_view {
+            Some(view) => self.buffer_view(view)?,
+            None => return Ok((vec![0.0; accessor.count * components], components)),
+        };
+        let stride = stride.unwrap_or(size * components);
+
+        let mut values = Vec::with_capacity(accessor.count * components);
+        for element in 0..accessor.count {
+            for component in 0..components {
+                let offset = accessor.byte_offset + element * stride + component * size;
+                let bytes = data
+                    .get(offset..offset + size)
+                    .ok_or(std::format!("Accessor {} is out of bounds! (glTF)", index))?;
+
+                values.push(read_component(bytes, accessor.component_type, accessor.normalized));
+            }
+        }
+
+        Ok((values, components))
+    }
+
+    fn image_bytes(&self, path: &AssetPath, index: usize, vfs: &Vfs) -> Result<Vec<u8>, StdError> {
+        let image = self.document.images
+            .get(index)
+            .ok_or(std::format!("{} has no image {}! (glTF)", path.file(), index))?;
+
+        match (&image.uri, image.buffer_view) {
+            (Some(uri), _) => read_uri(path, uri, vfs),
+            (None, Some(view)) => Ok(self.buffer_view(view)?.0.to_vec()),
+            (None, None) => Err(std::format!("Image {} of {} has no data! (glTF)", index, path.file()).into()),
+        }
+    }
+}
+
+// Public
+impl AssetManager {
+    /// The nodes of the default scene as entities, with their local transforms.
+    ///
+    /// Every primitive after the first of a node is a child entity, nodes without a mesh have UUID 0 as mesh and material,
+    /// so do primitives without a material. path is anything AssetPath::new accepts.
+    pub fn spawn_gltf(&self, path: &str) -> Result<Vec<LgEntityNode>, StdError> {
+        profile_function!();
+
+        let path = AssetPath::new(path)?;
+        let document = Gltf::document(&self.vfs.read(&path)?)?;
+
+        let roots = match document.scene.or(if document.scenes.is_empty() { None } else { Some(0) }) {
+            Some(scene) => document.scenes
+                .get(scene)
+                .ok_or(std::format!("{} has no scene {}! (glTF)", path, scene))?
+                .nodes
+                .clone(),
+            // Every node that is nobody's child.
+            None => (0..document.nodes.len())
+                .filter(|n| !document.nodes.iter().any(|other| other.children.contains(n)))
+                .collect(),
+        };
+
+        roots
+            .iter()
+            .map(|n| spawn_node(&path, &document, *n, 0))
+            .collect()
+    }
+}
+
+// Public(crate)
+impl AssetManager {
+    /// Registers the meshes, materials and images of the file, they have no .meta file.
+    ///
+    /// Base color and emissive images are imported as sRGB.
+    pub(super) fn store_gltf_assets(&mut self, path: &AssetPath) -> Result<Vec<UUID>, StdError> {
+        let document = Gltf::document(&self.vfs.read(path)?)?;
+        let mut uuids = Vec::new();
+
+        for (m, mesh) in document.meshes.iter().enumerate() {
+            for p in 0..mesh.primitives.len() {
+                let mesh_path = path.with_label(&primitive_label(m, p));
+                let meta = AssetMeta::new(sub_asset_uuid(&mesh_path)?);
+
+                uuids.push(self.register_asset(mesh_path, meta)?);
+            }
+        }
+
+        for m in 0..document.materials.len() {
+            let material_path = path.with_label(&material_label(m));
+            let meta = AssetMeta::new(sub_asset_uuid(&material_path)?);
+
+            uuids.push(self.register_asset(material_path, meta)?);
+        }
+
+        let color_images = document.materials
+            .iter()
+            .flat_map(|m| [m.pbr_metallic_roughness.as_ref().and_then(|pbr| pbr.base_color_texture.as_ref()), m.emissive_texture.as_ref()])
+            .flatten()
+            .filter_map(|t| document.textures.get(t.index)?.source)
+            .collect::<Vec<_>>();
+
+        for i in 0..document.images.len() {
+            let image_path = path.with_label(&image_label(i));
+            let mut meta = AssetMeta::new(sub_asset_uuid(&image_path)?);
+            meta.import.srgb = color_images.contains(&i);
+
+            uuids.push(self.register_asset(image_path, meta)?);
+        }
+
+        Ok(uuids)
+    }
+
+    /// One primitive, the label of path is mesh{m}/primitive{p}.
+    pub(super) fn import_gltf_mesh(path: &AssetPath, bytes: &[u8], context: &ImportContext) -> Result<Mesh, StdError> {
+        let (m, p) = path.label()
+            .and_then(|l| l.strip_prefix("mesh"))
+            .and_then(|l| l.split_once("/primitive"))
+            .and_then(|(m, p)| Some((m.parse::<usize>().ok()?, p.parse::<usize>().ok()?)))
+            .ok_or(std::format!("{} is not a glTF primitive! (glTF)", path))?;
+
+        let gltf = Gltf::load(path, bytes, context.vfs(), |document| {
+            let primitive = document.meshes.get(m).and_then(|mesh| mesh.primitives.get(p));
+
+            primitive
+                .into_iter()
+                .flat_map(|primitive| {
+                    ["POSITION", "NORMAL", "TEXCOORD_0"]
+                        .iter()
+                        .filter_map(|a| primitive.attributes.get(*a).copied())
+                        .chain(primitive.indices)
+                })
+                .filter_map(|a| document.accessors.get(a)?.buffer_view)
+                .collect()
+        })?;
+        let mesh = gltf.document.meshes
+            .get(m)
+            .ok_or(std::format!("{} has no mesh {}! (glTF)", path.file(), m))?;
+        let primitive = mesh.primitives
+            .get(p)
+            .ok_or(std::format!("{} has no primitive {}! (glTF)", path.file(), p))?;
+
+        if primitive.mode.unwrap_or(TRIANGLES) != TRIANGLES {
+            return Err(std::format!("{}: only triangle primitives are supported! (glTF)", path).into());
+        }
+
+        let attribute = |name: &str| -> Result<Option<(Vec<f64>, usize)>, StdError> {
+            primitive.attributes
+                .get(name)
+                .map(|a| gltf.accessor(*a))
+                .transpose()
+        };
+
+        let (positions, _) = attribute("POSITION")?
+            .ok_or(std::format!("{} has no positions! (glTF)", path))?;
+        let vertex_count = positions.len() / 3;
+        let normals = attribute("NORMAL")?
+            .map(|(n, _)| n)
+            .filter(|n| n.len() >= vertex_count * 3);
+        let tex_coords = attribute("TEXCOORD_0")?
+            .map(|(tc, _)| tc)
+            .filter(|tc| tc.len() >= vertex_count * 2);
+        let flip_uv = context.meta().import.flip_uv;
+
+        let vertices = (0..vertex_count)
+            .map(|i| {
+                let normal = match &normals {
+                    Some(n) => glm::vec3(n[i * 3] as f32, n[i * 3 + 1] as f32, n[i * 3 + 2] as f32),
+                    None => glm::Vec3::zeros(),
+                };
+
+                // glTF UVs start at the top left, which is already what the textures expect.
+                let tex_coord = match &tex_coords {
+                    Some(tc) => {
+                        let v = tc[i * 2 + 1] as f32;
+                        glm::vec2(-tc[i * 2] as f32, if flip_uv { 1.0 - v } else { v })
+                    },
+                    None => glm::Vec2::zeros(),
+                };
+
+                Vertex {
+                    position: glm::vec3(positions[i * 3] as f32, positions[i * 3 + 1] as f32, positions[i * 3 + 2] as f32),
+                    normal,
+                    tex_coord,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let indices = match primitive.indices {
+            Some(accessor) => gltf.accessor(accessor)?.0.iter().map(|i| *i as u32).collect(),
+            None => (0..vertex_count as u32).collect::<Vec<_>>(),
+        };
+        if indices.iter().any(|i| *i as usize >= vertex_count) {
+            return Err(std::format!("{} has indices out of bounds! (glTF)", path).into());
+        }
+
+        let (mut vertices, indices) = match (normals.is_some(), context.meta().import.normals) {
+            (true, _) => (vertices, indices),
+            (false, NormalGeneration::Smooth) => {
+                let mut vertices = vertices;
+                mesh_processing::smooth_normals(&mut vertices, &indices);
+
+                (vertices, indices)
+            },
+            (false, NormalGeneration::Flat) => mesh_processing::flat_normals(&vertices, &indices),
+        };
+        if tex_coords.is_none() {
+            mesh_processing::box_uvs(&mut vertices);
+        }
+
+        let name = mesh.name.clone().unwrap_or_else(|| std::format!("{}_{}", path.file_stem(), m));
+        let material = primitive.material
+            .map(|mat| sub_asset_uuid(&path.with_label(&material_label(mat))))
+            .transpose()?;
+
+        let sub_meshes = vec![SubMesh {
+            name: name.clone(),
+            first_index: 0,
+            index_count: indices.len() as u32,
+            material,
+        }];
+
+        Ok(Mesh::with_sub_meshes(
+            context.meta().uuid,
+            &name,
+            vertices,
+            indices,
+            sub_meshes
+        ))
+    }
+
+    /// The base color texture is the only texture, the base color factor is the base_color_factor parameter.
+    pub(super) fn import_gltf_material(path: &AssetPath, bytes: &[u8], context: &ImportContext) -> Result<Material, StdError> {
+        let m = path.label()
+            .and_then(|l| l.strip_prefix("material"))
+            .and_then(|m| m.parse::<usize>().ok())
+            .ok_or(std::format!("{} is not a glTF material! (glTF)", path))?;
+
+        let document = Gltf::document(bytes)?;
+        let material = document.materials
+            .get(m)
+            .ok_or(std::format!("{} has no material {}! (glTF)", path.file(), m))?;
+
+        let textures = material.pbr_metallic_roughness
+            .as_ref()
+            .and_then(|pbr| pbr.base_color_texture.as_ref())
+            .and_then(|t| document.textures.get(t.index)?.source)
+            .map(|image| sub_asset_uuid(&path.with_label(&image_label(image))))
+            .transpose()?
+            .into_iter()
+            .collect();
+
+        let parameters = material.pbr_metallic_roughness
+            .as_ref()
+            .and_then(|pbr| pbr.base_color_factor)
+            .map(|factor| (String::from("base_color_factor"), UniformValue::VEC4(factor)))
+            .into_iter()
+            .collect::<BTreeMap<_, _>>();
+
+        let shaders = vec![
+            context.resolve(IMPORTED_VERTEX_SHADER)?,
+            context.resolve(IMPORTED_FRAGMENT_SHADER)?,
+        ];
+
+        let name = material.name.clone().unwrap_or_else(|| std::format!("{}_{}", path.file_stem(), material_label(m)));
+
+        Ok(Material::new(
+            context.meta().uuid,
+            &name,
+            shaders,
+            textures,
+            vec![]
+        ).with_parameters(parameters))
+    }
+
+    /// Embedded (data URI or buffer view) or external images.
+    pub(super) fn import_gltf_texture(path: &AssetPath, bytes: &[u8], context: &ImportContext) -> Result<Texture, StdError> {
+        let i = path.label()
+            .and_then(|l| l.strip_prefix("image"))
+            .and_then(|i| i.parse::<usize>().ok())
+            .ok_or(std::format!("{} is not a glTF image! (glTF)", path))?;
+
+        let gltf = Gltf::load(path, bytes, context.vfs(), |document| {
+            document.images.get(i).and_then(|image| image.buffer_view).into_iter().collect()
+        })?;
+        let image = gltf.image_bytes(path, i, context.vfs())?;
+
+        Self::import_image(path, &image, context)
+    }
+}
+
+fn spawn_node(path: &AssetPath, document: &Document, index: usize, depth: usize) -> Result<LgEntityNode, StdError> {
+    // Cycles are invalid glTF, this stops them.
+    if depth > document.nodes.len() {
+        return Err(std::format!("{} has a cycle in its nodes! (glTF)", path).into());
+    }
+
+    let node = document.nodes
+        .get(index)
+        .ok_or(std::format!("{} has no node {}! (glTF)", path, index))?;
+    let name = node.name.clone().unwrap_or_else(|| std::format!("node{index}"));
+
+    let primitives = match node.mesh {
+        Some(m) => {
+            let mesh = document.meshes
+                .get(m)
+                .ok_or(std::format!("{} has no mesh {}! (glTF)", path, m))?;
+
+            mesh.primitives
+                .iter()
+                .enumerate()
+                .map(|(p, primitive)| Ok((
+                    sub_asset_uuid(&path.with_label(&primitive_label(m, p)))?,
+                    primitive.material
+                        .map(|mat| sub_asset_uuid(&path.with_label(&material_label(mat))))
+                        .transpose()?
+                        .unwrap_or_default(),
+                )))
+                .collect::<Result<Vec<_>, StdError>>()?
+        },
+        None => Vec::new(),
+    };
+
+    let (mesh, material) = primitives.first().copied().unwrap_or_default();
+    let mut entity = LgEntity::new(&name, mesh, material, glm::Vec3::zeros());
+    set_local_transform(&mut entity, node);
+
+    let mut children = primitives
+        .iter()
+        .enumerate()
+        .skip(1)
+        .map(|(p, (mesh, material))| LgEntityNode {
+            entity: LgEntity::new(&std::format!("{name}/primitive{p}"), *mesh, *material, glm::Vec3::zeros()),
+            children: Vec::new(),
+        })
+        .collect::<Vec<_>>();
+
+    for child in &node.children {
+        children.push(spawn_node(path, document, *child, depth + 1)?);
+    }
+
+    Ok(LgEntityNode { entity, children })
+}
+
+fn set_local_transform(entity: &mut LgEntity, node: &GltfNode) {
+    let (translation, rotation, scale) = match node.matrix {
+        Some(matrix) => decompose(&glm::make_mat4(&matrix)),
+        None => (
+            node.translation.map_or(glm::Vec3::zeros(), |t| glm::make_vec3(&t)),
+            // glTF quaternions are x, y, z, w.
+            node.rotation.map_or(glm::Quat::identity(), |r| glm::quat(r[0], r[1], r[2], r[3])),
+            node.scale.map_or(glm::vec3(1.0, 1.0, 1.0), |s| glm::make_vec3(&s)),
+        ),
+    };
+
+    let angle = glm::quat_angle(&rotation);
+    let axis = glm::quat_axis(&rotation);
+
+    entity.set_position(translation);
+    entity.set_scale(scale);
+    // LgEntity can't take a zero axis, even with no rotation.
+    if angle.abs() > f32::EPSILON && axis.norm() > f32::EPSILON {
+        entity.set_rotation_axis(axis);
+        entity.set_rotation_angle(angle);
+    }
+    else {
+        entity.set_rotation_axis(glm::vec3(0.0, 1.0, 0.0));
+        entity.set_rotation_angle(0.0);
+    }
+}
+
+/// Translation, rotation and scale of a matrix without shear.
+fn decompose(matrix: &glm::Mat4) -> (glm::Vec3, glm::Quat, glm::Vec3) {
+    let translation = glm::vec3(matrix[(0, 3)], matrix[(1, 3)], matrix[(2, 3)]);
+    let columns = [0, 1, 2].map(|c| glm::vec3(matrix[(0, c)], matrix[(1, c)], matrix[(2, c)]));
+    let scale = glm::vec3(columns[0].norm(), columns[1].norm(), columns[2].norm());
+
+    let rotation = glm::mat3(
+        columns[0].x / scale.x, columns[1].x / scale.y, columns[2].x / scale.z,
+        columns[0].y / scale.x, columns[1].y / scale.y, columns[2].y / scale.z,
+        columns[0].z / scale.x, columns[1].z / scale.y, columns[2].z / scale.z,
+    );
+
+    (translation, glm::mat3_to_quat(&rotation), scale)
+}
+
+fn read_component(bytes: &[u8], component_type: u32, normalized: bool) -> f64 {
+    match (component_type, normalized) {
+        (5120, false) => bytes[0] as i8 as f64,
+        (5120, true) => (bytes[0] as i8 as f64 / 127.0).max(-1.0),
+        (5121, false) => bytes[0] as f64,
+        (5121, true) => bytes[0] as f64 / 255.0,
+        (5122, false) => i16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+        (5122, true) => (i16::from_le_bytes([bytes[0], bytes[1]]) as f64 / 32767.0).max(-1.0),
+        (5123, false) => u16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+        (5123, true) => u16::from_le_bytes([bytes[0], bytes[1]]) as f64 / 65535.0,
+        (5125, _) => u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+        _ => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+    }
+}
+
+/// The JSON and the binary chunk of a .glb, or the whole file for a .gltf.
+fn split_glb(bytes: &[u8]) -> Result<(&[u8], Option<Vec<u8>>), StdError> {
+    if !bytes.starts_with(GLB_MAGIC) {
+        return Ok((bytes, None));
+    }
+
+    let u32_at = |offset: usize| -> Result<u32, StdError> {
+        let b = bytes
+            .get(offset..offset + 4)
+            .ok_or("Truncated GLB file! (glTF)")?;
+
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    };
+
+    let mut json = None;
+    let mut bin = None;
+    // Header: magic, version, length.
+    let mut offset = 12;
+    while offset < bytes.len() {
+        let length = u32_at(offset)? as usize;
+        let chunk_type = u32_at(offset + 4)?;
+        let data = bytes
+            .get(offset + 8..offset + 8 + length)
+            .ok_or("Truncated GLB chunk! (glTF)")?;
+
+        match chunk_type {
+            GLB_JSON_CHUNK => json = Some(data),
+            GLB_BIN_CHUNK => bin = Some(data.to_vec()),
+            // Unknown chunks must be ignored.
+            _ => (),
+        }
+
+        offset += 8 + length;
+    }
+
+    Ok((json.ok_or("GLB file without JSON chunk! (glTF)")?, bin))
+}
+
+/// data: URIs are decoded, anything else is a file relative to path.
+fn read_uri(path: &AssetPath, uri: &str, vfs: &Vfs) -> Result<Vec<u8>, StdError> {
+    if let Some(data) = uri.strip_prefix("data:") {
+        let (header, data) = data
+            .split_once(',')
+            .ok_or("Invalid data URI! (glTF)")?;
+
+        return match header.ends_with(";base64") {
+            true => decode_base64(data),
+            false => Ok(percent_decode(data).into_bytes()),
+        };
+    }
+
+    vfs.read(&path.sibling(&percent_decode(uri))?)
+}
+
+fn decode_base64(data: &str) -> Result<Vec<u8>, StdError> {
+    let value = |c: u8| -> Result<u32, StdError> {
+        Ok(match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => return Err(std::format!("Invalid base64 character {}! (glTF)", c as char).into()),
+        } as u32)
+    };
+
+    let data = data
+        .bytes()
+        .filter(|c| !c.is_ascii_whitespace() && *c != b'=')
+        .collect::<Vec<_>>();
+
+    let mut result = Vec::with_capacity(data.len() * 3 / 4);
+    for chunk in data.chunks(4) {
+        let mut bits = 0u32;
+        for (i, c) in chunk.iter().enumerate() {
+            bits |= value(*c)? << (18 - 6 * i);
+        }
+
+        let bytes = bits.to_be_bytes();
+        result.extend_from_slice(&bytes[1..chunk.len()]);
+    }
+
+    Ok(result)
+}
+
+fn percent_decode(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut result = Vec::with_capacity(bytes.len());
+
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                result.push(byte);
+                i += 3;
+            },
+            (byte, _) => {
+                result.push(byte);
+                i += 1;
+            },
+        }
+    }
+
+    String::from_utf8_lossy(&result).into_owned()
+}
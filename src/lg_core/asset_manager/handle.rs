@@ -0,0 +1,368 @@
+use std::{collections::{hash_map::Entry, HashMap}, hash::Hash, marker::PhantomData, sync::{Arc, Mutex, Weak}};
+use crate::{lg_core::{renderer::{material::Material, mesh::Mesh, shader::Shader, texture::Texture}, uuid::UUID}, StdError};
+use super::{loader::LoadState, AssetManager, AssetPath, AssetStorage, ImportContext};
+
+/// Shared by every strong handle of an asset, queues the asset on to_destroy when the last one drops.
+#[derive(Debug)]
+pub(crate) struct HandleInner {
+    uuid: UUID,
+    kind: AssetKind,
+    state: Mutex<LoadState>,
+    to_destroy: Arc<Mutex<AssetStorage>>,
+}
+impl HandleInner {
+    pub(crate) fn uuid(&self) -> &UUID {
+        &self.uuid
+    }
+
+    pub(crate) fn state(&self) -> LoadState {
+        self.state.lock().unwrap().clone()
+    }
+
+    pub(crate) fn set_state(&self, state: LoadState) {
+        *self.state.lock().unwrap() = state;
+    }
+}
+impl Drop for HandleInner {
+    fn drop(&mut self) {
+        let mut to_destroy = match self.to_destroy.lock() {
+            Ok(to_destroy) => to_destroy,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        match self.kind {
+            AssetKind::Texture => to_destroy.textures.push(self.uuid),
+            AssetKind::Mesh => to_destroy.meshes.push(self.uuid),
+            AssetKind::Shader => to_destroy.shaders.push(self.uuid),
+            AssetKind::Material => to_destroy.materials.push(self.uuid),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AssetKind {
+    Texture,
+    Mesh,
+    Shader,
+    Material,
+}
+
+/// Strong, reference counted handle to an asset of the AssetManager.
+///
+/// Safe to store, the asset stays loaded while a strong handle exists and is resolved through the AssetManager.
+pub struct Handle<T> {
+    pub(super) inner: Arc<HandleInner>,
+    _marker: PhantomData<fn() -> T>,
+}
+impl<T> Handle<T> {
+    pub fn uuid(&self) -> &UUID {
+        &self.inner.uuid
+    }
+
+    /// Loaded once it can be resolved.
+    pub fn state(&self) -> LoadState {
+        self.inner.state()
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        self.inner.state() == LoadState::Loaded
+    }
+
+    /// How many strong handles point to this asset.
+    pub fn strong_count(&self) -> usize {
+        Arc::strong_count(&self.inner)
+    }
+
+    pub fn downgrade(&self) -> WeakHandle<T> {
+        WeakHandle {
+            uuid: self.inner.uuid,
+            inner: Arc::downgrade(&self.inner),
+            _marker: PhantomData,
+        }
+    }
+}
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+            _marker: PhantomData,
+        }
+    }
+}
+impl<T> std::fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Handle")
+            .field("uuid", &self.inner.uuid)
+            .field("kind", &self.inner.kind)
+            .finish()
+    }
+}
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner.uuid == other.inner.uuid
+    }
+}
+impl<T> Eq for Handle<T> {}
+impl<T> Hash for Handle<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.inner.uuid.hash(state);
+    }
+}
+
+/// Doesn't keep the asset loaded.
+pub struct WeakHandle<T> {
+    uuid: UUID,
+    inner: Weak<HandleInner>,
+    _marker: PhantomData<fn() -> T>,
+}
+impl<T> WeakHandle<T> {
+    pub fn uuid(&self) -> &UUID {
+        &self.uuid
+    }
+
+    /// None if every strong handle was dropped.
+    pub fn upgrade(&self) -> Option<Handle<T>> {
+        Some(Handle {
+            inner: self.inner.upgrade()?,
+            _marker: PhantomData,
+        })
+    }
+}
+impl<T> Clone for WeakHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            uuid: self.uuid,
+            inner: Weak::clone(&self.inner),
+            _marker: PhantomData,
+        }
+    }
+}
+impl<T> std::fmt::Debug for WeakHandle<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WeakHandle")
+            .field("uuid", &self.uuid)
+            .finish()
+    }
+}
+
+/// Live handles of every asset kind.
+#[derive(Debug, Default)]
+pub(crate) struct HandleRegistry {
+    textures: HashMap<UUID, Weak<HandleInner>>,
+    meshes: HashMap<UUID, Weak<HandleInner>>,
+    shaders: HashMap<UUID, Weak<HandleInner>>,
+    materials: HashMap<UUID, Weak<HandleInner>>,
+}
+impl HandleRegistry {
+    /// Strong handles alive for the asset.
+    pub(crate) fn count(&self, kind: AssetKind, uuid: &UUID) -> usize {
+        self.map(kind)
+            .get(uuid)
+            .map_or(0, Weak::strong_count)
+    }
+
+    pub(crate) fn get(&self, kind: AssetKind, uuid: &UUID) -> Option<Arc<HandleInner>> {
+        self.map(kind)
+            .get(uuid)
+            .and_then(Weak::upgrade)
+    }
+
+    /// Forgets the handles that were dropped.
+    pub(crate) fn clean(&mut self) {
+        for map in [&mut self.textures, &mut self.meshes, &mut self.shaders, &mut self.materials] {
+            map.retain(|_, h| h.strong_count() > 0);
+        }
+    }
+
+    fn map(&self, kind: AssetKind) -> &HashMap<UUID, Weak<HandleInner>> {
+        match kind {
+            AssetKind::Texture => &self.textures,
+            AssetKind::Mesh => &self.meshes,
+            AssetKind::Shader => &self.shaders,
+            AssetKind::Material => &self.materials,
+        }
+    }
+
+    fn map_mut(&mut self, kind: AssetKind) -> &mut HashMap<UUID, Weak<HandleInner>> {
+        match kind {
+            AssetKind::Texture => &mut self.textures,
+            AssetKind::Mesh => &mut self.meshes,
+            AssetKind::Shader => &mut self.shaders,
+            AssetKind::Material => &mut self.materials,
+        }
+    }
+}
+
+/// Implemented by every type the AssetManager stores.
+pub trait Asset: Sized + Send + 'static {
+    #[doc(hidden)]
+    const KIND: AssetKind;
+
+    #[doc(hidden)]
+    fn storage(asset_manager: &AssetManager) -> &HashMap<UUID, Self>;
+
+    /// Loads the asset if needed.
+    #[doc(hidden)]
+    fn load(asset_manager: &mut AssetManager, uuid: &UUID) -> Result<(), StdError>;
+
+    #[doc(hidden)]
+    fn path(asset_manager: &AssetManager, uuid: &UUID) -> Option<AssetPath>;
+
+    /// Decodes the bytes, doesn't touch the AssetManager so it can run on any thread.
+    #[doc(hidden)]
+    fn import(path: &AssetPath, bytes: &[u8], context: &ImportContext) -> Result<Self, StdError>;
+
+    /// Does nothing if it was loaded in the meantime.
+    #[doc(hidden)]
+    fn insert(asset_manager: &mut AssetManager, asset: Self);
+}
+impl Asset for Texture {
+    const KIND: AssetKind = AssetKind::Texture;
+
+    fn storage(asset_manager: &AssetManager) -> &HashMap<UUID, Self> {
+        &asset_manager.textures
+    }
+
+    fn load(asset_manager: &mut AssetManager, uuid: &UUID) -> Result<(), StdError> {
+        asset_manager.get_texture(uuid).map(|_| ())
+    }
+
+    fn path(asset_manager: &AssetManager, uuid: &UUID) -> Option<AssetPath> {
+        asset_manager.assets_path.textures.get(uuid).cloned()
+    }
+
+    fn import(path: &AssetPath, bytes: &[u8], context: &ImportContext) -> Result<Self, StdError> {
+        AssetManager::import_texture(path, bytes, context)
+    }
+
+    fn insert(asset_manager: &mut AssetManager, asset: Self) {
+        if let Entry::Vacant(entry) = asset_manager.textures.entry(*asset.uuid()) {
+            asset_manager.to_init_gl.textures.push(*asset.uuid());
+            entry.insert(asset);
+        }
+    }
+}
+impl Asset for Mesh {
+    const KIND: AssetKind = AssetKind::Mesh;
+
+    fn storage(asset_manager: &AssetManager) -> &HashMap<UUID, Self> {
+        &asset_manager.meshes
+    }
+
+    fn load(asset_manager: &mut AssetManager, uuid: &UUID) -> Result<(), StdError> {
+        asset_manager.get_mesh(uuid).map(|_| ())
+    }
+
+    fn path(asset_manager: &AssetManager, uuid: &UUID) -> Option<AssetPath> {
+        asset_manager.assets_path.meshes.get(uuid).cloned()
+    }
+
+    fn import(path: &AssetPath, bytes: &[u8], context: &ImportContext) -> Result<Self, StdError> {
+        AssetManager::import_mesh(path, bytes, context)
+    }
+
+    fn insert(asset_manager: &mut AssetManager, asset: Self) {
+        if let Entry::Vacant(entry) = asset_manager.meshes.entry(*asset.uuid()) {
+            asset_manager.to_init_gl.meshes.push(*asset.uuid());
+            asset_manager.dependency_graph.set_mesh(&asset);
+            entry.insert(asset);
+        }
+    }
+}
+impl Asset for Shader {
+    const KIND: AssetKind = AssetKind::Shader;
+
+    fn storage(asset_manager: &AssetManager) -> &HashMap<UUID, Self> {
+        &asset_manager.shaders
+    }
+
+    fn load(asset_manager: &mut AssetManager, uuid: &UUID) -> Result<(), StdError> {
+        asset_manager.get_shader(uuid).map(|_| ())
+    }
+
+    fn path(asset_manager: &AssetManager, uuid: &UUID) -> Option<AssetPath> {
+        asset_manager.assets_path.shaders.get(uuid).cloned()
+    }
+
+    fn import(path: &AssetPath, bytes: &[u8], context: &ImportContext) -> Result<Self, StdError> {
+        AssetManager::import_shader(path, bytes, context)
+    }
+
+    fn insert(asset_manager: &mut AssetManager, asset: Self) {
+        if let Entry::Vacant(entry) = asset_manager.shaders.entry(*asset.uuid()) {
+            entry.insert(asset);
+        }
+    }
+}
+impl Asset for Material {
+    const KIND: AssetKind = AssetKind::Material;
+
+    fn storage(asset_manager: &AssetManager) -> &HashMap<UUID, Self> {
+        &asset_manager.materials
+    }
+
+    fn load(asset_manager: &mut AssetManager, uuid: &UUID) -> Result<(), StdError> {
+        asset_manager.get_material(uuid).map(|_| ())
+    }
+
+    fn path(asset_manager: &AssetManager, uuid: &UUID) -> Option<AssetPath> {
+        asset_manager.assets_path.materials.get(uuid).cloned()
+    }
+
+    fn import(path: &AssetPath, bytes: &[u8], context: &ImportContext) -> Result<Self, StdError> {
+        AssetManager::import_material(path, bytes, context)
+    }
+
+    fn insert(asset_manager: &mut AssetManager, asset: Self) {
+        if let Entry::Vacant(entry) = asset_manager.materials.entry(*asset.uuid()) {
+            asset_manager.to_init_gl.materials.push(*asset.uuid());
+            asset_manager.dependency_graph.set_material(&asset);
+            entry.insert(asset);
+        }
+    }
+}
+
+// Public
+impl AssetManager {
+    /// Loads the asset if needed and returns a strong handle to it.
+    pub fn handle<T: Asset>(&mut self, uuid: &UUID) -> Result<Handle<T>, StdError> {
+        T::load(self, uuid)?;
+
+        let handle = self.new_handle(uuid, LoadState::Loaded);
+        // It may have been loading in the background.
+        handle.inner.set_state(LoadState::Loaded);
+
+        Ok(handle)
+    }
+
+    /// None if the asset isn't loaded: the handle is still Loading, its load Failed, or it is of another AssetManager.
+    pub fn resolve<T: Asset>(&self, handle: &Handle<T>) -> Option<&T> {
+        T::storage(self).get(handle.uuid())
+    }
+
+    /// How many strong handles point to the asset.
+    pub fn handle_count<T: Asset>(&self, uuid: &UUID) -> usize {
+        self.handles.count(T::KIND, uuid)
+    }
+}
+
+// Public(crate)
+impl AssetManager {
+    /// The existing handle if there is one, state is only used for a new one.
+    pub(crate) fn new_handle<T: Asset>(&mut self, uuid: &UUID, state: LoadState) -> Handle<T> {
+        let handles = self.handles.map_mut(T::KIND);
+        if let Some(inner) = handles.get(uuid).and_then(Weak::upgrade) {
+            return Handle { inner, _marker: PhantomData };
+        }
+
+        let inner = Arc::new(HandleInner {
+            uuid: *uuid,
+            kind: T::KIND,
+            state: Mutex::new(state),
+            to_destroy: Arc::clone(&self.to_destroy),
+        });
+        handles.insert(*uuid, Arc::downgrade(&inner));
+
+        Handle { inner, _marker: PhantomData }
+    }
+}
@@ -0,0 +1,203 @@
+use std::{collections::HashMap, sync::Arc};
+use crate::{lg_core::renderer::{material::Material, mesh::Mesh, shader::Shader, texture::Texture}, StdError};
+use super::{gltf, lgmat::LGMAT_EXTENSION, obj::MTL_EXTENSION, AssetKind, AssetManager, AssetPath, ImportContext};
+
+/// What an AssetImporter produces.
+#[derive(Debug)]
+pub enum ImportedAsset {
+    Texture(Texture),
+    Mesh(Mesh),
+    Shader(Shader),
+    Material(Material),
+}
+impl ImportedAsset {
+    pub fn kind(&self) -> AssetKind {
+        match self {
+            ImportedAsset::Texture(_) => AssetKind::Texture,
+            ImportedAsset::Mesh(_) => AssetKind::Mesh,
+            ImportedAsset::Shader(_) => AssetKind::Shader,
+            ImportedAsset::Material(_) => AssetKind::Material,
+        }
+    }
+}
+
+macro_rules! imported_asset {
+    ($($kind:ident),*) => {
+        $(
+            impl TryFrom<ImportedAsset> for $kind {
+                type Error = StdError;
+
+                fn try_from(asset: ImportedAsset) -> Result<Self, Self::Error> {
+                    match asset {
+                        ImportedAsset::$kind(asset) => Ok(asset),
+                        asset => Err(std::format!("Imported a {:?} instead of a {}! (AssetManager)", asset.kind(), stringify!($kind)).into()),
+                    }
+                }
+            }
+        )*
+    };
+}
+imported_asset!(Texture, Mesh, Shader, Material);
+
+/// Turns the bytes of a file into an engine asset, registered per extension with AssetManager::register_importer.
+///
+/// Async loads call it on the rayon pool, so it only gets the file and the ImportContext.
+pub trait AssetImporter: Send + Sync {
+    /// Kind of the asset at path, None if it isn't one (e.g. a glTF file without a label).
+    fn kind(&self, path: &AssetPath) -> Option<AssetKind>;
+
+    /// The UUID of the asset is context.meta().uuid.
+    fn import(&self, path: &AssetPath, bytes: &[u8], context: &ImportContext) -> Result<ImportedAsset, StdError>;
+}
+
+/// Importers by lowercase extension.
+#[derive(Clone)]
+pub(crate) struct ImporterRegistry {
+    importers: HashMap<String, Arc<dyn AssetImporter>>,
+}
+impl Default for ImporterRegistry {
+    fn default() -> Self {
+        let mut result = Self { importers: HashMap::default() };
+
+        for extension in ["png", "jpg", "jpeg", "tga", "bmp", "hdr", "exr"] {
+            result.register(extension, Arc::new(ImageImporter));
+        }
+        result.register("obj", Arc::new(ObjImporter));
+        result.register("ply", Arc::new(PlyImporter));
+        result.register("stl", Arc::new(StlImporter));
+        for extension in ["vert", "frag"] {
+            result.register(extension, Arc::new(ShaderImporter));
+        }
+        result.register(LGMAT_EXTENSION, Arc::new(LgmatImporter));
+        result.register(MTL_EXTENSION, Arc::new(MtlImporter));
+        for extension in ["gltf", "glb"] {
+            result.register(extension, Arc::new(GltfImporter));
+        }
+
+        result
+    }
+}
+impl std::fmt::Debug for ImporterRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set()
+            .entries(self.importers.keys())
+            .finish()
+    }
+}
+impl ImporterRegistry {
+    /// Returns the importer it replaces.
+    pub(crate) fn register(&mut self, extension: &str, importer: Arc<dyn AssetImporter>) -> Option<Arc<dyn AssetImporter>> {
+        self.importers.insert(extension.trim_start_matches('.').to_ascii_lowercase(), importer)
+    }
+
+    pub(crate) fn get(&self, path: &AssetPath) -> Option<&Arc<dyn AssetImporter>> {
+        self.importers.get(&path.extension()?.to_ascii_lowercase())
+    }
+
+    pub(crate) fn kind_of(&self, path: &AssetPath) -> Option<AssetKind> {
+        self.get(path)?.kind(path)
+    }
+
+    pub(crate) fn import(&self, path: &AssetPath, bytes: &[u8], context: &ImportContext) -> Result<ImportedAsset, StdError> {
+        self.get(path)
+            .ok_or(std::format!("There is no importer for {}! (AssetManager)", path))?
+            .import(path, bytes, context)
+    }
+}
+
+/// Everything the image crate decodes.
+struct ImageImporter;
+impl AssetImporter for ImageImporter {
+    fn kind(&self, _path: &AssetPath) -> Option<AssetKind> {
+        Some(AssetKind::Texture)
+    }
+
+    fn import(&self, path: &AssetPath, bytes: &[u8], context: &ImportContext) -> Result<ImportedAsset, StdError> {
+        AssetManager::import_image(path, bytes, context).map(ImportedAsset::Texture)
+    }
+}
+
+struct ObjImporter;
+impl AssetImporter for ObjImporter {
+    fn kind(&self, _path: &AssetPath) -> Option<AssetKind> {
+        Some(AssetKind::Mesh)
+    }
+
+    fn import(&self, path: &AssetPath, bytes: &[u8], context: &ImportContext) -> Result<ImportedAsset, StdError> {
+        AssetManager::import_obj(path, bytes, context).map(ImportedAsset::Mesh)
+    }
+}
+
+struct PlyImporter;
+impl AssetImporter for PlyImporter {
+    fn kind(&self, _path: &AssetPath) -> Option<AssetKind> {
+        Some(AssetKind::Mesh)
+    }
+
+    fn import(&self, path: &AssetPath, bytes: &[u8], context: &ImportContext) -> Result<ImportedAsset, StdError> {
+        AssetManager::import_ply(path, bytes, context).map(ImportedAsset::Mesh)
+    }
+}
+
+struct StlImporter;
+impl AssetImporter for StlImporter {
+    fn kind(&self, _path: &AssetPath) -> Option<AssetKind> {
+        Some(AssetKind::Mesh)
+    }
+
+    fn import(&self, path: &AssetPath, bytes: &[u8], context: &ImportContext) -> Result<ImportedAsset, StdError> {
+        AssetManager::import_stl(path, bytes, context).map(ImportedAsset::Mesh)
+    }
+}
+
+/// GLSL source, the stage is the extension.
+struct ShaderImporter;
+impl AssetImporter for ShaderImporter {
+    fn kind(&self, _path: &AssetPath) -> Option<AssetKind> {
+        Some(AssetKind::Shader)
+    }
+
+    fn import(&self, path: &AssetPath, bytes: &[u8], context: &ImportContext) -> Result<ImportedAsset, StdError> {
+        AssetManager::import_glsl(path, bytes, context).map(ImportedAsset::Shader)
+    }
+}
+
+struct LgmatImporter;
+impl AssetImporter for LgmatImporter {
+    fn kind(&self, _path: &AssetPath) -> Option<AssetKind> {
+        Some(AssetKind::Material)
+    }
+
+    fn import(&self, path: &AssetPath, bytes: &[u8], context: &ImportContext) -> Result<ImportedAsset, StdError> {
+        AssetManager::import_lgmat(path, bytes, context).map(ImportedAsset::Material)
+    }
+}
+
+/// The material named after the label, the file itself is registered by AssetManager::store_mtl_materials.
+struct MtlImporter;
+impl AssetImporter for MtlImporter {
+    fn kind(&self, _path: &AssetPath) -> Option<AssetKind> {
+        Some(AssetKind::Material)
+    }
+
+    fn import(&self, path: &AssetPath, bytes: &[u8], context: &ImportContext) -> Result<ImportedAsset, StdError> {
+        AssetManager::import_mtl_material(path, bytes, context).map(ImportedAsset::Material)
+    }
+}
+
+/// Sub-assets by label, the file itself is registered by AssetManager::store_gltf_assets.
+struct GltfImporter;
+impl AssetImporter for GltfImporter {
+    fn kind(&self, path: &AssetPath) -> Option<AssetKind> {
+        gltf::label_kind(path.label()?)
+    }
+
+    fn import(&self, path: &AssetPath, bytes: &[u8], context: &ImportContext) -> Result<ImportedAsset, StdError> {
+        match self.kind(path) {
+            Some(AssetKind::Texture) => AssetManager::import_gltf_texture(path, bytes, context).map(ImportedAsset::Texture),
+            Some(AssetKind::Mesh) => AssetManager::import_gltf_mesh(path, bytes, context).map(ImportedAsset::Mesh),
+            Some(AssetKind::Material) => AssetManager::import_gltf_material(path, bytes, context).map(ImportedAsset::Material),
+            _ => Err(std::format!("{} is not a glTF sub-asset! (AssetManager)", path).into()),
+        }
+    }
+}
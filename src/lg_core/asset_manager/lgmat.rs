@@ -0,0 +1,196 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use sllog::{info, warn};
+use crate::{lg_core::{renderer::{material::{Material, UniformValue}, render_state::RenderState, sampler::SamplerSpecs}, uuid::UUID}, StdError};
+use super::{vfs::Vfs, AssetManager, AssetPath, ImportContext};
+
+pub const LGMAT_EXTENSION: &str = "lgmat";
+/// Written by this version, older files are migrated when they are read.
+pub const LGMAT_VERSION: u32 = 1;
+
+/// Texture bound to a slot, slots are bound in file order.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default)]
+pub struct TextureSlot {
+    /// Sampler uniform of the slot, empty if the shader uses layout bindings or an array.
+    pub name: String,
+    /// UUID or asset path, empty if the draw binds it (e.g. a render pass). Those slots go last.
+    pub texture: String,
+    /// Replaces the sampler of the texture.
+    pub sampler: Option<SamplerSpecs>,
+}
+
+/// UUIDs or asset paths.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct ShaderStages {
+    pub vertex: String,
+    pub fragment: String,
+}
+
+/// Contents of a .lgmat file.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MaterialDesc {
+    pub version: u32,
+    pub name: String,
+    /// Used for the .meta file of a new material, the .meta file wins after that.
+    #[serde(default)]
+    pub uuid: UUID,
+    #[serde(default)]
+    pub textures: Vec<TextureSlot>,
+    pub shaders: ShaderStages,
+    /// Default values of plain uniforms, by name.
+    #[serde(default)]
+    pub parameters: BTreeMap<String, UniformValue>,
+    #[serde(default)]
+    pub render_state: RenderState,
+}
+impl MaterialDesc {
+    pub fn new(name: &str, uuid: UUID, shaders: ShaderStages) -> Self {
+        Self {
+            version: LGMAT_VERSION,
+            name: name.to_string(),
+            uuid,
+            textures: Vec::new(),
+            shaders,
+            parameters: BTreeMap::new(),
+            render_state: RenderState::default(),
+        }
+    }
+
+    /// Reads every version, the bool is true if the file is older and was migrated.
+    pub fn from_bytes(bytes: &[u8]) -> Result<(Self, bool), StdError> {
+        /// Legacy files are YamlNode trees, they don't have a version.
+        #[derive(Deserialize)]
+        struct Version {
+            version: Option<u32>,
+        }
+
+        let version: Version = serializer::deserialize_bytes(bytes)?;
+        let (desc, migrated) = match version.version {
+            None => (Self::from_legacy(serializer::deserialize_bytes(bytes)?)?, true),
+            Some(LGMAT_VERSION) => (serializer::deserialize_bytes::<Self>(bytes)?, false),
+            Some(version) => return Err(std::format!("Material version {} is newer than {}! (AssetManager)", version, LGMAT_VERSION).into()),
+        };
+        desc.check_slots()?;
+
+        Ok((desc, migrated))
+    }
+
+    /// Next to the other assets of path, in the mount it comes from.
+    pub fn write(&self, vfs: &Vfs, path: &AssetPath) -> Result<(), StdError> {
+        let bytes = serializer::serialize_str(self)?;
+
+        match vfs.find(path) {
+            Some(mount) => mount.write(path, bytes.as_bytes()),
+            None => vfs.write(path, bytes.as_bytes()),
+        }
+    }
+
+    /// References are resolved with context, the UUID is the one of its .meta file.
+    pub fn to_material(&self, context: &ImportContext) -> Result<Material, StdError> {
+        let textures = self.textures
+            .iter()
+            .filter(|slot| !slot.texture.is_empty())
+            .map(|slot| context.resolve(&slot.texture))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let shaders = vec![
+            context.resolve(&self.shaders.vertex)?,
+            context.resolve(&self.shaders.fragment)?,
+        ];
+
+        Ok(Material::new(
+            context.meta().uuid,
+            &self.name,
+            shaders,
+            textures,
+            vec![]
+        )
+        .with_samplers(self.textures.iter().map(|slot| slot.sampler).collect())
+        .with_slot_names(self.textures.iter().map(|slot| slot.name.clone()).collect())
+        .with_parameters(self.parameters.clone())
+        .with_render_state(self.render_state))
+    }
+}
+// Private
+impl MaterialDesc {
+    /// Slots keep their index as texture unit, so the ones the draw binds can't come before a texture.
+    fn check_slots(&self) -> Result<(), StdError> {
+        let first_empty = self.textures.iter().position(|slot| slot.texture.is_empty());
+
+        match (first_empty, self.textures.iter().rposition(|slot| !slot.texture.is_empty())) {
+            (Some(empty), Some(texture)) if empty < texture => Err(std::format!(
+                "Material {} has texture slot {} bound by the draw before slot {} with a texture, those slots go last! (AssetManager)",
+                self.name,
+                empty,
+                texture
+            ).into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// uuid, textures (comma separated), vertex_shader, fragment_shader and samplers (one child per slot) nodes.
+    fn from_legacy(node: serializer::YamlNode) -> Result<Self, StdError> {
+        let mut result = Self::new(&node.name, UUID::default(), ShaderStages::default());
+        let mut samplers = Vec::new();
+
+        for child in node.children {
+            let value = child.value.trim();
+
+            match child.name.as_str() {
+                "uuid" => result.uuid = UUID::from_u128(value.parse::<u128>()?),
+                "textures" => result.textures.extend(value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|t| !t.is_empty())
+                    .map(|t| TextureSlot { texture: t.to_string(), ..Default::default() })),
+                "vertex_shader" => result.shaders.vertex = value.to_string(),
+                "fragment_shader" => result.shaders.fragment = value.to_string(),
+                "samplers" => for sampler in child.children {
+                    let slot = sampler.name.trim().parse::<usize>()?;
+                    samplers.push((slot, serializer::deserialize_str::<SamplerSpecs>(&sampler.value)?));
+                },
+
+                _ => return Err(std::format!("Unknown material node {}! (AssetManager)", child.name).into()),
+            }
+        }
+
+        // Overrides of slots the draw binds.
+        for (slot, sampler) in samplers {
+            if result.textures.len() <= slot {
+                result.textures.resize(slot + 1, TextureSlot::default());
+            }
+            result.textures[slot].sampler = Some(sampler);
+        }
+
+        Ok(result)
+    }
+}
+
+// Public(crate)
+impl AssetManager {
+    /// Files written before LGMAT_VERSION are migrated in memory, see save_migrated_lgmat.
+    pub(super) fn import_lgmat(path: &AssetPath, bytes: &[u8], context: &ImportContext) -> Result<Material, StdError> {
+        let (desc, _) = MaterialDesc::from_bytes(bytes)
+            .map_err(|e| std::format!("{}: {e}", path))?;
+
+        desc.to_material(context)
+    }
+
+    /// Saves path in the current version if it is older, when a material is loaded on the thread of the AssetManager.
+    ///
+    /// Discovery, hot reload and background imports never write it.
+    pub(super) fn save_migrated_lgmat(&self, path: &AssetPath) {
+        if self.read_only || path.extension() != Some(LGMAT_EXTENSION) { return; }
+
+        let desc = match self.vfs.read(path).and_then(|bytes| MaterialDesc::from_bytes(&bytes)) {
+            Ok((desc, true)) => desc,
+            _ => return,
+        };
+
+        match desc.write(&self.vfs, path) {
+            Ok(()) => info!("Migrated {} to version {} (AssetManager)", path, LGMAT_VERSION),
+            Err(e) => warn!("Failed to save the migrated {}: {e} (AssetManager)", path),
+        }
+    }
+}
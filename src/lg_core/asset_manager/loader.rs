@@ -0,0 +1,180 @@
+use std::{collections::HashSet, sync::{mpsc::{self, Receiver, Sender}, Arc}};
+use crate::{lg_core::uuid::UUID, profile_function, StdError};
+use super::{handle::HandleInner, Asset, AssetKind, AssetManager, Handle};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadState {
+    Loading,
+    Loaded,
+    Failed(String),
+}
+
+/// Inserts the decoded asset into the AssetManager.
+type InsertAsset = Box<dyn FnOnce(&mut AssetManager) + Send>;
+
+struct FinishedLoad {
+    kind: AssetKind,
+    uuid: UUID,
+    result: Result<InsertAsset, String>,
+}
+
+/// Loads running on the rayon pool, they are finished by AssetManager::finish_loads.
+pub(crate) struct AsyncLoads {
+    sender: Sender<FinishedLoad>,
+    receiver: Receiver<FinishedLoad>,
+    loading: HashSet<(AssetKind, UUID)>,
+}
+impl Default for AsyncLoads {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        Self {
+            sender,
+            receiver,
+            loading: HashSet::default(),
+        }
+    }
+}
+
+/// A group of loads that can be polled or awaited together.
+#[derive(Default)]
+pub struct LoadBatch {
+    handles: Vec<Arc<HandleInner>>,
+}
+impl LoadBatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push<T>(&mut self, handle: &Handle<T>) {
+        self.handles.push(Arc::clone(&handle.inner));
+    }
+
+    pub fn len(&self) -> usize {
+        self.handles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.handles.is_empty()
+    }
+
+    /// Loads that are not Loading anymore.
+    pub fn finished(&self) -> usize {
+        self.handles
+            .iter()
+            .filter(|h| h.state() != LoadState::Loading)
+            .count()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.finished() == self.handles.len()
+    }
+
+    /// UUID and error of every load that failed.
+    pub fn failed(&self) -> Vec<(UUID, String)> {
+        self.handles
+            .iter()
+            .filter_map(|h| match h.state() {
+                LoadState::Failed(e) => Some((*h.uuid(), e)),
+                _ => None,
+            })
+            .collect()
+    }
+}
+impl std::fmt::Debug for LoadBatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LoadBatch")
+            .field("len", &self.handles.len())
+            .field("finished", &self.finished())
+            .finish()
+    }
+}
+
+// Public
+impl AssetManager {
+    /// Returns right away, the asset is decoded on the rayon pool and inserted by finish_loads.
+    ///
+    /// The handle is Loaded if the asset already was.
+    pub fn load_async<T: Asset>(&mut self, uuid: &UUID) -> Result<Handle<T>, StdError> {
+        profile_function!();
+
+        if T::storage(self).contains_key(uuid) {
+            return Ok(self.new_handle(uuid, LoadState::Loaded));
+        }
+
+        // Generated, there is no file to read.
+        if T::KIND == AssetKind::Mesh && self.assets_path.primitives.contains_key(uuid) {
+            T::load(self, uuid)?;
+
+            return Ok(self.new_handle(uuid, LoadState::Loaded));
+        }
+
+        let path = T::path(self, uuid)
+            .ok_or(std::format!("{} is an invalid {:?} UUID!", uuid, T::KIND))?;
+
+        // Before the load is marked as running, so a missing file doesn't leave it Loading forever.
+        let mount = Arc::clone(self.vfs
+            .find(&path)
+            .ok_or(std::format!("{} was not found! (AssetManager)", path))?);
+        let context = self.import_context(uuid);
+
+        let handle = self.new_handle(uuid, LoadState::Loading);
+        if !self.loads.loading.insert((T::KIND, *uuid)) {
+            return Ok(handle);
+        }
+
+        let sender = self.loads.sender.clone();
+        let uuid = *uuid;
+        rayon::spawn(move || {
+            let result = mount.read(&path.file())
+                .and_then(|bytes| T::import(&path, &bytes, &context))
+                .map(|asset| Box::new(move |asset_manager: &mut AssetManager| T::insert(asset_manager, asset)) as InsertAsset)
+                .map_err(|e| e.to_string());
+
+            // The AssetManager may be gone already.
+            let _ = sender.send(FinishedLoad {
+                kind: T::KIND,
+                uuid,
+                result,
+            });
+        });
+
+        Ok(handle)
+    }
+}
+
+// Public(crate)
+impl AssetManager {
+    /// Inserts the assets decoded since the last call, their GPU resources are created by the next init_gl_*.
+    pub(crate) fn finish_loads(&mut self) {
+        profile_function!();
+
+        while let Ok(load) = self.loads.receiver.try_recv() {
+            self.loads.loading.remove(&(load.kind, load.uuid));
+
+            // Nobody is waiting for it anymore.
+            let handle = match self.handles.get(load.kind, &load.uuid) {
+                Some(handle) => handle,
+                None => continue,
+            };
+
+            match load.result {
+                Ok(insert) => {
+                    insert(self);
+                    handle.set_state(LoadState::Loaded);
+
+                    // Not from the rayon pool, see save_migrated_lgmat.
+                    if load.kind == AssetKind::Material {
+                        if let Some(path) = self.path_of(&load.uuid).cloned() {
+                            self.save_migrated_lgmat(&path);
+                        }
+                    }
+                },
+                Err(e) => {
+                    sllog::error!("Failed to load {}: {e} (AssetManager)", load.uuid);
+                    handle.set_state(LoadState::Failed(e));
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use serde::{Deserialize, Serialize};
+use crate::{lg_core::{renderer::{sampler::SamplerSpecs, texture::{TextureFilter, TextureFormat}}, uuid::UUID}, StdError};
+use super::{vfs::Vfs, AssetPath};
+
+pub const META_EXTENSION: &str = "meta";
+
+/// Normals generated for meshes that don't have them.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+pub enum NormalGeneration {
+    /// Averaged over the faces sharing a vertex.
+    #[default]
+    Smooth,
+    /// One per face, vertices are not shared.
+    Flat,
+}
+
+/// How the images of a texture file are arranged.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+pub enum TextureLayout {
+    /// A 2D texture.
+    #[default]
+    Single,
+    /// Cube map from a 2:1 panorama, None uses half of its height as the face size.
+    Equirectangular { face_size: Option<u32> },
+    /// Cube map, six square faces side by side in +X, -X, +Y, -Y, +Z, -Z order.
+    CubeFaces,
+    /// 2D array, layers with the same size stacked from top to bottom.
+    Array { layers: u32 },
+}
+
+/// How an asset file is imported, unused settings are ignored (e.g. filter for meshes).
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct ImportSettings {
+    /// Textures, None picks it from the file (RGBA if it has alpha, RGB otherwise).
+    pub format: Option<TextureFormat>,
+    /// Textures, min and mag filter of the default sampler.
+    pub filter: TextureFilter,
+    /// Textures, replaces filter when set.
+    pub sampler: Option<SamplerSpecs>,
+    /// Textures, the color data is sRGB encoded.
+    pub srgb: bool,
+    /// Textures.
+    pub layout: TextureLayout,
+    /// Textures are flipped vertically, meshes get v = 1 - v.
+    pub flip_uv: bool,
+    /// Meshes without normals.
+    pub normals: NormalGeneration,
+    /// Meshes, identical vertices are merged.
+    pub weld_vertices: bool,
+    /// Meshes, the triangles of every SubMesh are reordered for the vertex cache.
+    pub optimize_vertex_cache: bool,
+    /// Meshes, for normal mapping.
+    pub tangents: bool,
+}
+impl Default for ImportSettings {
+    fn default() -> Self {
+        Self {
+            format: None,
+            filter: TextureFilter::LINEAR,
+            sampler: None,
+            srgb: false,
+            layout: TextureLayout::Single,
+            flip_uv: false,
+            normals: NormalGeneration::Smooth,
+            weld_vertices: false,
+            optimize_vertex_cache: false,
+            tangents: false,
+        }
+    }
+}
+
+/// Sidecar file next to every asset (e.g. stripes.png.meta), it keeps the UUID when the asset is moved or renamed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AssetMeta {
+    pub uuid: UUID,
+    #[serde(default)]
+    pub import: ImportSettings,
+}
+impl AssetMeta {
+    pub fn new(uuid: UUID) -> Self {
+        Self {
+            uuid,
+            import: ImportSettings::default(),
+        }
+    }
+
+    /// asset.ext -> asset.ext.meta
+    pub fn path_of(asset_path: &AssetPath) -> AssetPath {
+        asset_path.with_added_extension(META_EXTENSION)
+    }
+
+    pub fn read(vfs: &Vfs, asset_path: &AssetPath) -> Result<Self, StdError> {
+        serializer::deserialize_with(vfs, &Self::path_of(asset_path).to_string())
+    }
+
+    /// Next to the asset, in the mount it comes from.
+    pub fn write(&self, vfs: &Vfs, asset_path: &AssetPath) -> Result<(), StdError> {
+        let bytes = serializer::serialize_str(self)?;
+
+        match vfs.find(asset_path) {
+            Some(mount) => mount.write(&Self::path_of(asset_path), bytes.as_bytes()),
+            None => vfs.write(&Self::path_of(asset_path), bytes.as_bytes()),
+        }
+    }
+
+    /// Reads the sidecar file, creating it with default if there is none.
+    ///
+    /// If it can't be written (e.g. read only mount) the meta is still returned, it just won't persist.
+    pub fn read_or_create(vfs: &Vfs, asset_path: &AssetPath, default: impl FnOnce() -> Self) -> Result<Self, StdError> {
+        if let Some(meta) = Self::read_existing(vfs, asset_path)? {
+            return Ok(meta);
+        }
+
+        let meta = default();
+        if let Err(e) = meta.write(vfs, asset_path) {
+            sllog::warn!("Failed to create {}: {e} (AssetMeta)", Self::path_of(asset_path));
+        }
+
+        Ok(meta)
+    }
+
+    /// Same as read_or_create, but a missing sidecar file is only kept in memory.
+    pub fn read_or_default(vfs: &Vfs, asset_path: &AssetPath, default: impl FnOnce() -> Self) -> Result<Self, StdError> {
+        Ok(Self::read_existing(vfs, asset_path)?.unwrap_or_else(default))
+    }
+}
+// Private
+impl AssetMeta {
+    /// None if there is no sidecar file.
+    fn read_existing(vfs: &Vfs, asset_path: &AssetPath) -> Result<Option<Self>, StdError> {
+        if !vfs.exists(&Self::path_of(asset_path)) {
+            return Ok(None);
+        }
+
+        Self::read(vfs, asset_path)
+            .map(Some)
+            .map_err(|e| std::format!("Failed to read {}: {e} (AssetMeta)", Self::path_of(asset_path)).into())
+    }
+}
+
+/// The UUID assets had before .meta files, a hash of the project relative path with Windows separators.
+///
+/// New meta files start with it, so materials and constants written before keep working.
+pub fn legacy_uuid(path: &AssetPath) -> Result<UUID, StdError> {
+    UUID::from_string(&std::format!("assets\\{}", path.as_str().replace('/', "\\")))
+}
+
+/// Sub-assets have no .meta file, their UUID is a hash of their path.
+pub fn sub_asset_uuid(path: &AssetPath) -> Result<UUID, StdError> {
+    UUID::from_string(&path.to_string())
+}
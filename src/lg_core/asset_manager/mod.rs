@@ -0,0 +1,986 @@
+mod asset_path;
+mod dependency;
+mod fallback;
+mod gltf;
+mod watcher;
+mod handle;
+mod importer;
+mod lgmat;
+mod loader;
+mod meta;
+mod obj;
+mod pack;
+mod ply;
+mod stl;
+mod vfs;
+
+pub use asset_path::{AssetPath, ASSET_SCHEME, LABEL_SEPARATOR};
+pub use dependency::DependencyGraph;
+pub use fallback::{FALLBACK_FRAGMENT_SHADER, FALLBACK_MATERIAL, FALLBACK_MESH, FALLBACK_TEXTURE, FALLBACK_VERTEX_SHADER};
+pub use handle::{Asset, AssetKind, Handle, WeakHandle};
+pub use importer::{AssetImporter, ImportedAsset};
+pub use lgmat::{MaterialDesc, ShaderStages, TextureSlot, LGMAT_EXTENSION, LGMAT_VERSION};
+pub use loader::{LoadBatch, LoadState};
+pub use meta::{legacy_uuid, sub_asset_uuid, AssetMeta, ImportSettings, NormalGeneration, TextureLayout};
+pub use pack::{AssetPack, Compression, PackBuilder, PackEntry, PACK_EXTENSION};
+pub use vfs::{DirMount, MemoryMount, Mount, Vfs, ROOT_PRIORITY};
+
+use std::{borrow::Cow, collections::{HashMap, HashSet}, path::{Path, PathBuf}, sync::{Arc, Mutex}};
+use fallback::FALLBACK_SHADERS;
+use handle::HandleRegistry;
+use importer::ImporterRegistry;
+use loader::AsyncLoads;
+use meta::META_EXTENSION;
+use obj::MTL_EXTENSION;
+use sllog::{error, warn};
+use watcher::AssetWatcher;
+use crate::{profile_function, StdError};
+use super::{renderer::{material::Material, mesh::{Mesh, MeshUpdate, Topology}, primitives::Primitive, sampler::SamplerSpecs, vertex::Vertex, shader::{Shader, ShaderStage}, texture::{Texture, TextureFormat, TextureKind, TextureSpecs}}, uuid::UUID};
+
+pub const DEFAULT_ASSETS_ROOT: &str = "assets";
+const TEXTURES_DIR: &str = "textures";
+const MESHES_DIR: &str = "meshes";
+const SHADERS_DIR: &str = "shaders/src";
+const MATERIALS_DIR: &str = "materials";
+/// Shaders of the materials imported from models (.mtl, glTF).
+const IMPORTED_VERTEX_SHADER: &str = "asset://shaders/src/instancing_v.vert";
+const IMPORTED_FRAGMENT_SHADER: &str = "asset://shaders/src/instancing_f.frag";
+/// Files given to AssetManager::load_bytes are above every other mount.
+const MEMORY_PRIORITY: i32 = i32::MAX;
+/// Distance under which imported vertices are merged, when ImportSettings::weld_vertices is set.
+const WELD_EPSILON: f32 = 1e-5;
+
+/// Asset paths by UUID, the bytes come from the Vfs.
+#[derive(Default)]
+struct AssetsPath {
+    textures: HashMap<UUID, AssetPath>,
+    meshes: HashMap<UUID, AssetPath>,
+    shaders: HashMap<UUID, AssetPath>,
+    materials: HashMap<UUID, AssetPath>,
+
+    /// UUID of the .meta file, shared with the background loads.
+    uuids: Arc<HashMap<AssetPath, UUID>>,
+    asset_paths: HashMap<UUID, AssetPath>,
+    metas: HashMap<UUID, AssetMeta>,
+    /// Meshes generated on load instead of read from a file.
+    primitives: HashMap<UUID, Primitive>,
+}
+
+/// What an import needs besides the file, can be sent to another thread.
+#[derive(Debug, Clone)]
+pub struct ImportContext {
+    meta: AssetMeta,
+    uuids: Arc<HashMap<AssetPath, UUID>>,
+    vfs: Vfs,
+    importers: Arc<ImporterRegistry>,
+}
+impl ImportContext {
+    pub fn meta(&self) -> &AssetMeta {
+        &self.meta
+    }
+
+    /// For the files an asset references, e.g. the .mtl of an OBJ.
+    pub fn vfs(&self) -> &Vfs {
+        &self.vfs
+    }
+
+    /// With the importer registered for the extension of path.
+    pub fn import(&self, path: &AssetPath, bytes: &[u8]) -> Result<ImportedAsset, StdError> {
+        self.importers.import(path, bytes, self)
+    }
+
+    /// References are either UUIDs or anything AssetPath::new accepts, Windows paths included.
+    pub fn resolve(&self, reference: &str) -> Result<UUID, StdError> {
+        if let Ok(value) = reference.parse::<u128>() {
+            return Ok(UUID::from_u128(value));
+        }
+
+        let path = AssetPath::new(reference)?;
+        match self.uuids.get(&path) {
+            Some(uuid) => Ok(*uuid),
+            None => legacy_uuid(&path),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct AssetStorage {
+    textures: Vec<UUID>,
+    meshes: Vec<UUID>,
+    shaders: Vec<UUID>,
+    materials: Vec<UUID>,
+}
+
+pub struct AssetManager {
+    root: PathBuf,
+    vfs: Vfs,
+    memory: Arc<MemoryMount>,
+    assets_path: AssetsPath,
+    /// Filled by the handles when the last strong one drops.
+    to_destroy: Arc<Mutex<AssetStorage>>,
+    to_init_gl: AssetStorage,
+    /// Materials whose GlProgram has to be rebuilt, the previous one is kept if it fails.
+    to_rebuild_gl: Vec<UUID>,
+    watcher: Option<AssetWatcher>,
+    handles: HandleRegistry,
+    loads: AsyncLoads,
+    /// Shared with the background loads.
+    importers: Arc<ImporterRegistry>,
+    /// Assets already replaced by a fallback, warned about once.
+    replaced: HashSet<UUID>,
+    dependency_graph: DependencyGraph,
+    /// Nothing is written to the mounts, see init_read_only.
+    read_only: bool,
+
+    textures: HashMap<UUID, Texture>,
+    meshes: HashMap<UUID, Mesh>,
+    shaders: HashMap<UUID, Shader>,
+    materials: HashMap<UUID, Material>,
+}
+impl Default for AssetManager {
+    fn default() -> Self {
+        Self::new(DEFAULT_ASSETS_ROOT)
+    }
+}
+// Public
+impl AssetManager {
+    /// root is mounted with ROOT_PRIORITY, asset paths are relative to it.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        let root = root.into();
+        let memory = Arc::new(MemoryMount::new());
+
+        let mut vfs = Vfs::new();
+        vfs.mount(Arc::new(DirMount::new(&root)), ROOT_PRIORITY);
+        vfs.mount(Arc::clone(&memory) as Arc<dyn Mount>, MEMORY_PRIORITY);
+
+        let mut result = Self {
+            root,
+            vfs,
+            memory,
+            assets_path: AssetsPath::default(),
+            to_destroy: Arc::default(),
+            to_init_gl: AssetStorage::default(),
+            to_rebuild_gl: Vec::new(),
+            watcher: None,
+            handles: HandleRegistry::default(),
+            loads: AsyncLoads::default(),
+            importers: Arc::default(),
+            replaced: HashSet::default(),
+            dependency_graph: DependencyGraph::default(),
+            read_only: false,
+
+            textures: HashMap::default(),
+            meshes: HashMap::default(),
+            shaders: HashMap::default(),
+            materials: HashMap::default(),
+        };
+        result.insert_fallbacks();
+
+        for primitive in Primitive::DEFAULTS {
+            if let Err(e) = result.register_primitive(primitive) {
+                error!("Failed to register {primitive}: {e} (AssetManager)");
+            }
+        }
+
+        result
+    }
+
+    /// Registers every asset of the mounts, the missing .meta files are created.
+    pub fn init(&mut self) -> Result<(), StdError> {
+        let files = self.vfs.files();
+
+        self.store_asset_paths(files)
+    }
+
+    /// Same as init, but the missing .meta files are only kept in memory and migrated files are not saved.
+    ///
+    /// For tools that inspect the assets, e.g. lgasset.
+    pub fn init_read_only(&mut self) -> Result<(), StdError> {
+        self.read_only = true;
+
+        self.init()
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Every registered asset file, primitives and fallbacks are not included.
+    pub fn assets(&self) -> impl Iterator<Item = (&UUID, &AssetPath)> {
+        self.assets_path.asset_paths.iter()
+    }
+
+    pub fn create_material(&mut self, name: &str, textures: Vec<String>, shaders: Vec<String>) -> Result<Handle<Material>, StdError> {
+        profile_function!();
+
+        let asset_path = AssetPath::new(&std::format!("{ASSET_SCHEME}{MATERIALS_DIR}/{name}.{LGMAT_EXTENSION}"))?;
+
+        let uuid = legacy_uuid(&asset_path)?;
+        let context = ImportContext {
+            meta: AssetMeta::new(uuid),
+            uuids: Arc::clone(&self.assets_path.uuids),
+            vfs: self.vfs.clone(),
+            importers: Arc::clone(&self.importers),
+        };
+
+        // Stored as UUIDs, so moving the files doesn't break the material.
+        let reference = |r: &String| context.resolve(r).map(|uuid| uuid.get_value().to_string());
+        let shaders = shaders
+            .iter()
+            .map(reference)
+            .collect::<Result<Vec<_>, _>>()?;
+        let [vertex, fragment] = <[String; 2]>::try_from(shaders)
+            .map_err(|s| std::format!("Material {} has {} shaders instead of 2! (AssetManager)", name, s.len()))?;
+
+        let mut desc = MaterialDesc::new(name, uuid, ShaderStages { vertex, fragment });
+        for texture in &textures {
+            desc.textures.push(TextureSlot {
+                texture: reference(texture)?,
+                ..Default::default()
+            });
+        }
+
+        desc.write(&self.vfs, &asset_path)?;
+
+        self.store_asset_path_with(&asset_path, |_| AssetMeta::new(uuid))?;
+        self.handle(&uuid)
+    }
+
+    /// The texture is named after its file, specs are saved as its import settings.
+    /// 
+    /// path is anything AssetPath::new accepts.
+    pub fn create_texture(&mut self, path: &str, specs: TextureSpecs) -> Result<Handle<Texture>, StdError> {
+        profile_function!();
+
+        let asset_path = AssetPath::new(path)?;
+
+        let uuid = self.store_asset_path_with(&asset_path, |_| {
+            let mut meta = AssetMeta::new(legacy_uuid(&asset_path).unwrap_or_default());
+            meta.import.format = Some(specs.tex_format);
+            meta.import.sampler = Some(specs.sampler);
+
+            meta
+        })?;
+
+        self.handle(&uuid)
+    }
+
+    /// Lives as long as it has handles, updated with update_mesh.
+    pub fn create_mesh(&mut self, name: &str, vertices: Vec<Vertex>, indices: Vec<u32>, topology: Topology) -> Result<Handle<Mesh>, StdError> {
+        profile_function!();
+
+        if let Some(i) = indices.iter().find(|i| **i as usize >= vertices.len()) {
+            return Err(std::format!("Index {} of {} is out of bounds! (AssetManager)", i, name).into());
+        }
+
+        let uuid = UUID::generate();
+        let mesh = Mesh::new(uuid, name, vertices, indices)
+            .with_topology(topology)
+            .with_dynamic(true);
+
+        self.to_init_gl.meshes.push(uuid);
+        self.meshes.insert(uuid, mesh);
+
+        self.handle(&uuid)
+    }
+
+    /// The GPU copy is updated by the next init_gl_vao.
+    pub fn update_mesh(&mut self, uuid: &UUID, update: MeshUpdate) -> Result<(), StdError> {
+        profile_function!();
+
+        self.meshes
+            .get_mut(uuid)
+            .ok_or(std::format!("{} is not a loaded mesh! (AssetManager)", uuid))?
+            .update(update)?;
+
+        if !self.to_init_gl.meshes.contains(uuid) {
+            self.to_init_gl.meshes.push(*uuid);
+        }
+
+        Ok(())
+    }
+
+    /// The mesh of primitive can be loaded with the returned UUID, the same as Primitive::uuid.
+    /// 
+    /// Primitive::DEFAULTS are registered already.
+    pub fn register_primitive(&mut self, primitive: Primitive) -> Result<UUID, StdError> {
+        let uuid = primitive.uuid()?;
+        self.assets_path.primitives.insert(uuid, primitive);
+
+        Ok(uuid)
+    }
+
+    /// UUID of the asset at path, from its .meta file.
+    pub fn uuid_of(&self, path: &AssetPath) -> Option<UUID> {
+        self.assets_path.uuids
+            .get(path)
+            .copied()
+    }
+
+    pub fn path_of(&self, uuid: &UUID) -> Option<&AssetPath> {
+        self.assets_path.asset_paths.get(uuid)
+    }
+
+    /// None if there is no importer for path.
+    pub fn kind_of(&self, path: &AssetPath) -> Option<AssetKind> {
+        self.importers.kind_of(path)
+    }
+
+    /// Files with extension become assets, the ones already mounted are registered right away.
+    /// 
+    /// Replaces the importer of the extension if there was one (e.g. a built-in one).
+    pub fn register_importer(&mut self, extension: &str, importer: Arc<dyn AssetImporter>) -> Result<(), StdError> {
+        profile_function!();
+
+        Arc::make_mut(&mut self.importers).register(extension, importer);
+
+        let extension = extension.trim_start_matches('.');
+        let files = self.vfs
+            .files()
+            .into_iter()
+            .filter(|p| p.extension().is_some_and(|e| e.eq_ignore_ascii_case(extension)))
+            .collect();
+
+        self.store_asset_paths(files)
+    }
+
+    pub fn meta(&self, uuid: &UUID) -> Option<&AssetMeta> {
+        self.assets_path.metas.get(uuid)
+    }
+
+    pub fn vfs(&self) -> &Vfs {
+        &self.vfs
+    }
+
+    /// The assets of mount are served from it, unless a mount with a higher priority has them too.
+    pub fn mount(&mut self, mount: Arc<dyn Mount>, priority: i32) -> Result<(), StdError> {
+        profile_function!();
+
+        let files = mount.files();
+        self.vfs.mount(mount, priority);
+
+        self.store_asset_paths(files)
+    }
+
+    /// Serves path from bytes (e.g. include_bytes!) above every mount, a loaded asset at path is replaced.
+    /// 
+    /// path is anything AssetPath::new accepts.
+    pub fn load_bytes<T: Asset>(&mut self, path: &str, bytes: impl Into<Cow<'static, [u8]>>) -> Result<Handle<T>, StdError> {
+        profile_function!();
+
+        let asset_path = AssetPath::new(path)?;
+        if self.kind_of(&asset_path) != Some(T::KIND) {
+            return Err(std::format!("{} is not a {:?}! (AssetManager)", asset_path, T::KIND).into());
+        }
+
+        self.memory.insert(asset_path.clone(), bytes);
+        self.reload_asset(&asset_path)?;
+
+        let uuid = self.uuid_of(&asset_path)
+            .ok_or(std::format!("Failed to register {}! (AssetManager)", asset_path))?;
+        T::load(self, &uuid)?;
+
+        Ok(self.new_handle(&uuid, LoadState::Loaded))
+    }
+}
+
+// Public(crate)
+impl AssetManager {
+    pub(crate) fn get_texture(&mut self, uuid: &UUID) -> Result<&Texture, StdError> {
+        profile_function!();
+
+        if self.textures.contains_key(uuid) {
+            return Ok(&self.textures[uuid]);
+        }
+
+        let path = self.assets_path.textures
+            .get(uuid)
+            .ok_or(std::format!("{} is an invalid texture UUID!", uuid))?
+            .clone();
+        
+        Ok(self.load_texture(&path, uuid)?)
+    }
+
+    pub(crate) fn get_mesh(&mut self, uuid: &UUID) -> Result<&Mesh, StdError> {
+        profile_function!();
+
+        if self.meshes.contains_key(uuid) {
+            return Ok(&self.meshes[uuid]);
+        }
+
+        if let Some(primitive) = self.assets_path.primitives.get(uuid) {
+            let mesh = primitive.mesh()?;
+            self.to_init_gl.meshes.push(*uuid);
+
+            return Ok(self.meshes.entry(*uuid).or_insert(mesh));
+        }
+
+        let path = self.assets_path.meshes
+            .get(uuid)
+            .ok_or(std::format!("{} is an invalid mesh UUID!", uuid))?
+            .clone();
+        
+        Ok(self.load_mesh(&path, uuid)?)
+    }
+    
+    pub(crate) fn get_shader(&mut self, uuid: &UUID) -> Result<&Shader, StdError> {
+        profile_function!();
+
+        if self.shaders.contains_key(uuid) {
+            return Ok(&self.shaders[uuid]);
+        }
+
+        let path = self.assets_path.shaders
+            .get(uuid)
+            .ok_or(std::format!("{} is an invalid shader UUID!", uuid))?
+            .clone();
+        
+        Ok(self.load_shader(&path, uuid)?)
+    }
+
+    pub(crate) fn get_material(&mut self, uuid: &UUID) -> Result<&Material, StdError> {
+        profile_function!();
+
+        if self.materials.contains_key(uuid) {
+            return Ok(&self.materials[uuid]);
+        }
+
+        let path = self.assets_path.materials
+            .get(uuid)
+            .ok_or(std::format!("{} is an invalid material UUID!", uuid))?
+            .clone();
+
+        Ok(self.load_material(&path, uuid)?)
+    }
+
+    /// Polls the asset directory for changes every poll_interval, None stops watching.
+    pub(crate) fn set_hot_reload(&mut self, poll_interval: Option<std::time::Duration>) {
+        self.watcher = poll_interval.map(|interval| AssetWatcher::new(&self.root, interval));
+    }
+
+    /// Reloads the loaded assets that changed on disk, their GPU resources are recreated by the next init_gl_*.
+    /// 
+    /// Only call this function from the render thread, the replaced assets are dropped right away.
+    pub(crate) fn hot_reload(&mut self) {
+        profile_function!();
+
+        let changes = match &mut self.watcher {
+            Some(watcher) => watcher.poll(),
+            None => return,
+        };
+
+        for change in changes {
+            let result = match &change {
+                watcher::AssetChange::Created(path) |
+                watcher::AssetChange::Modified(path) => match AssetPath::from_fs(&self.root, path) {
+                    Some(asset_path) => self.reload_asset(&asset_path),
+                    None => Ok(()),
+                },
+                watcher::AssetChange::Removed(path) => {
+                    // Loaded assets stay alive, they may still be in use.
+                    warn!("{} was removed! (AssetManager)", path.display());
+                    Ok(())
+                },
+            };
+
+            if let Err(e) = result {
+                error!("Failed to reload {:?}: {e} (AssetManager)", change);
+            }
+        }
+    }
+
+    /// Only call this function from the render thread
+    pub(crate) fn init_gl_program(&mut self) -> Result<(), StdError> {
+        profile_function!();
+
+        let materials = std::mem::take(&mut self.to_init_gl.materials);
+
+        for mat_uui in materials {
+            let shaders = self.material_shader_uuids_or_fallback(&mat_uui).map(|uuid| &self.shaders[&uuid]);
+            let fallback = FALLBACK_SHADERS.map(|uuid| &self.shaders[&uuid]);
+
+            let mat = self.materials.get_mut(&mat_uui).unwrap();
+            if let Err(e) = mat.init_opengl(&shaders) {
+                error!("Failed to create the program of {}, using the error shaders: {e} (AssetManager)", mat.name());
+                mat.init_opengl(&fallback)?;
+            }
+        }
+
+        let materials = std::mem::take(&mut self.to_rebuild_gl);
+
+        for mat_uui in materials {
+            let shaders = match self.material_shader_uuids(&mat_uui) {
+                Ok(shaders) => shaders.map(|uuid| &self.shaders[&uuid]),
+                Err(e) => {
+                    error!("Failed to rebuild the program of {}, keeping the previous one: {e} (AssetManager)", mat_uui);
+                    continue;
+                },
+            };
+
+            let mat = self.materials.get_mut(&mat_uui).unwrap();
+            if let Err(e) = mat.rebuild_opengl(&shaders) {
+                error!("Failed to rebuild the program of {}, keeping the previous one: {e} (AssetManager)", mat.name());
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Only call this function from the render thread
+    pub(crate) fn init_gl_vao(&mut self) -> Result<(), StdError> {
+        profile_function!();
+
+        let meshes = std::mem::take(&mut self.to_init_gl.meshes);
+
+        for mesh_uui in meshes {
+            let mesh = self.meshes.get_mut(&mesh_uui).unwrap();
+            mesh.init_opengl()?;
+        }
+        
+        Ok(())
+    }
+
+    /// Only call this function from the render thread
+    pub(crate) fn init_gl_texture(&mut self) -> Result<(), StdError> {
+        profile_function!();
+
+        let textures = std::mem::take(&mut self.to_init_gl.textures);
+
+        for texture_uui in textures {
+            let texture = self.textures.get_mut(&texture_uui).unwrap();
+            texture.init_opengl()?;
+        }
+        
+        Ok(())
+    }
+
+    /// For backends without GPU resources: what was waiting to be initialized, (textures, meshes, materials).
+    pub(crate) fn take_to_init_gl(&mut self) -> (Vec<UUID>, Vec<UUID>, Vec<UUID>) {
+        let storage = std::mem::take(&mut self.to_init_gl);
+        let mut materials = storage.materials;
+        materials.append(&mut self.to_rebuild_gl);
+
+        (storage.textures, storage.meshes, materials)
+    }
+
+    /// Only call this function from the render thread
+    pub(crate) fn to_destroy(&mut self) {
+        profile_function!();
+
+        let mut storage = std::mem::take(&mut *self.to_destroy.lock().unwrap());
+
+        // The fallbacks are used in place of the assets that fail to load, they have to stay.
+        storage.textures.retain(|uuid| !Self::is_fallback(uuid));
+        storage.meshes.retain(|uuid| !Self::is_fallback(uuid));
+        storage.shaders.retain(|uuid| !Self::is_fallback(uuid));
+        storage.materials.retain(|uuid| !Self::is_fallback(uuid));
+
+        // A new handle may have been created since the last one dropped.
+        for tex_uui in storage.textures {
+            if self.handles.count(AssetKind::Texture, &tex_uui) == 0 {
+                let _ = self.textures.remove(&tex_uui);
+                self.to_init_gl.textures.retain(|uuid| *uuid != tex_uui);
+            }
+        }
+
+        for mesh_uui in storage.meshes {
+            if self.handles.count(AssetKind::Mesh, &mesh_uui) == 0 {
+                let _ = self.meshes.remove(&mesh_uui);
+                self.to_init_gl.meshes.retain(|uuid| *uuid != mesh_uui);
+            }
+        }
+
+        for shader_uui in storage.shaders {
+            if self.handles.count(AssetKind::Shader, &shader_uui) == 0 {
+                let _ = self.shaders.remove(&shader_uui);
+            }
+        }
+
+        for mat_uui in storage.materials {
+            if self.handles.count(AssetKind::Material, &mat_uui) == 0 {
+                let _ = self.materials.remove(&mat_uui);
+                self.to_init_gl.materials.retain(|uuid| *uuid != mat_uui);
+                self.to_rebuild_gl.retain(|uuid| *uuid != mat_uui);
+            }
+        }
+
+        self.handles.clean();
+    }
+}
+
+// Private
+impl AssetManager {
+    fn store_asset_paths(&mut self, paths: Vec<AssetPath>) -> Result<(), StdError> {
+        for path in paths {
+            if path.extension().is_none() || path.extension() == Some(META_EXTENSION) { continue; }
+
+            if path.extension() == Some(gltf::GLTF_BUFFER_EXTENSION) { continue; }
+
+            if let Some(result) = self.store_sub_assets(&path) {
+                if let Err(e) = result {
+                    warn!("Failed to read the assets of {}: {e} (AssetManager)", path);
+                }
+                continue;
+            }
+
+            if self.kind_of(&path).is_none() {
+                warn!("There is no importer for {}, it is skipped! (AssetManager)", path);
+                continue;
+            }
+
+            if let Err(e) = self.store_asset_path(&path) {
+                warn!("Failed to register {}, it is skipped: {e} (AssetManager)", path);
+            }
+        }
+
+        // After every path is registered, so the references resolve.
+        let materials = self.assets_path.materials
+            .iter()
+            .filter(|(_, path)| path.extension() == Some(LGMAT_EXTENSION))
+            .map(|(uuid, path)| (*uuid, path.clone()))
+            .collect::<Vec<_>>();
+
+        for (uuid, path) in materials {
+            if let Err(e) = self.scan_dependencies(&path, &uuid) {
+                warn!("Failed to read the dependencies of {}: {e} (AssetManager)", path);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Dependencies of a .lgmat file that isn't loaded, its shaders and textures are not imported.
+    fn scan_dependencies(&mut self, path: &AssetPath, uuid: &UUID) -> Result<(), StdError> {
+        if self.materials.contains_key(uuid) { return Ok(()); }
+
+        let material = Self::import_material(path, &self.vfs.read(path)?, &self.import_context(uuid))?;
+        self.dependency_graph.set_material(&material);
+
+        Ok(())
+    }
+    
+    /// Files made of sub-assets (.mtl, glTF) register every asset in them, None for other files.
+    fn store_sub_assets(&mut self, path: &AssetPath) -> Option<Result<Vec<UUID>, StdError>> {
+        match path.extension() {
+            Some(MTL_EXTENSION) => Some(self.store_mtl_materials(path)),
+            Some(extension) if gltf::is_gltf_extension(extension) => Some(self.store_gltf_assets(path)),
+            _ => None,
+        }
+    }
+
+    fn store_asset_path(&mut self, path: &AssetPath) -> Result<UUID, StdError> {
+        self.store_asset_path_with(path, |vfs| Self::default_meta(path, vfs))
+    }
+
+    /// Reads or creates the .meta file of path, default is used for a new one.
+    fn store_asset_path_with(&mut self, path: &AssetPath, default: impl FnOnce(&Vfs) -> AssetMeta) -> Result<UUID, StdError> {
+        if self.kind_of(path).is_none() {
+            return Err(std::format!("{} is an invalid asset path!", path).into());
+        }
+
+        let meta = match self.read_only {
+            true => AssetMeta::read_or_default(&self.vfs, path, || default(&self.vfs))?,
+            false => AssetMeta::read_or_create(&self.vfs, path, || default(&self.vfs))?,
+        };
+
+        self.register_asset(path.clone(), meta)
+    }
+
+    fn register_asset(&mut self, asset_path: AssetPath, meta: AssetMeta) -> Result<UUID, StdError> {
+        let uuid = meta.uuid;
+
+        let paths = match self.kind_of(&asset_path) {
+            Some(AssetKind::Texture) => &mut self.assets_path.textures,
+            Some(AssetKind::Mesh) => &mut self.assets_path.meshes,
+            Some(AssetKind::Shader) => &mut self.assets_path.shaders,
+            Some(AssetKind::Material) => &mut self.assets_path.materials,
+            None => return Err(std::format!("{} is an invalid asset path!", asset_path).into()),
+        };
+
+        if let Some(previous) = paths.insert(uuid, asset_path.clone()) {
+            if previous != asset_path {
+                warn!("{} and {} have the same UUID, using {}! (AssetManager)", previous, asset_path, asset_path);
+            }
+        }
+
+        // The .meta file may have been edited to another UUID.
+        if let Some(old) = Arc::make_mut(&mut self.assets_path.uuids).insert(asset_path.clone(), uuid) {
+            if old != uuid {
+                self.assets_path.textures.remove(&old);
+                self.assets_path.meshes.remove(&old);
+                self.assets_path.shaders.remove(&old);
+                self.assets_path.materials.remove(&old);
+                self.assets_path.asset_paths.remove(&old);
+                self.assets_path.metas.remove(&old);
+            }
+        }
+        self.assets_path.asset_paths.insert(uuid, asset_path);
+        self.assets_path.metas.insert(uuid, meta);
+
+        Ok(uuid)
+    }
+
+    /// Meta of an asset seen for the first time.
+    fn default_meta(path: &AssetPath, vfs: &Vfs) -> AssetMeta {
+        // Materials already carry a UUID, it is kept.
+        let uuid = match path.extension() {
+            Some(LGMAT_EXTENSION) => vfs.read(path).ok().and_then(|bytes| Self::material_file_uuid(&bytes)),
+            _ => None,
+        };
+
+        AssetMeta::new(uuid.unwrap_or_else(|| legacy_uuid(path).unwrap_or_else(|_| UUID::generate())))
+    }
+
+    fn material_file_uuid(bytes: &[u8]) -> Option<UUID> {
+        MaterialDesc::from_bytes(bytes)
+            .ok()
+            .map(|(desc, _)| desc.uuid)
+            .filter(UUID::is_valid)
+    }
+
+    fn import_context(&self, uuid: &UUID) -> ImportContext {
+        ImportContext {
+            meta: self.assets_path.metas
+                .get(uuid)
+                .cloned()
+                .unwrap_or_else(|| AssetMeta::new(*uuid)),
+            uuids: Arc::clone(&self.assets_path.uuids),
+            vfs: self.vfs.clone(),
+            importers: Arc::clone(&self.importers),
+        }
+    }
+    
+    fn reload_asset(&mut self, path: &AssetPath) -> Result<(), StdError> {
+        let extension = match path.extension() {
+            Some(extension) => extension,
+            None => return Ok(()),
+        };
+
+        // Import settings changed, reload the asset itself.
+        if extension == META_EXTENSION {
+            return match path.strip_extension(META_EXTENSION) {
+                Some(asset_path) if self.vfs.exists(&asset_path) => self.reload_asset(&asset_path),
+                _ => Ok(()),
+            };
+        }
+
+        if let Some(result) = self.store_sub_assets(path) {
+            for uuid in result? {
+                let material_path = self.assets_path.asset_paths[&uuid].clone();
+                self.replace_loaded(&material_path, uuid)?;
+            }
+
+            return Ok(());
+        }
+
+        // New files become available, loaded ones are replaced.
+        let uuid = match self.store_asset_path(path) {
+            Ok(uuid) => uuid,
+            Err(e) => {
+                warn!("{e}");
+                return Ok(());
+            },
+        };
+
+        if extension == LGMAT_EXTENSION {
+            self.scan_dependencies(path, &uuid)?;
+        }
+
+        self.replace_loaded(path, uuid)
+    }
+
+    /// Imports path again if uuid is loaded.
+    fn replace_loaded(&mut self, path: &AssetPath, uuid: UUID) -> Result<(), StdError> {
+        let context = self.import_context(&uuid);
+
+        match self.kind_of(path) {
+            Some(AssetKind::Texture) => if let Some(texture) = self.textures.get_mut(&uuid) {
+                *texture = Self::import_texture(path, &self.vfs.read(path)?, &context)?;
+                self.to_init_gl.textures.push(uuid);
+            },
+
+            Some(AssetKind::Mesh) => if let Some(mesh) = self.meshes.get_mut(&uuid) {
+                *mesh = Self::import_mesh(path, &self.vfs.read(path)?, &context)?;
+                self.dependency_graph.set_mesh(mesh);
+                self.to_init_gl.meshes.push(uuid);
+            },
+
+            Some(AssetKind::Shader) => if let Some(shader) = self.shaders.get_mut(&uuid) {
+                *shader = Self::import_shader(path, &self.vfs.read(path)?, &context)?;
+
+                let materials = self.dependency_graph
+                    .dependents(&uuid)
+                    .filter(|m| self.materials.contains_key(m))
+                    .copied()
+                    .collect::<Vec<_>>();
+
+                self.to_rebuild_gl.extend(materials);
+            },
+
+            Some(AssetKind::Material) => if let Some(old) = self.materials.get_mut(&uuid) {
+                let mut material = Self::import_material(path, &self.vfs.read(path)?, &context)?;
+                material.gl_program = old.gl_program.take();
+                self.dependency_graph.set_material(&material);
+                *old = material;
+
+                self.to_rebuild_gl.push(uuid);
+            },
+
+            None => (),
+        }
+
+        Ok(())
+    }
+
+    /// [vertex, fragment], loaded in self.shaders.
+    fn material_shader_uuids(&mut self, material: &UUID) -> Result<[UUID; 2], StdError> {
+        self.get_material(material)?;
+        let shaders = <[UUID; 2]>::try_from(self.materials[material].shaders())
+            .map_err(|_| std::format!("Material {} doesn't have a vertex and a fragment shader! (AssetManager)", material))?;
+
+        for shader in &shaders {
+            self.get_shader(shader)?;
+        }
+
+        Ok(shaders)
+    }
+
+    fn load_texture(&mut self, path: &AssetPath, uuid: &UUID) -> Result<&mut Texture, StdError> {
+        let bytes = self.vfs.read(path)?;
+        let texture = Self::import_texture(path, &bytes, &self.import_context(uuid))?;
+
+        self.to_init_gl.textures.push(*texture.uuid());
+        Ok(self.textures.entry(*texture.uuid()).or_insert(texture))
+    }
+
+    fn import_texture(path: &AssetPath, bytes: &[u8], context: &ImportContext) -> Result<Texture, StdError> {
+        Texture::try_from(context.import(path, bytes)?)
+    }
+
+    /// bytes is an encoded image, e.g. png.
+    fn import_image(path: &AssetPath, bytes: &[u8], context: &ImportContext) -> Result<Texture, StdError> {
+        let settings = &context.meta.import;
+        let mut image = image::load_from_memory(bytes)?;
+
+        if settings.flip_uv {
+            image = image.flipv();
+        }
+
+        let tex_format = match (settings.format, settings.srgb) {
+            (Some(TextureFormat::RGB), true) | (Some(TextureFormat::SRGB8), _) => TextureFormat::SRGB8,
+            (Some(TextureFormat::RGBA), true) | (Some(TextureFormat::SRGBA8), _) => TextureFormat::SRGBA8,
+            (Some(format), _) => format,
+            // HDR and 16-bit images keep their precision, sRGB is only for 8 bits.
+            (None, srgb) => match (image.color().bytes_per_pixel() / image.color().channel_count(), image.color().has_alpha(), srgb) {
+                (4, true, _) => TextureFormat::RGBA32F,
+                (4, false, _) => TextureFormat::RGB32F,
+                (2, true, _) => TextureFormat::RGBA16,
+                (2, false, _) => TextureFormat::RGB16,
+                (_, true, false) => TextureFormat::RGBA,
+                (_, true, true) => TextureFormat::SRGBA8,
+                (_, false, false) => TextureFormat::RGB,
+                (_, false, true) => TextureFormat::SRGB8,
+            },
+        };
+
+        let tex_specs = TextureSpecs {
+            tex_format,
+            tex_type: tex_format.texture_type(),
+            sampler: settings.sampler.unwrap_or(SamplerSpecs::from_filter(settings.filter)),
+            tex_kind: TextureKind::TEXTURE_2D,
+        };
+
+        let (uuid, name) = (context.meta.uuid, path.file_stem());
+        match settings.layout {
+            TextureLayout::Single => Ok(Texture::from_image(uuid, name, &image, tex_specs)),
+            TextureLayout::Equirectangular { face_size } => {
+                let face_size = face_size.unwrap_or(image.height() / 2);
+                Texture::cube_map_from_equirectangular(uuid, name, &image, face_size, tex_specs)
+            },
+            TextureLayout::CubeFaces => {
+                let size = image.width() / 6;
+                let faces = std::array::from_fn(|face| image.crop_imm(face as u32 * size, 0, size, image.height()));
+
+                Texture::cube_map_from_faces(uuid, name, &faces, tex_specs)
+            },
+            TextureLayout::Array { layers } => {
+                let height = image.height() / layers.max(1);
+                let layers = (0..layers)
+                    .map(|layer| image.crop_imm(0, layer * height, image.width(), height))
+                    .collect::<Vec<_>>();
+
+                Texture::array_from_images(uuid, name, &layers, tex_specs)
+            },
+        }
+    }
+    
+    fn import_mesh(path: &AssetPath, bytes: &[u8], context: &ImportContext) -> Result<Mesh, StdError> {
+        let mut mesh = Mesh::try_from(context.import(path, bytes)?)?;
+
+        let settings = &context.meta().import;
+        if settings.weld_vertices {
+            mesh.weld(WELD_EPSILON);
+        }
+        if settings.optimize_vertex_cache {
+            mesh.optimize_vertex_cache();
+        }
+        if settings.tangents {
+            mesh.generate_tangents();
+        }
+
+        Ok(mesh)
+    }
+
+    fn load_mesh(&mut self, path: &AssetPath, uuid: &UUID) -> Result<&mut Mesh, StdError> {
+        let bytes = self.vfs.read(path)?;
+        let mesh = Self::import_mesh(path, &bytes, &self.import_context(uuid))?;
+        let uuid = *mesh.uuid();
+
+        self.to_init_gl.meshes.push(uuid);
+        self.dependency_graph.set_mesh(&mesh);
+
+        Ok(self.meshes.entry(uuid).or_insert(mesh))
+    }
+
+    fn load_shader(&mut self, path: &AssetPath, uuid: &UUID) -> Result<&mut Shader, StdError> {
+        let bytes = self.vfs.read(path)?;
+        let shader = Self::import_shader(path, &bytes, &self.import_context(uuid))?;
+
+        Ok(self.shaders.entry(*shader.uuid()).or_insert(shader))
+    }
+
+    fn import_shader(path: &AssetPath, bytes: &[u8], context: &ImportContext) -> Result<Shader, StdError> {
+        Shader::try_from(context.import(path, bytes)?)
+    }
+
+    fn import_glsl(path: &AssetPath, bytes: &[u8], context: &ImportContext) -> Result<Shader, StdError> {
+        let uuid = context.meta.uuid;
+        let name = path.file_stem().to_string();
+        let stage = ShaderStage::from_str(path.extension().unwrap_or_default())?;
+        let src_code = String::from_utf8(bytes.to_vec())?;
+        
+        Ok(Shader::new(
+            uuid, 
+            name, 
+            vec![], 
+            stage, 
+            src_code
+        ))
+    }
+    
+    fn load_material(&mut self, path: &AssetPath, uuid: &UUID) -> Result<&mut Material, StdError> {
+        let bytes = self.vfs.read(path)?;
+        let material = Self::import_material(path, &bytes, &self.import_context(uuid))?;
+        let uuid = *material.uuid();
+        self.save_migrated_lgmat(path);
+
+        self.to_init_gl.materials.push(uuid);
+        self.dependency_graph.set_material(&material);
+
+        Ok(self.materials.entry(uuid).or_insert(material))
+    }
+
+    fn import_material(path: &AssetPath, bytes: &[u8], context: &ImportContext) -> Result<Material, StdError> {
+        Material::try_from(context.import(path, bytes)?)
+    }
+}
@@ -0,0 +1,181 @@
+use std::{cell::RefCell, io::BufReader};
+use sllog::warn;
+use crate::{glm, lg_core::{renderer::{material::Material, mesh::{Mesh, SubMesh}, mesh_processing, vertex::Vertex}, uuid::UUID}, StdError};
+use super::{meta::{sub_asset_uuid, NormalGeneration}, AssetManager, AssetMeta, AssetPath, ImportContext, ImportSettings, IMPORTED_FRAGMENT_SHADER, IMPORTED_VERTEX_SHADER};
+
+/// Every material of an .mtl file is a sub-asset, e.g. asset://objects/cube.mtl#Red.
+pub(super) const MTL_EXTENSION: &str = "mtl";
+
+// Public(crate)
+impl AssetManager {
+    /// Every model of the file is a SubMesh, missing normals are generated and missing UVs are box projected from the bounds.
+    pub(super) fn import_obj(path: &AssetPath, bytes: &[u8], context: &ImportContext) -> Result<Mesh, StdError> {
+        // Path of every material tobj loads, in the same order.
+        let material_paths = RefCell::new(Vec::new());
+
+        let (models, materials) = tobj::load_obj_buf(
+            &mut BufReader::new(bytes),
+            &tobj::GPU_LOAD_OPTIONS,
+            |mtl| {
+                let mtl_path = mtl.to_str()
+                    .and_then(|mtl| path.sibling(mtl).ok())
+                    .ok_or(tobj::LoadError::OpenFileFailed)?;
+                let mtl_bytes = context.vfs()
+                    .read(&mtl_path)
+                    .map_err(|_| tobj::LoadError::OpenFileFailed)?;
+
+                let result = tobj::load_mtl_buf(&mut BufReader::new(mtl_bytes.as_slice()));
+                if let Ok((materials, _)) = &result {
+                    material_paths.borrow_mut().extend(materials.iter().map(|m| mtl_path.with_label(&m.name)));
+                }
+
+                result
+            }
+        )?;
+
+        if let Err(e) = materials {
+            warn!("Failed to load the materials of {}: {e} (AssetManager)", path);
+        }
+        let material_paths = material_paths.into_inner();
+
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        let mut sub_meshes = Vec::new();
+
+        for model in models.iter().filter(|m| !m.mesh.indices.is_empty()) {
+            let (model_vertices, model_indices) = obj_vertices(&model.mesh, &context.meta().import);
+            let first_vertex = vertices.len() as u32;
+            let first_index = indices.len() as u32;
+
+            vertices.extend(model_vertices);
+            indices.extend(model_indices.iter().map(|i| i + first_vertex));
+
+            let material = match model.mesh.material_id.and_then(|i| material_paths.get(i)) {
+                Some(material_path) => Some(sub_asset_uuid(material_path)?),
+                None => None,
+            };
+
+            sub_meshes.push(SubMesh {
+                name: model.name.clone(),
+                first_index,
+                index_count: indices.len() as u32 - first_index,
+                material,
+            });
+        }
+
+        if sub_meshes.is_empty() {
+            return Err(std::format!("{} has no models, it needs at least one object with faces! (AssetManager)", path).into());
+        }
+
+        Ok(Mesh::with_sub_meshes(
+            context.meta().uuid,
+            path.file_stem(),
+            vertices,
+            indices,
+            sub_meshes
+        ))
+    }
+
+    /// The material named after the label of path, its diffuse texture is resolved from the .mtl directory.
+    pub(super) fn import_mtl_material(path: &AssetPath, bytes: &[u8], context: &ImportContext) -> Result<Material, StdError> {
+        let name = path
+            .label()
+            .ok_or(std::format!("{} has no material name! (AssetManager)", path))?;
+
+        let (materials, names) = tobj::load_mtl_buf(&mut BufReader::new(bytes))?;
+        let material = names
+            .get(name)
+            .map(|i| &materials[*i])
+            .ok_or(std::format!("{} is not in {}! (AssetManager)", name, path.file()))?;
+
+        let textures = match &material.diffuse_texture {
+            Some(texture) => vec![context.resolve(&path.sibling(texture)?.to_string())?],
+            None => Vec::new(),
+        };
+
+        let shaders = vec![
+            context.resolve(IMPORTED_VERTEX_SHADER)?,
+            context.resolve(IMPORTED_FRAGMENT_SHADER)?,
+        ];
+
+        Ok(Material::new(
+            context.meta().uuid,
+            name,
+            shaders,
+            textures,
+            vec![]
+        ))
+    }
+
+    /// Registers every material of the .mtl file, they have no .meta file.
+    pub(super) fn store_mtl_materials(&mut self, path: &AssetPath) -> Result<Vec<UUID>, StdError> {
+        let bytes = self.vfs.read(path)?;
+        let (materials, _) = tobj::load_mtl_buf(&mut BufReader::new(bytes.as_slice()))?;
+
+        materials
+            .iter()
+            .map(|m| {
+                let material_path = path.with_label(&m.name);
+                let meta = AssetMeta::new(sub_asset_uuid(&material_path)?);
+
+                self.register_asset(material_path, meta)
+            })
+            .collect()
+    }
+}
+
+/// Indices are local to the model.
+fn obj_vertices(mesh: &tobj::Mesh, settings: &ImportSettings) -> (Vec<Vertex>, Vec<u32>) {
+    let vertex_count = mesh.positions.len() / 3;
+    let has_normals = mesh.normals.len() == mesh.positions.len();
+    let has_tex_coords = mesh.texcoords.len() / 2 == vertex_count;
+
+    let vertices = (0..vertex_count)
+        .map(|i| {
+            let p = &mesh.positions[i * 3..i * 3 + 3];
+
+            let normal = match has_normals {
+                true => glm::vec3(mesh.normals[i * 3], mesh.normals[i * 3 + 1], mesh.normals[i * 3 + 2]),
+                false => glm::Vec3::zeros(),
+            };
+
+            let tex_coord = match has_tex_coords {
+                true => {
+                    let tc = &mesh.texcoords[i * 2..i * 2 + 2];
+                    let v = if settings.flip_uv { 1.0 + tc[1] } else { -tc[1] };
+
+                    glm::vec2(-tc[0], v)
+                },
+                false => glm::Vec2::zeros(),
+            };
+
+            Vertex {
+                position: glm::vec3(p[0], p[1], p[2]),
+                normal,
+                tex_coord,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let (mut vertices, indices) = match has_normals {
+        true => (vertices, mesh.indices.clone()),
+        false => generate_normals(vertices, mesh.indices.clone(), settings.normals),
+    };
+    if !has_tex_coords {
+        mesh_processing::box_uvs(&mut vertices);
+    }
+
+    (vertices, indices)
+}
+
+/// For meshes without normals, flat ones don't share vertices.
+pub(super) fn generate_normals(mut vertices: Vec<Vertex>, indices: Vec<u32>, normals: NormalGeneration) -> (Vec<Vertex>, Vec<u32>) {
+    match normals {
+        NormalGeneration::Smooth => {
+            mesh_processing::smooth_normals(&mut vertices, &indices);
+
+            (vertices, indices)
+        },
+        NormalGeneration::Flat => mesh_processing::flat_normals(&vertices, &indices),
+    }
+}
@@ -0,0 +1,378 @@
+use std::{collections::HashMap, fs::File, io::{BufReader, Read, Seek, SeekFrom, Write}, path::Path, sync::{Arc, Mutex}};
+use sha2::Digest;
+use crate::{lg_core::uuid::UUID, profile_function, StdError};
+use super::{meta::META_EXTENSION, vfs::{DirMount, Mount, Vfs, ROOT_PRIORITY}, AssetManager, AssetMeta, AssetPath};
+
+pub const PACK_EXTENSION: &str = "lgpak";
+
+const MAGIC: &[u8; 8] = b"LGPAK\0\0\0";
+const VERSION: u32 = 1;
+/// Magic, version, index offset, entry count.
+const HEADER_SIZE: u64 = 8 + 4 + 8 + 4;
+/// UUID, two empty strings, compression, hash, sizes and offset.
+const MIN_ENTRY_SIZE: u64 = 16 + 4 + 4 + 1 + 32 + 8 + 8 + 8;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    #[default]
+    None,
+    Deflate,
+}
+impl Compression {
+    fn from_u8(value: u8) -> Result<Self, StdError> {
+        match value {
+            0 => Ok(Self::None),
+            1 => Ok(Self::Deflate),
+            _ => Err(std::format!("Unknown compression {}! (AssetPack)", value).into()),
+        }
+    }
+
+    fn to_u8(self) -> u8 {
+        match self {
+            Self::None => 0,
+            Self::Deflate => 1,
+        }
+    }
+}
+
+/// Index entry, data is at offset in the pack.
+#[derive(Debug, Clone)]
+pub struct PackEntry {
+    pub uuid: UUID,
+    pub path: AssetPath,
+    pub meta: AssetMeta,
+    pub compression: Compression,
+    /// SHA-256 of the uncompressed data.
+    pub hash: [u8; 32],
+    /// Uncompressed.
+    pub size: u64,
+    offset: u64,
+    stored_size: u64,
+}
+
+/// Read only archive of assets by UUID.
+///
+/// Layout: header, entry data, then the index (uuid, path, meta, compression, hash, sizes, offset of every entry).
+/// Numbers are little endian, strings are a u32 length followed by UTF-8.
+///
+/// As a Mount it also serves the .meta file of every entry.
+#[derive(Debug)]
+pub struct AssetPack {
+    entries: HashMap<UUID, PackEntry>,
+    paths: HashMap<AssetPath, UUID>,
+    file: Mutex<File>,
+}
+impl AssetPack {
+    /// Lengths and offsets are checked against the file size, so a truncated or corrupt pack is an error.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StdError> {
+        profile_function!();
+
+        let mut file = File::open(path.as_ref())
+            .map_err(|e| std::format!("Failed to open {}: {e} (AssetPack)", path.as_ref().display()))?;
+
+        let mut magic = [0u8; 8];
+        file.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(std::format!("{} is not an asset pack! (AssetPack)", path.as_ref().display()).into());
+        }
+
+        let version = read_u32(&mut file)?;
+        if version != VERSION {
+            return Err(std::format!("Unsupported asset pack version {}! (AssetPack)", version).into());
+        }
+
+        let index_offset = read_u64(&mut file)?;
+        let count = read_u32(&mut file)?;
+
+        let file_size = file.metadata()?.len();
+        if index_offset < HEADER_SIZE || index_offset > file_size {
+            return Err(std::format!("{} has an invalid index offset! (AssetPack)", path.as_ref().display()).into());
+        }
+        let index_size = file_size - index_offset;
+        if count as u64 * MIN_ENTRY_SIZE > index_size {
+            return Err(std::format!("{} is truncated, its index is too short! (AssetPack)", path.as_ref().display()).into());
+        }
+
+        file.seek(SeekFrom::Start(index_offset))?;
+        // Reading past the index fails instead of reading whatever follows.
+        let mut reader = BufReader::new(&mut file).take(index_size);
+
+        let mut entries = HashMap::with_capacity(count as usize);
+        for _ in 0..count {
+            let uuid = UUID::from_u128(read_u128(&mut reader)?);
+            let path = AssetPath::new(&read_string(&mut reader, index_size)?)?;
+            let meta = serializer::deserialize_str(&read_string(&mut reader, index_size)?)?;
+            let compression = Compression::from_u8(read_u8(&mut reader)?)?;
+
+            let mut hash = [0u8; 32];
+            reader.read_exact(&mut hash)?;
+
+            let size = read_u64(&mut reader)?;
+            let offset = read_u64(&mut reader)?;
+            let stored_size = read_u64(&mut reader)?;
+
+            // The data is between the header and the index.
+            if offset < HEADER_SIZE || offset.checked_add(stored_size).is_none_or(|end| end > index_offset) {
+                return Err(std::format!("The data of {} is out of bounds! (AssetPack)", path).into());
+            }
+            if compression == Compression::None && size != stored_size {
+                return Err(std::format!("The size of {} doesn't match its stored size! (AssetPack)", path).into());
+            }
+
+            if entries.contains_key(&uuid) {
+                return Err(std::format!("{} is in the asset pack twice! (AssetPack)", uuid).into());
+            }
+            entries.insert(uuid, PackEntry { uuid, path, meta, compression, hash, size, offset, stored_size });
+        }
+
+        let paths = entries
+            .values()
+            .map(|e| (e.path.clone(), e.uuid))
+            .collect();
+
+        Ok(Self {
+            entries,
+            paths,
+            file: Mutex::new(file),
+        })
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &PackEntry> {
+        self.entries.values()
+    }
+
+    pub fn entry(&self, uuid: &UUID) -> Option<&PackEntry> {
+        self.entries.get(uuid)
+    }
+
+    pub fn contains(&self, uuid: &UUID) -> bool {
+        self.entries.contains_key(uuid)
+    }
+
+    /// Uncompressed data, fails if it doesn't match the content hash.
+    pub fn read_entry(&self, uuid: &UUID) -> Result<Vec<u8>, StdError> {
+        profile_function!();
+
+        let entry = self.entries
+            .get(uuid)
+            .ok_or(std::format!("{} is not in the asset pack! (AssetPack)", uuid))?;
+
+        let mut stored = vec![0u8; entry.stored_size as usize];
+        {
+            let mut file = self.file.lock().unwrap();
+            file.seek(SeekFrom::Start(entry.offset))?;
+            file.read_exact(&mut stored)?;
+        }
+
+        let data = match entry.compression {
+            Compression::None => stored,
+            Compression::Deflate => {
+                // Not preallocated and never more than the size, which is only checked by the hash.
+                let mut data = Vec::new();
+                flate2::read::DeflateDecoder::new(stored.as_slice())
+                    .take(entry.size)
+                    .read_to_end(&mut data)?;
+
+                data
+            },
+        };
+
+        if sha2::Sha256::digest(&data).as_slice() != entry.hash {
+            return Err(std::format!("{} is corrupted, the content hash doesn't match! (AssetPack)", entry.path).into());
+        }
+
+        Ok(data)
+    }
+}
+impl Mount for AssetPack {
+    fn exists(&self, path: &AssetPath) -> bool {
+        self.paths.contains_key(path) || path
+            .strip_extension(META_EXTENSION)
+            .is_some_and(|p| self.paths.contains_key(&p))
+    }
+
+    fn read(&self, path: &AssetPath) -> Result<Vec<u8>, StdError> {
+        if let Some(uuid) = self.paths.get(path) {
+            return self.read_entry(uuid);
+        }
+
+        let entry = path
+            .strip_extension(META_EXTENSION)
+            .and_then(|p| self.paths.get(&p))
+            .map(|uuid| &self.entries[uuid])
+            .ok_or(std::format!("{} is not in the asset pack! (AssetPack)", path))?;
+
+        Ok(serializer::serialize_str(&entry.meta)?.into_bytes())
+    }
+
+    fn files(&self) -> Vec<AssetPath> {
+        self.paths
+            .keys()
+            .flat_map(|p| [p.clone(), p.with_added_extension(META_EXTENSION)])
+            .collect()
+    }
+}
+
+/// Packs assets into an .lgpak file.
+#[derive(Debug, Default)]
+pub struct PackBuilder {
+    compression: Compression,
+    entries: Vec<(AssetPath, AssetMeta, Vec<u8>, Compression)>,
+}
+impl PackBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Used for the entries added after it.
+    pub fn compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    pub fn add(mut self, path: AssetPath, meta: AssetMeta, data: Vec<u8>) -> Self {
+        self.entries.push((path, meta, data, self.compression));
+        self
+    }
+
+    /// Adds every asset under root, with the UUID and import settings of its .meta file.
+    ///
+    /// Assets without a .meta file get the UUID the AssetManager would give them, no file is created.
+    pub fn add_dir(self, root: impl AsRef<Path>) -> Result<Self, StdError> {
+        let mut vfs = Vfs::new();
+        vfs.mount(Arc::new(DirMount::new(root.as_ref())), ROOT_PRIORITY);
+
+        self.add_vfs(&vfs)
+    }
+
+    /// Adds every asset of the Vfs, as the AssetManager would see it.
+    pub fn add_vfs(mut self, vfs: &Vfs) -> Result<Self, StdError> {
+        profile_function!();
+
+        for path in vfs.files() {
+            match path.extension() {
+                Some(extension) if extension != META_EXTENSION => (),
+                _ => continue,
+            }
+
+            let meta = match vfs.exists(&AssetMeta::path_of(&path)) {
+                true => AssetMeta::read(vfs, &path)?,
+                false => AssetManager::default_meta(&path, vfs),
+            };
+
+            let data = vfs.read(&path)?;
+            self.entries.push((path, meta, data, self.compression));
+        }
+
+        Ok(self)
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), StdError> {
+        profile_function!();
+
+        let mut file = std::io::BufWriter::new(File::create(path.as_ref())?);
+
+        file.write_all(MAGIC)?;
+        file.write_all(&VERSION.to_le_bytes())?;
+        // Index offset, written once the data is.
+        file.write_all(&0u64.to_le_bytes())?;
+        file.write_all(&(self.entries.len() as u32).to_le_bytes())?;
+
+        let mut index = Vec::new();
+        let mut offset = HEADER_SIZE;
+
+        for (path, meta, data, compression) in &self.entries {
+            let (stored, compression) = match compression {
+                Compression::None => (data.clone(), Compression::None),
+                Compression::Deflate => {
+                    let mut encoder = flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+                    encoder.write_all(data)?;
+                    let compressed = encoder.finish()?;
+
+                    // Already compressed data (e.g. png) is stored as it is.
+                    match compressed.len() < data.len() {
+                        true => (compressed, Compression::Deflate),
+                        false => (data.clone(), Compression::None),
+                    }
+                },
+            };
+
+            file.write_all(&stored)?;
+
+            index.extend_from_slice(&meta.uuid.get_value().to_le_bytes());
+            write_string(&mut index, &path.to_string());
+            write_string(&mut index, &serializer::serialize_str(meta)?);
+            index.push(compression.to_u8());
+            index.extend_from_slice(sha2::Sha256::digest(data).as_slice());
+            index.extend_from_slice(&(data.len() as u64).to_le_bytes());
+            index.extend_from_slice(&offset.to_le_bytes());
+            index.extend_from_slice(&(stored.len() as u64).to_le_bytes());
+
+            offset += stored.len() as u64;
+        }
+
+        file.write_all(&index)?;
+
+        let mut file = file.into_inner().map_err(|e| e.to_string())?;
+        file.seek(SeekFrom::Start(MAGIC.len() as u64 + 4))?;
+        file.write_all(&offset.to_le_bytes())?;
+
+        Ok(())
+    }
+}
+
+// Public
+impl AssetManager {
+    /// Assets of the pack are served from it instead of the mounts below priority, e.g. the asset root.
+    pub fn mount_pack(&mut self, path: impl AsRef<Path>, priority: i32) -> Result<(), StdError> {
+        profile_function!();
+
+        self.mount(Arc::new(AssetPack::open(path)?), priority)
+    }
+}
+
+fn read_u8(reader: &mut impl Read) -> Result<u8, StdError> {
+    let mut bytes = [0u8; 1];
+    reader.read_exact(&mut bytes)?;
+
+    Ok(bytes[0])
+}
+
+fn read_u32(reader: &mut impl Read) -> Result<u32, StdError> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(reader: &mut impl Read) -> Result<u64, StdError> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn read_u128(reader: &mut impl Read) -> Result<u128, StdError> {
+    let mut bytes = [0u8; 16];
+    reader.read_exact(&mut bytes)?;
+
+    Ok(u128::from_le_bytes(bytes))
+}
+
+/// Longer than max is an error, nothing is allocated for it.
+fn read_string(reader: &mut impl Read, max: u64) -> Result<String, StdError> {
+    let len = read_u32(reader)?;
+    if len as u64 > max {
+        return Err(std::format!("String of length {} is longer than the index! (AssetPack)", len).into());
+    }
+
+    let mut bytes = vec![0u8; len as usize];
+    reader.read_exact(&mut bytes)?;
+
+    Ok(String::from_utf8(bytes)?)
+}
+
+fn write_string(buffer: &mut Vec<u8>, value: &str) {
+    buffer.extend_from_slice(&(value.len() as u32).to_le_bytes());
+    buffer.extend_from_slice(value.as_bytes());
+}
@@ -0,0 +1,254 @@
+use crate::{glm, lg_core::renderer::{mesh::Mesh, mesh_processing, vertex::Vertex}, StdError};
+use super::{obj::generate_normals, AssetManager, AssetPath, ImportContext};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PlyFormat {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PlyType {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+impl PlyType {
+    fn from(name: &str) -> Result<Self, StdError> {
+        match name {
+            "char" | "int8" => Ok(Self::I8),
+            "uchar" | "uint8" => Ok(Self::U8),
+            "short" | "int16" => Ok(Self::I16),
+            "ushort" | "uint16" => Ok(Self::U16),
+            "int" | "int32" => Ok(Self::I32),
+            "uint" | "uint32" => Ok(Self::U32),
+            "float" | "float32" => Ok(Self::F32),
+            "double" | "float64" => Ok(Self::F64),
+            _ => Err(std::format!("{} is an invalid PLY property type! (AssetManager)", name).into()),
+        }
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            PlyType::I8 | PlyType::U8 => 1,
+            PlyType::I16 | PlyType::U16 => 2,
+            PlyType::I32 | PlyType::U32 | PlyType::F32 => 4,
+            PlyType::F64 => 8,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct PlyProperty {
+    name: String,
+    value: PlyType,
+    /// Type of the count, for lists.
+    list: Option<PlyType>,
+}
+
+#[derive(Debug)]
+struct PlyElement {
+    name: String,
+    count: usize,
+    properties: Vec<PlyProperty>,
+}
+
+/// Values of the body, in file order.
+struct PlyReader<'a> {
+    format: PlyFormat,
+    bytes: &'a [u8],
+    tokens: std::str::SplitAsciiWhitespace<'a>,
+}
+impl PlyReader<'_> {
+    fn read(&mut self, value: PlyType) -> Result<f64, StdError> {
+        if self.format == PlyFormat::Ascii {
+            return Ok(self.tokens
+                .next()
+                .ok_or("PLY file ended too early! (AssetManager)")?
+                .parse::<f64>()?);
+        }
+
+        if self.bytes.len() < value.size() {
+            return Err("PLY file ended too early! (AssetManager)".into());
+        }
+        let (bytes, rest) = self.bytes.split_at(value.size());
+        self.bytes = rest;
+
+        let little = self.format == PlyFormat::BinaryLittleEndian;
+        macro_rules! number {
+            ($t:ty) => {{
+                let bytes = bytes.try_into()?;
+                (if little { <$t>::from_le_bytes(bytes) } else { <$t>::from_be_bytes(bytes) }) as f64
+            }};
+        }
+
+        Ok(match value {
+            PlyType::I8 => number!(i8),
+            PlyType::U8 => number!(u8),
+            PlyType::I16 => number!(i16),
+            PlyType::U16 => number!(u16),
+            PlyType::I32 => number!(i32),
+            PlyType::U32 => number!(u32),
+            PlyType::F32 => number!(f32),
+            PlyType::F64 => number!(f64),
+        })
+    }
+}
+
+// Public(crate)
+impl AssetManager {
+    /// ASCII and binary PLY, the vertex and face elements are read and polygons are triangulated as fans.
+    ///
+    /// Missing normals are generated and missing UVs (s, t or u, v) are box projected from the bounds.
+    pub(super) fn import_ply(path: &AssetPath, bytes: &[u8], context: &ImportContext) -> Result<Mesh, StdError> {
+        let (format, elements, body) = ply_header(bytes)
+            .map_err(|e| std::format!("{}: {e}", path))?;
+
+        let mut reader = PlyReader {
+            format,
+            bytes: body,
+            tokens: match format {
+                PlyFormat::Ascii => std::str::from_utf8(body)?.split_ascii_whitespace(),
+                _ => "".split_ascii_whitespace(),
+            },
+        };
+
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        let mut has_normals = false;
+        let mut has_tex_coords = false;
+
+        for element in &elements {
+            let property = |names: &[&str]| element.properties
+                .iter()
+                .position(|p| names.contains(&p.name.as_str()));
+
+            let position = [property(&["x"]), property(&["y"]), property(&["z"])];
+            let normal = [property(&["nx"]), property(&["ny"]), property(&["nz"])];
+            let tex_coord = [property(&["s", "u", "texture_s", "texture_u"]), property(&["t", "v", "texture_t", "texture_v"])];
+            let face = property(&["vertex_indices", "vertex_index"]);
+
+            if element.name == "vertex" {
+                has_normals = normal.iter().all(Option::is_some);
+                has_tex_coords = tex_coord.iter().all(Option::is_some);
+            }
+
+            let mut values = vec![0.0; element.properties.len()];
+            let mut list = Vec::new();
+            for _ in 0..element.count {
+                for (i, p) in element.properties.iter().enumerate() {
+                    match p.list {
+                        Some(count) => {
+                            let count = reader.read(count)? as usize;
+                            let items = (0..count)
+                                .map(|_| reader.read(p.value))
+                                .collect::<Result<Vec<_>, _>>()?;
+
+                            if Some(i) == face { list = items; }
+                        },
+                        None => values[i] = reader.read(p.value)?,
+                    }
+                }
+
+                match element.name.as_str() {
+                    "vertex" => {
+                        let get = |i: Option<usize>| i.map_or(0.0, |i| values[i] as f32);
+                        let (u, v) = (get(tex_coord[0]), get(tex_coord[1]));
+
+                        // Same convention as the OBJ importer.
+                        vertices.push(Vertex {
+                            position: glm::vec3(get(position[0]), get(position[1]), get(position[2])),
+                            normal: glm::vec3(get(normal[0]), get(normal[1]), get(normal[2])),
+                            tex_coord: glm::vec2(-u, if context.meta().import.flip_uv { 1.0 + v } else { -v }),
+                        });
+                    },
+                    "face" => for i in 1..list.len().saturating_sub(1) {
+                        indices.extend([list[0] as u32, list[i] as u32, list[i + 1] as u32]);
+                    },
+                    _ => (),
+                }
+            }
+        }
+
+        if indices.is_empty() {
+            return Err(std::format!("{} has no faces! (AssetManager)", path).into());
+        }
+        if let Some(i) = indices.iter().find(|i| **i as usize >= vertices.len()) {
+            return Err(std::format!("Index {} of {} is out of bounds! (AssetManager)", i, path).into());
+        }
+
+        let (mut vertices, indices) = match has_normals {
+            true => (vertices, indices),
+            false => generate_normals(vertices, indices, context.meta().import.normals),
+        };
+        if !has_tex_coords {
+            mesh_processing::box_uvs(&mut vertices);
+        }
+
+        Ok(Mesh::new(
+            context.meta().uuid,
+            path.file_stem(),
+            vertices,
+            indices
+        ))
+    }
+}
+
+/// Format, elements and the bytes after end_header.
+fn ply_header(bytes: &[u8]) -> Result<(PlyFormat, Vec<PlyElement>, &[u8]), StdError> {
+    const END_HEADER: &[u8] = b"end_header";
+
+    let end = bytes
+        .windows(END_HEADER.len())
+        .position(|w| w == END_HEADER)
+        .ok_or("PLY header has no end_header! (AssetManager)")?;
+    let body_start = bytes[end..]
+        .iter()
+        .position(|b| *b == b'\n')
+        .map_or(bytes.len(), |i| end + i + 1);
+
+    let header = std::str::from_utf8(&bytes[..end])?;
+    let mut lines = header.lines().map(str::trim);
+    if lines.next() != Some("ply") {
+        return Err("Not a PLY file! (AssetManager)".into());
+    }
+
+    let mut format = None;
+    let mut elements: Vec<PlyElement> = Vec::new();
+    for line in lines {
+        let words = line.split_ascii_whitespace().collect::<Vec<_>>();
+
+        match words.as_slice() {
+            ["format", "ascii", ..] => format = Some(PlyFormat::Ascii),
+            ["format", "binary_little_endian", ..] => format = Some(PlyFormat::BinaryLittleEndian),
+            ["format", "binary_big_endian", ..] => format = Some(PlyFormat::BinaryBigEndian),
+            ["element", name, count] => elements.push(PlyElement {
+                name: name.to_string(),
+                count: count.parse()?,
+                properties: Vec::new(),
+            }),
+            ["property", "list", count, value, name] => elements
+                .last_mut()
+                .ok_or("PLY property outside of an element! (AssetManager)")?
+                .properties
+                .push(PlyProperty { name: name.to_string(), value: PlyType::from(value)?, list: Some(PlyType::from(count)?) }),
+            ["property", value, name] => elements
+                .last_mut()
+                .ok_or("PLY property outside of an element! (AssetManager)")?
+                .properties
+                .push(PlyProperty { name: name.to_string(), value: PlyType::from(value)?, list: None }),
+            ["comment", ..] | ["obj_info", ..] | [] => (),
+            _ => return Err(std::format!("Invalid PLY header line: {}! (AssetManager)", line).into()),
+        }
+    }
+
+    let format = format.ok_or("PLY header has no format! (AssetManager)")?;
+
+    Ok((format, elements, &bytes[body_start..]))
+}
@@ -0,0 +1,102 @@
+use crate::{glm, lg_core::renderer::{mesh::Mesh, mesh_processing, vertex::Vertex}, StdError};
+use super::{AssetManager, AssetPath, ImportContext};
+
+const STL_HEADER_SIZE: usize = 80;
+/// Normal, 3 vertices and the attribute byte count.
+const STL_TRIANGLE_SIZE: usize = 50;
+
+// Public(crate)
+impl AssetManager {
+    /// ASCII and binary STL, vertices are not shared so the normals stay flat. STL has no UVs, they are box projected.
+    pub(super) fn import_stl(path: &AssetPath, bytes: &[u8], context: &ImportContext) -> Result<Mesh, StdError> {
+        // Binary files may start with "solid" too, their size is exact.
+        let binary_count = bytes
+            .get(STL_HEADER_SIZE..STL_HEADER_SIZE + 4)
+            .map(|c| u32::from_le_bytes(c.try_into().unwrap()) as usize);
+        let is_binary = binary_count.is_some_and(|count| bytes.len() == STL_HEADER_SIZE + 4 + count * STL_TRIANGLE_SIZE);
+
+        let triangles = match is_binary {
+            true => binary_triangles(&bytes[STL_HEADER_SIZE + 4..]),
+            false => ascii_triangles(std::str::from_utf8(bytes)?)
+                .map_err(|e| std::format!("{}: {e}", path))?,
+        };
+
+        if triangles.is_empty() {
+            return Err(std::format!("{} has no triangles! (AssetManager)", path).into());
+        }
+
+        let mut vertices = Vec::with_capacity(triangles.len() * 3);
+        for (normal, corners) in triangles {
+            // The stored normal is often zero, the corners are counter-clockwise.
+            let face_normal = (corners[1] - corners[0]).cross(&(corners[2] - corners[0]));
+            let normal = match normal.norm() > f32::EPSILON {
+                true => normal.normalize(),
+                false if face_normal.norm() > f32::EPSILON => face_normal.normalize(),
+                false => glm::Vec3::zeros(),
+            };
+
+            vertices.extend(corners.map(|position| Vertex {
+                position,
+                normal,
+                tex_coord: glm::Vec2::zeros(),
+            }));
+        }
+        mesh_processing::box_uvs(&mut vertices);
+        let indices = (0..vertices.len() as u32).collect();
+
+        Ok(Mesh::new(
+            context.meta().uuid,
+            path.file_stem(),
+            vertices,
+            indices
+        ))
+    }
+}
+
+/// (normal, corners)
+type StlTriangle = (glm::Vec3, [glm::Vec3; 3]);
+
+fn binary_triangles(bytes: &[u8]) -> Vec<StlTriangle> {
+    let vec3 = |b: &[u8]| {
+        let f = |i: usize| f32::from_le_bytes(b[i * 4..i * 4 + 4].try_into().unwrap());
+        glm::vec3(f(0), f(1), f(2))
+    };
+
+    bytes
+        .chunks_exact(STL_TRIANGLE_SIZE)
+        .map(|t| (vec3(&t[0..12]), [vec3(&t[12..24]), vec3(&t[24..36]), vec3(&t[36..48])]))
+        .collect()
+}
+
+fn ascii_triangles(text: &str) -> Result<Vec<StlTriangle>, StdError> {
+    let mut triangles = Vec::new();
+    let mut normal = glm::Vec3::zeros();
+    let mut corners = Vec::with_capacity(3);
+
+    let mut words = text.split_ascii_whitespace();
+    let vec3 = |words: &mut std::str::SplitAsciiWhitespace| -> Result<glm::Vec3, StdError> {
+        let mut f = || -> Result<f32, StdError> {
+            Ok(words.next().ok_or("STL file ended too early! (AssetManager)")?.parse::<f32>()?)
+        };
+
+        Ok(glm::vec3(f()?, f()?, f()?))
+    };
+
+    while let Some(word) = words.next() {
+        match word {
+            "normal" => normal = vec3(&mut words)?,
+            "vertex" => corners.push(vec3(&mut words)?),
+            "endfacet" => {
+                if corners.len() != 3 {
+                    return Err(std::format!("STL facet has {} vertices instead of 3! (AssetManager)", corners.len()).into());
+                }
+
+                triangles.push((normal, [corners[0], corners[1], corners[2]]));
+                corners.clear();
+            },
+            _ => (),
+        }
+    }
+
+    Ok(triangles)
+}
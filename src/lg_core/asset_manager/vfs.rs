@@ -0,0 +1,225 @@
+use std::{borrow::Cow, collections::{BTreeSet, HashMap}, path::{Path, PathBuf}, sync::{Arc, RwLock}};
+use crate::StdError;
+use super::AssetPath;
+
+/// Priority of the asset root, mounts above it override its files.
+pub const ROOT_PRIORITY: i32 = 0;
+
+/// A source of files for the Vfs.
+pub trait Mount: std::fmt::Debug + Send + Sync {
+    fn exists(&self, path: &AssetPath) -> bool;
+
+    fn read(&self, path: &AssetPath) -> Result<Vec<u8>, StdError>;
+
+    /// Every file, .meta files included.
+    fn files(&self) -> Vec<AssetPath>;
+
+    /// Read only unless overridden.
+    fn write(&self, path: &AssetPath, _bytes: &[u8]) -> Result<(), StdError> {
+        Err(std::format!("{} is read only! (Vfs)", path).into())
+    }
+}
+
+/// A real directory.
+#[derive(Debug, Clone)]
+pub struct DirMount {
+    root: PathBuf,
+}
+impl DirMount {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+}
+impl Mount for DirMount {
+    fn exists(&self, path: &AssetPath) -> bool {
+        path.to_fs(&self.root).is_file()
+    }
+
+    fn read(&self, path: &AssetPath) -> Result<Vec<u8>, StdError> {
+        let fs_path = path.to_fs(&self.root);
+
+        std::fs::read(&fs_path)
+            .map_err(|e| std::format!("Failed to read {}: {e} (Vfs)", fs_path.display()).into())
+    }
+
+    /// Empty if the directory doesn't exist.
+    fn files(&self) -> Vec<AssetPath> {
+        let mut files = Vec::new();
+        collect_files(&self.root, &self.root, &mut files);
+
+        files
+    }
+
+    fn write(&self, path: &AssetPath, bytes: &[u8]) -> Result<(), StdError> {
+        let fs_path = path.to_fs(&self.root);
+        if let Some(dir) = fs_path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        Ok(std::fs::write(fs_path, bytes)?)
+    }
+}
+
+/// Files kept in memory, e.g. embedded with include_bytes!.
+#[derive(Debug, Default)]
+pub struct MemoryMount {
+    files: RwLock<HashMap<AssetPath, Cow<'static, [u8]>>>,
+}
+impl MemoryMount {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&self, path: AssetPath, bytes: impl Into<Cow<'static, [u8]>>) {
+        self.files.write().unwrap().insert(path, bytes.into());
+    }
+
+    pub fn remove(&self, path: &AssetPath) -> bool {
+        self.files.write().unwrap().remove(path).is_some()
+    }
+}
+impl Mount for MemoryMount {
+    fn exists(&self, path: &AssetPath) -> bool {
+        self.files.read().unwrap().contains_key(path)
+    }
+
+    fn read(&self, path: &AssetPath) -> Result<Vec<u8>, StdError> {
+        self.files.read().unwrap()
+            .get(path)
+            .map(|bytes| bytes.to_vec())
+            .ok_or(std::format!("{} is not in memory! (Vfs)", path).into())
+    }
+
+    fn files(&self) -> Vec<AssetPath> {
+        self.files.read().unwrap().keys().cloned().collect()
+    }
+
+    fn write(&self, path: &AssetPath, bytes: &[u8]) -> Result<(), StdError> {
+        self.insert(path.clone(), bytes.to_vec());
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MountPoint {
+    priority: i32,
+    mount: Arc<dyn Mount>,
+}
+
+/// Every file the assets are read from, a path is served by the highest priority mount that has it.
+///
+/// Mounts with the same priority: the last one mounted wins.
+#[derive(Debug, Default, Clone)]
+pub struct Vfs {
+    mounts: Vec<MountPoint>,
+}
+impl Vfs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn mount(&mut self, mount: Arc<dyn Mount>, priority: i32) {
+        let index = self.mounts
+            .iter()
+            .position(|m| m.priority <= priority)
+            .unwrap_or(self.mounts.len());
+
+        self.mounts.insert(index, MountPoint { priority, mount });
+    }
+
+    pub fn unmount(&mut self, mount: &Arc<dyn Mount>) -> bool {
+        let len = self.mounts.len();
+        self.mounts.retain(|m| !Arc::ptr_eq(&m.mount, mount));
+
+        len != self.mounts.len()
+    }
+
+    /// The mount path is read from, labels are ignored.
+    pub fn find(&self, path: &AssetPath) -> Option<&Arc<dyn Mount>> {
+        let file = path.file();
+
+        self.mounts
+            .iter()
+            .find(|m| m.mount.exists(&file))
+            .map(|m| &m.mount)
+    }
+
+    pub fn exists(&self, path: &AssetPath) -> bool {
+        self.find(path).is_some()
+    }
+
+    /// Of the file, for sub-assets too.
+    pub fn read(&self, path: &AssetPath) -> Result<Vec<u8>, StdError> {
+        self.find(path)
+            .ok_or(std::format!("{} was not found! (Vfs)", path))?
+            .read(&path.file())
+    }
+
+    /// Into the mount that has path, new files go to the highest priority mount that accepts them,
+    /// ignoring the ones above ROOT_PRIORITY since those are overlays.
+    pub fn write(&self, path: &AssetPath, bytes: &[u8]) -> Result<(), StdError> {
+        if let Some(mount) = self.find(path) {
+            return mount.write(path, bytes);
+        }
+
+        let mut result = Err(std::format!("No mount accepts {}! (Vfs)", path).into());
+        for m in self.mounts.iter().filter(|m| m.priority <= ROOT_PRIORITY) {
+            result = m.mount.write(path, bytes);
+            if result.is_ok() { break; }
+        }
+
+        result
+    }
+
+    /// Every file of every mount, sorted.
+    pub fn files(&self) -> Vec<AssetPath> {
+        self.mounts
+            .iter()
+            .flat_map(|m| m.mount.files())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// Files under dir, e.g. "textures" or "asset://shaders/src".
+    pub fn list(&self, dir: &str) -> Result<Vec<AssetPath>, StdError> {
+        let prefix = std::format!("{}/", AssetPath::new(dir)?.as_str());
+
+        Ok(self.files()
+            .into_iter()
+            .filter(|p| p.as_str().starts_with(&prefix))
+            .collect())
+    }
+}
+impl serializer::FileSystem for Vfs {
+    fn read(&self, path: &str) -> Result<Vec<u8>, StdError> {
+        Vfs::read(self, &AssetPath::new(path)?)
+    }
+
+    fn write(&self, path: &str, bytes: &[u8]) -> Result<(), StdError> {
+        Vfs::write(self, &AssetPath::new(path)?, bytes)
+    }
+}
+
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<AssetPath>) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+
+        if path.is_dir() {
+            collect_files(root, &path, files);
+        }
+        else if let Some(asset_path) = AssetPath::from_fs(root, &path) {
+            files.push(asset_path);
+        }
+    }
+}
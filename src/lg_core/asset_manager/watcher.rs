@@ -0,0 +1,79 @@
+use std::{collections::HashMap, path::{Path, PathBuf}, time::{Duration, Instant, SystemTime}};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AssetChange {
+    Created(PathBuf),
+    Modified(PathBuf),
+    Removed(PathBuf),
+}
+
+/// Polls the modification time of every file under the asset root.
+#[derive(Debug)]
+pub(crate) struct AssetWatcher {
+    root: PathBuf,
+    interval: Duration,
+    last_poll: Instant,
+    files: HashMap<PathBuf, SystemTime>,
+}
+impl AssetWatcher {
+    pub(crate) fn new(root: impl AsRef<Path>, interval: Duration) -> Self {
+        let mut files = HashMap::new();
+        scan(root.as_ref(), &mut files);
+
+        Self {
+            root: root.as_ref().to_path_buf(),
+            interval,
+            last_poll: Instant::now(),
+            files,
+        }
+    }
+
+    /// Always empty until the interval has passed since the last poll.
+    pub(crate) fn poll(&mut self) -> Vec<AssetChange> {
+        if self.last_poll.elapsed() < self.interval {
+            return Vec::new();
+        }
+        self.last_poll = Instant::now();
+
+        let mut files = HashMap::with_capacity(self.files.len());
+        scan(&self.root, &mut files);
+
+        let mut changes = Vec::new();
+        for (path, modified) in &files {
+            match self.files.get(path) {
+                None => changes.push(AssetChange::Created(path.clone())),
+                Some(previous) if previous != modified => changes.push(AssetChange::Modified(path.clone())),
+                _ => (),
+            }
+        }
+
+        for path in self.files.keys() {
+            if !files.contains_key(path) {
+                changes.push(AssetChange::Removed(path.clone()));
+            }
+        }
+
+        self.files = files;
+
+        changes
+    }
+}
+
+/// Unreadable entries are skipped, they show up once they can be read.
+fn scan(dir: &Path, files: &mut HashMap<PathBuf, SystemTime>) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+
+        if path.is_dir() {
+            scan(&path, files);
+        }
+        else if let Ok(modified) = entry.metadata().and_then(|m| m.modified()) {
+            files.insert(path, modified);
+        }
+    }
+}
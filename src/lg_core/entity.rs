@@ -78,6 +78,26 @@ impl LgEntity {
         &self.uuid
     }
 }
+/// An entity and its children, their transforms are relative to it.
+#[derive(Default, Clone)]
+pub struct LgEntityNode {
+    pub entity: LgEntity,
+    pub children: Vec<LgEntityNode>,
+}
+impl LgEntityNode {
+    /// Every entity of the tree with its model matrix in the space of parent_model, parents first.
+    pub fn models(&self, parent_model: &glm::Mat4) -> Vec<(&LgEntity, glm::Mat4)> {
+        let model = parent_model * self.entity.model();
+
+        let mut result = vec![(&self.entity, model)];
+        for child in &self.children {
+            result.extend(child.models(&model));
+        }
+
+        result
+    }
+}
+
 // Private
 impl LgEntity {
     fn set_model(&mut self) {
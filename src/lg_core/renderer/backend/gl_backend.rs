@@ -0,0 +1,441 @@
+use std::{collections::HashMap, ffi::CString};
+use glutin::display::GlDisplay;
+use sllog::{error, warn};
+use crate::{gl_check, gl_check_and_print, lg_core::{asset_manager::AssetManager, renderer::{material::Material, opengl::{gl_buffer::GlBuffer, gl_sampler::GlSampler, GlSpecs}, render_state::RenderState, render_target::{FramebufferFormat, RenderTarget, RenderTargetSpecs}, sampler::SamplerSpecs, uniform::Uniform}}, profile_function, profile_scope, StdError};
+use super::{DrawCall, RenderBackend, RenderTargetId, TextureBinding};
+
+pub(crate) struct OpenGlBackend {
+    specs: GlSpecs,
+    targets: HashMap<RenderTargetId, RenderTarget>,
+    next_target: RenderTargetId,
+    /// Created the first time they are used, shared by every texture.
+    samplers: Vec<(SamplerSpecs, GlSampler)>,
+    /// Of the bound render target, for materials that don't set it.
+    depth_test: bool,
+}
+// Public(crate)
+impl OpenGlBackend {
+    /// Loads the OpenGL functions, the context has to be current on this thread.
+    pub(crate) fn new(specs: GlSpecs) -> Self {
+        let mut _symbol_debug = 0;
+
+        gl::load_with(|symbol| {
+            _symbol_debug += 1;
+
+            let symbol = CString::new(symbol).unwrap();
+            specs.gl_display.get_proc_address(symbol.as_c_str()) as *const _
+        });
+
+        warn!("OpenGL Symbols loaded: {}", _symbol_debug);
+
+        // Debug
+        gl_check_and_print!(gl::Enable(gl::DEBUG_OUTPUT));
+        gl_check_and_print!(gl::DebugMessageCallback(Some(debug_callback), std::ptr::null()));
+
+        // Depth, Blend
+        gl_check_and_print!(gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA));
+        gl_check_and_print!(gl::Enable(gl::BLEND));
+
+        Self {
+            specs,
+            targets: HashMap::default(),
+            next_target: 1,
+            samplers: Vec::new(),
+            depth_test: false,
+        }
+    }
+}
+// Private
+impl OpenGlBackend {
+    fn target(&self, target: RenderTargetId) -> Result<&RenderTarget, StdError> {
+        Ok(self.targets
+            .get(&target)
+            .ok_or(std::format!("Render target {} doesn't exist! (OpenGlBackend)", target))?)
+    }
+
+    fn set_uniforms(&self, uniforms: &[Uniform]) -> Result<Vec<GlBuffer>, StdError> {
+        profile_function!();
+
+        let mut gl_ubos = Vec::with_capacity(uniforms.len());
+
+        for u in uniforms {
+            let buffer = GlBuffer::new(u.u_type().to_opengl())?;
+
+            buffer.bind()?;
+            buffer.bind_base(u.binding())?;
+            buffer.set_data_full(
+                u.data_size(),
+                u.get_raw_data(),
+                gl::STATIC_DRAW
+            )?;
+            buffer.unbind()?;
+
+            gl_ubos.push(buffer);
+        }
+
+        Ok(gl_ubos)
+    }
+
+    fn sampler(&mut self, specs: &SamplerSpecs) -> Result<&GlSampler, StdError> {
+        let index = match self.samplers.iter().position(|(s, _)| s == specs) {
+            Some(index) => index,
+            None => {
+                self.samplers.push((*specs, GlSampler::new(specs)?));
+                self.samplers.len() - 1
+            },
+        };
+
+        Ok(&self.samplers[index].1)
+    }
+
+    /// The sampler of the material wins over the one of the texture, Raw textures keep their own parameters.
+    fn bind_texture(&mut self, location: usize, binding: &TextureBinding, material: &Material) -> Result<(), StdError> {
+        let sampler = match binding {
+            TextureBinding::Texture(texture) => Some(material.sampler(location).unwrap_or(texture.specs().sampler)),
+            TextureBinding::RenderTarget(target) => Some(material.sampler(location).unwrap_or(self.target(*target)?.specs.color_texture_specs.sampler)),
+            TextureBinding::Raw(_) => material.sampler(location),
+        };
+        match sampler {
+            Some(specs) => self.sampler(&specs)?.bind(location as u32)?,
+            None => GlSampler::unbind(location as u32)?,
+        }
+
+        let tex = match binding {
+            TextureBinding::Texture(texture) => {
+                let gl_texture = texture.gl_texture
+                    .as_ref()
+                    .ok_or("Couldn't find GlTexture in Texture!")?;
+
+                gl_texture.activate(location as u32)?;
+                gl_texture.bind()?;
+
+                return Ok(());
+            },
+            TextureBinding::RenderTarget(target) => self.target(*target)?.color_texture,
+            TextureBinding::Raw(tex) => *tex,
+        };
+
+        gl_check_and_print!(gl::ActiveTexture(gl::TEXTURE0 + location as u32));
+        gl_check_and_print!(gl::BindTexture(gl::TEXTURE_2D, tex));
+        gl_check_and_print!(gl::Uniform1i(location as i32, location as i32));
+
+        Ok(())
+    }
+
+    fn set_render_state(&self, state: &RenderState) {
+        match state.blend.to_opengl() {
+            Some((src, dst)) => {
+                gl_check_and_print!(gl::Enable(gl::BLEND));
+                gl_check_and_print!(gl::BlendFunc(src, dst));
+            },
+            None => gl_check_and_print!(gl::Disable(gl::BLEND)),
+        }
+
+        if state.depth_test.unwrap_or(self.depth_test) {
+            gl_check_and_print!(gl::Enable(gl::DEPTH_TEST));
+        } else {
+            gl_check_and_print!(gl::Disable(gl::DEPTH_TEST));
+        }
+        gl_check_and_print!(gl::DepthMask(state.depth_write as gl::types::GLboolean));
+
+        match state.cull.to_opengl() {
+            Some(face) => {
+                gl_check_and_print!(gl::Enable(gl::CULL_FACE));
+                gl_check_and_print!(gl::CullFace(face));
+            },
+            None => gl_check_and_print!(gl::Disable(gl::CULL_FACE)),
+        }
+
+        gl_check_and_print!(gl::PolygonMode(gl::FRONT_AND_BACK, state.polygon.to_opengl()));
+    }
+}
+impl RenderBackend for OpenGlBackend {
+    fn init_assets(&mut self, asset_manager: &mut AssetManager) -> Result<(), StdError> {
+        asset_manager.init_gl_program()?;
+        asset_manager.init_gl_vao()?;
+        asset_manager.init_gl_texture()?;
+
+        Ok(())
+    }
+
+    fn create_render_target(&mut self, specs: &RenderTargetSpecs) -> Result<RenderTargetId, StdError> {
+        let target = self.next_target;
+        self.next_target += 1;
+
+        self.targets.insert(target, RenderTarget::new(specs.clone()));
+
+        Ok(target)
+    }
+
+    fn destroy_render_target(&mut self, target: RenderTargetId) {
+        let _ = self.targets.remove(&target);
+    }
+
+    fn bind_render_target(&mut self, target: Option<RenderTargetId>, specs: &RenderTargetSpecs) -> Result<(), StdError> {
+        profile_function!();
+
+        let fb_target = match target {
+            Some(target) => self.target(target)?.framebuffer,
+            None => self.specs.gl_surface.framebuffer(),
+        };
+
+        let viewport = specs.viewport;
+        if specs.framebuffer_format == FramebufferFormat::SRGB {
+            gl_check_and_print!(gl::Enable(gl::FRAMEBUFFER_SRGB));
+        }
+        else {
+            gl_check_and_print!(gl::Disable(gl::FRAMEBUFFER_SRGB));
+        }
+
+        gl_check_and_print!(gl::BindFramebuffer(gl::FRAMEBUFFER, fb_target));
+        gl_check_and_print!(gl::Viewport(viewport.0, viewport.1, viewport.2, viewport.3));
+        gl_check_and_print!(gl::ClearColor(specs.clear_color.x, specs.clear_color.y, specs.clear_color.z, specs.clear_color.w));
+
+        if specs.clear {
+            self.depth_test = specs.depth_test;
+            // A material may have disabled it, the depth buffer wouldn't be cleared.
+            gl_check_and_print!(gl::DepthMask(gl::TRUE));
+
+            if specs.depth_test {
+                gl_check_and_print!(gl::Enable(gl::DEPTH_TEST));
+                gl_check_and_print!(gl::DepthFunc(gl::LESS));
+
+                gl_check_and_print!(gl::ClearDepth(specs.clear_depth));
+                gl_check_and_print!(gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT));
+            } else {
+                gl_check_and_print!(gl::Disable(gl::DEPTH_TEST));
+                gl_check_and_print!(gl::Clear(gl::COLOR_BUFFER_BIT));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, call: &DrawCall) -> Result<(), StdError> {
+        profile_function!();
+
+        // Program
+        let program = call.material.gl_program.as_ref().ok_or("Couldn't find GlProgram in Material!")?;
+        program.use_prog()?;
+
+        // VAO
+        let vao = call.mesh.gl_vao.as_ref().ok_or("Couldn't find GlVertexArray in Mesh!")?;
+        vao.bind()?;
+        vao.vertex_buffer().bind()?;
+        vao.index_buffer().bind()?;
+
+        let ubos = self.set_uniforms(call.uniforms)?;
+
+        for ubo in &ubos {
+            ubo.bind()?;
+        }
+
+        for (location, binding) in call.textures.iter().enumerate() {
+            self.bind_texture(location, binding, call.material)?;
+
+            if let Some(name) = call.material.slot_name(location) {
+                program.set_sampler(name, location as u32)?;
+            }
+        }
+
+        for (name, value) in call.material.parameters() {
+            program.set_uniform(name, value)?;
+        }
+        self.set_render_state(call.material.render_state());
+
+        let indices_len = call.mesh.indices().len() as i32;
+        let topology = call.mesh.gl_topology();
+
+        match &call.instances {
+            Some(instances) => {
+                let instance_vbo = GlBuffer::new(gl::ARRAY_BUFFER)?;
+                instance_vbo.bind()?;
+                instance_vbo.set_data(instances.data, gl::STATIC_DRAW)?;
+
+                for info in &instances.info.gl_info {
+                    let location = info.0 + instances.first_location + 1;
+                    vao.set_attribute(location, info.1, instances.info.stride, info.2)?;
+
+                    gl_check_and_print!(gl::VertexAttribDivisor(location, 1));
+                }
+
+                {
+                    profile_scope!("DrawElementsInstanced");
+                    gl_check_and_print!(gl::DrawElementsInstanced(
+                        topology,
+                        indices_len,
+                        gl::UNSIGNED_INT,
+                        std::ptr::null(),
+                        instances.count as i32
+                    ));
+                }
+
+                instance_vbo.unbind()?;
+            },
+            None => {
+                profile_scope!("DrawElements");
+                gl_check_and_print!(gl::DrawElements(topology, indices_len, gl::UNSIGNED_INT, std::ptr::null()));
+            },
+        }
+
+        vao.unbind_buffers()?;
+        vao.unbind()?;
+        program.unuse()?;
+
+        Ok(())
+    }
+
+    fn read_pixels(&mut self, target: Option<RenderTargetId>) -> Result<image::RgbaImage, StdError> {
+        profile_function!();
+
+        let (framebuffer, read_buffer, size) = match target {
+            Some(target) => {
+                let target = self.target(target)?;
+
+                (
+                    target.framebuffer,
+                    gl::COLOR_ATTACHMENT0,
+                    (target.specs.viewport.2 as u32, target.specs.viewport.3 as u32)
+                )
+            },
+            None => (
+                self.specs.gl_surface.framebuffer(),
+                self.specs.gl_surface.read_buffer(),
+                self.backbuffer_size()
+            ),
+        };
+
+        let mut pixels = vec![0u8; (size.0 * size.1 * 4) as usize];
+
+        gl_check!(gl::BindFramebuffer(gl::READ_FRAMEBUFFER, framebuffer), "Failed to bind the read framebuffer!")?;
+        gl_check!(gl::ReadBuffer(read_buffer), "Failed to set the read buffer!")?;
+        gl_check!(gl::PixelStorei(gl::PACK_ALIGNMENT, 1), "Failed to set the pack alignment!")?;
+        gl_check!(
+            gl::ReadPixels(
+                0,
+                0,
+                size.0 as i32,
+                size.1 as i32,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                pixels.as_mut_ptr() as *mut _
+            ),
+            "Failed to read pixels!"
+        )?;
+        gl_check!(gl::BindFramebuffer(gl::READ_FRAMEBUFFER, 0), "Failed to unbind the read framebuffer!")?;
+
+        let mut image = image::RgbaImage::from_raw(size.0, size.1, pixels)
+            .ok_or("Failed to create image from pixels! (OpenGlBackend)")?;
+        image::imageops::flip_vertical_in_place(&mut image);
+
+        Ok(image)
+    }
+
+    fn present(&mut self) -> Result<(), StdError> {
+        profile_function!();
+        self.specs.gl_surface.swap_buffers(&self.specs.gl_context)?;
+
+        Ok(())
+    }
+
+    fn resize(&mut self, new_size: (u32, u32)) -> Result<(), StdError> {
+        profile_function!();
+        let specs = &mut self.specs;
+        specs.gl_surface.resize(
+            &specs.gl_display,
+            &specs.gl_context,
+            std::num::NonZeroU32::new(new_size.0).ok_or("Invalid width! (OpenGlBackend)")?,
+            std::num::NonZeroU32::new(new_size.1).ok_or("Invalid height! (OpenGlBackend)")?,
+        )
+    }
+
+    fn set_vsync(&mut self, vsync: bool) -> Result<(), StdError> {
+        profile_function!();
+
+        let interval = if vsync {
+            glutin::surface::SwapInterval::Wait(std::num::NonZeroU32::new(1).unwrap())
+        } else {
+            glutin::surface::SwapInterval::DontWait
+        };
+
+        self.specs.gl_surface.set_swap_interval(&self.specs.gl_context, interval)?;
+
+        Ok(())
+    }
+
+    fn backbuffer_size(&self) -> (u32, u32) {
+        (
+            self.specs.gl_surface.width().unwrap_or(0),
+            self.specs.gl_surface.height().unwrap_or(0),
+        )
+    }
+
+    fn texture_id(&self, target: RenderTargetId) -> Option<u32> {
+        self.targets.get(&target).map(|t| t.color_texture)
+    }
+}
+impl Drop for OpenGlBackend {
+    fn drop(&mut self) {
+        if cfg!(debug_assertions) {
+            loop {
+                let err = unsafe { gl::GetError() };
+                if err == gl::NO_ERROR {
+                    break;
+                }
+
+                println!("OpenGL error {:08x}", err)
+            }
+        }
+    }
+}
+
+extern "system" fn debug_callback(
+    source: gl::types::GLenum,
+    gltype: gl::types::GLenum,
+    id: gl::types::GLuint,
+    severity: gl::types::GLenum,
+    _length: gl::types::GLsizei,
+    message: *const gl::types::GLchar,
+    _user_param: *mut std::ffi::c_void,
+) {
+    let source_str = match source {
+        gl::DEBUG_SOURCE_API => "API",
+        gl::DEBUG_SOURCE_WINDOW_SYSTEM => "Window System",
+        gl::DEBUG_SOURCE_SHADER_COMPILER => "Shader Compiler",
+        gl::DEBUG_SOURCE_THIRD_PARTY => "Third Party",
+        gl::DEBUG_SOURCE_APPLICATION => "Application",
+        _ => "Unknown",
+    };
+
+    let severity_str = match severity {
+        gl::DEBUG_SEVERITY_HIGH => "High",
+        gl::DEBUG_SEVERITY_MEDIUM => "Medium",
+        gl::DEBUG_SEVERITY_LOW => "Low",
+        gl::DEBUG_SEVERITY_NOTIFICATION => "Notification",
+        _ => "Unknown",
+    };
+
+    let gltype_str = match gltype {
+        gl::DEBUG_TYPE_ERROR => "Error",
+        gl::DEBUG_TYPE_DEPRECATED_BEHAVIOR => "Deprecated Behavior",
+        gl::DEBUG_TYPE_UNDEFINED_BEHAVIOR => "Undefined Behavior",
+        gl::DEBUG_TYPE_PORTABILITY => "Portability",
+        gl::DEBUG_TYPE_PERFORMANCE => "Performance",
+        gl::DEBUG_TYPE_OTHER => "Other",
+        gl::DEBUG_TYPE_MARKER => "Marker",
+        gl::DEBUG_TYPE_PUSH_GROUP => "Push Group",
+        gl::DEBUG_TYPE_POP_GROUP => "Pop Group",
+        _ => "Unknown",
+    };
+
+    if severity != gl::DEBUG_SEVERITY_NOTIFICATION {
+        let message_str = unsafe { 
+            std::str::from_utf8(std::ffi::CStr::from_ptr(message).to_bytes()).unwrap() 
+        };
+        error!("{}", message_str);
+        error!(
+            "OpenGL Debug Message:\n  Source: {}\n  Type: {}\n  ID: {}\n  Severity: {}\n  Message: {}",
+            source_str, gltype_str, id, severity_str, message_str
+        );
+    }
+}
\ No newline at end of file
@@ -0,0 +1,67 @@
+pub(crate) mod gl_backend;
+pub mod recording_backend;
+pub mod software;
+
+use crate::{lg_core::asset_manager::AssetManager, StdError};
+use recording_backend::RecordingBackend;
+use super::{material::Material, mesh::Mesh, render_target::RenderTargetSpecs, texture::Texture, uniform::Uniform, vertex::VertexInfo};
+
+/// Render targets are owned by the backend, the renderer only keeps their ids.
+pub type RenderTargetId = u32;
+
+#[derive(Debug, Clone, Copy)]
+pub enum TextureBinding<'a> {
+    Texture(&'a Texture),
+    /// Color attachment of a render target.
+    RenderTarget(RenderTargetId),
+    /// Texture name of the backend itself (e.g. an OpenGL texture).
+    Raw(u32),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct InstanceData<'a> {
+    pub count: u32,
+    pub info: &'a VertexInfo,
+    pub data: &'a [u8],
+    /// Last vertex attribute location of the mesh, instance attributes come after it.
+    pub first_location: u32,
+}
+
+/// Everything needed to submit one draw, textures are bound in order (slot 0, 1, ...).
+#[derive(Debug)]
+pub struct DrawCall<'a> {
+    pub mesh: &'a Mesh,
+    pub material: &'a Material,
+    pub uniforms: &'a [Uniform],
+    pub textures: Vec<TextureBinding<'a>>,
+    pub instances: Option<InstanceData<'a>>,
+}
+
+/// What RendererCore submits every frame, only called from the render thread.
+pub trait RenderBackend {
+    /// Creates the GPU resources of the assets loaded since the last call.
+    fn init_assets(&mut self, asset_manager: &mut AssetManager) -> Result<(), StdError>;
+
+    fn create_render_target(&mut self, specs: &RenderTargetSpecs) -> Result<RenderTargetId, StdError>;
+    fn destroy_render_target(&mut self, target: RenderTargetId);
+
+    /// None binds the backbuffer. Clears it if specs.clear is set.
+    fn bind_render_target(&mut self, target: Option<RenderTargetId>, specs: &RenderTargetSpecs) -> Result<(), StdError>;
+    fn draw(&mut self, call: &DrawCall) -> Result<(), StdError>;
+
+    /// None reads the backbuffer. The first row is the top of the image.
+    fn read_pixels(&mut self, target: Option<RenderTargetId>) -> Result<image::RgbaImage, StdError>;
+    fn present(&mut self) -> Result<(), StdError>;
+
+    fn resize(&mut self, new_size: (u32, u32)) -> Result<(), StdError>;
+    fn set_vsync(&mut self, vsync: bool) -> Result<(), StdError>;
+    fn backbuffer_size(&self) -> (u32, u32);
+
+    /// Backend texture name of the color attachment, can be used as an ImGui TextureId.
+    fn texture_id(&self, target: RenderTargetId) -> Option<u32>;
+
+    /// Some only for the recording backend.
+    fn recorder(&mut self) -> Option<&mut RecordingBackend> {
+        None
+    }
+}
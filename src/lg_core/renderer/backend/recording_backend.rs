@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+use crate::{lg_core::{asset_manager::AssetManager, renderer::{mesh::Topology, render_target::RenderTargetSpecs}, uuid::UUID}, StdError};
+use super::{DrawCall, RenderBackend, RenderTargetId, TextureBinding};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecordedTexture {
+    Texture(UUID),
+    RenderTarget(RenderTargetId),
+    Raw(u32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedDraw {
+    pub mesh: UUID,
+    pub material: UUID,
+    pub topology: Topology,
+    pub index_count: usize,
+    /// 1 when not instanced.
+    pub instance_count: u32,
+    /// Binding of each uniform block.
+    pub uniforms: Vec<usize>,
+    pub textures: Vec<RecordedTexture>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecordedCommand {
+    InitAssets {
+        textures: Vec<UUID>,
+        meshes: Vec<UUID>,
+        materials: Vec<UUID>,
+    },
+    CreateRenderTarget(RenderTargetId, RenderTargetSpecs),
+    DestroyRenderTarget(RenderTargetId),
+    /// None is the backbuffer.
+    BindRenderTarget(Option<RenderTargetId>, RenderTargetSpecs),
+    Draw(RecordedDraw),
+    ReadPixels(Option<RenderTargetId>),
+    Present,
+    Resize(u32, u32),
+    SetVsync(bool),
+}
+
+/// Null backend, nothing is rendered, every command is logged instead.
+///
+/// Commands are validated the same way a real backend would fail (unknown render targets, etc.).
+/// Only the current and the last presented frame are kept.
+#[derive(Debug, Default)]
+pub struct RecordingBackend {
+    commands: Vec<RecordedCommand>,
+    last_frame: Vec<RecordedCommand>,
+    targets: HashMap<RenderTargetId, RenderTargetSpecs>,
+    next_target: RenderTargetId,
+    size: (u32, u32),
+}
+// Public
+impl RecordingBackend {
+    pub fn new(size: (u32, u32)) -> Self {
+        Self {
+            next_target: 1,
+            size,
+            ..Default::default()
+        }
+    }
+
+    /// Recorded since the last present().
+    pub fn commands(&self) -> &[RecordedCommand] {
+        &self.commands
+    }
+
+    /// Everything the last presented frame submitted, ending with RecordedCommand::Present.
+    pub fn last_frame(&self) -> &[RecordedCommand] {
+        &self.last_frame
+    }
+
+    /// Returns everything recorded so far and starts a new list.
+    pub fn take_commands(&mut self) -> Vec<RecordedCommand> {
+        std::mem::take(&mut self.commands)
+    }
+
+    /// Draws of the last presented frame.
+    pub fn draws(&self) -> impl Iterator<Item = &RecordedDraw> {
+        self.last_frame
+            .iter()
+            .filter_map(|c| match c {
+                RecordedCommand::Draw(draw) => Some(draw),
+                _ => None,
+            })
+    }
+
+    pub fn target_specs(&self, target: RenderTargetId) -> Option<&RenderTargetSpecs> {
+        self.targets.get(&target)
+    }
+}
+// Private
+impl RecordingBackend {
+    fn check_target(&self, target: RenderTargetId) -> Result<(), StdError> {
+        if !self.targets.contains_key(&target) {
+            return Err(std::format!("Render target {} doesn't exist! (RecordingBackend)", target).into());
+        }
+
+        Ok(())
+    }
+}
+impl RenderBackend for RecordingBackend {
+    fn init_assets(&mut self, asset_manager: &mut AssetManager) -> Result<(), StdError> {
+        let (textures, meshes, materials) = asset_manager.take_to_init_gl();
+
+        if !textures.is_empty() || !meshes.is_empty() || !materials.is_empty() {
+            self.commands.push(RecordedCommand::InitAssets { textures, meshes, materials });
+        }
+
+        Ok(())
+    }
+
+    fn create_render_target(&mut self, specs: &RenderTargetSpecs) -> Result<RenderTargetId, StdError> {
+        let target = self.next_target;
+        self.next_target += 1;
+
+        self.targets.insert(target, specs.clone());
+        self.commands.push(RecordedCommand::CreateRenderTarget(target, specs.clone()));
+
+        Ok(target)
+    }
+
+    fn destroy_render_target(&mut self, target: RenderTargetId) {
+        if self.targets.remove(&target).is_some() {
+            self.commands.push(RecordedCommand::DestroyRenderTarget(target));
+        }
+    }
+
+    fn bind_render_target(&mut self, target: Option<RenderTargetId>, specs: &RenderTargetSpecs) -> Result<(), StdError> {
+        if let Some(target) = target {
+            self.check_target(target)?;
+        }
+
+        self.commands.push(RecordedCommand::BindRenderTarget(target, specs.clone()));
+
+        Ok(())
+    }
+
+    fn draw(&mut self, call: &DrawCall) -> Result<(), StdError> {
+        let mut textures = Vec::with_capacity(call.textures.len());
+        for binding in &call.textures {
+            textures.push(match binding {
+                TextureBinding::Texture(texture) => RecordedTexture::Texture(*texture.uuid()),
+                TextureBinding::RenderTarget(target) => {
+                    self.check_target(*target)?;
+                    RecordedTexture::RenderTarget(*target)
+                },
+                TextureBinding::Raw(id) => RecordedTexture::Raw(*id),
+            });
+        }
+
+        self.commands.push(RecordedCommand::Draw(RecordedDraw {
+            mesh: *call.mesh.uuid(),
+            material: *call.material.uuid(),
+            topology: call.mesh.topology(),
+            index_count: call.mesh.indices().len(),
+            instance_count: call.instances.map_or(1, |i| i.count),
+            uniforms: call.uniforms.iter().map(|u| u.binding()).collect(),
+            textures,
+        }));
+
+        Ok(())
+    }
+
+    /// Always a transparent image with the size of the target.
+    fn read_pixels(&mut self, target: Option<RenderTargetId>) -> Result<image::RgbaImage, StdError> {
+        let size = match target {
+            Some(target) => {
+                self.check_target(target)?;
+                let viewport = self.targets[&target].viewport;
+
+                (viewport.2 as u32, viewport.3 as u32)
+            },
+            None => self.size,
+        };
+
+        self.commands.push(RecordedCommand::ReadPixels(target));
+
+        Ok(image::RgbaImage::new(size.0, size.1))
+    }
+
+    fn present(&mut self) -> Result<(), StdError> {
+        self.commands.push(RecordedCommand::Present);
+        self.last_frame = std::mem::take(&mut self.commands);
+
+        Ok(())
+    }
+
+    fn resize(&mut self, new_size: (u32, u32)) -> Result<(), StdError> {
+        self.size = new_size;
+        self.commands.push(RecordedCommand::Resize(new_size.0, new_size.1));
+
+        Ok(())
+    }
+
+    fn set_vsync(&mut self, vsync: bool) -> Result<(), StdError> {
+        self.commands.push(RecordedCommand::SetVsync(vsync));
+
+        Ok(())
+    }
+
+    fn backbuffer_size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn texture_id(&self, target: RenderTargetId) -> Option<u32> {
+        self.targets.contains_key(&target).then_some(target)
+    }
+
+    fn recorder(&mut self) -> Option<&mut RecordingBackend> {
+        Some(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lg_core::{asset_manager::{AssetManager, FALLBACK_MATERIAL}, renderer::{command::SendDrawData, primitives::Primitive, render_graph::{RenderGraph, RenderPassDesc}, CreationWindowInfo, Renderer}};
+    use super::*;
+
+    #[test]
+    fn records_the_submitted_frame() {
+        // Empty, nothing is written next to the real assets.
+        let root = std::env::temp_dir().join(std::format!("l3gion_recording_{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+
+        let window_info = CreationWindowInfo {
+            event_loop: None,
+            title: String::new(),
+            width: 64,
+            height: 32,
+            vsync: false,
+        };
+        let (renderer, _window) = Renderer::new_headless(window_info, AssetManager::new(&root)).unwrap();
+
+        let specs = RenderTargetSpecs {
+            clear: true,
+            viewport: (0, 0, 64, 32),
+            ..Default::default()
+        };
+        let mut graph = RenderGraph::new();
+        graph.set_pass(RenderPassDesc {
+            name: "main".to_string(),
+            reads: Vec::new(),
+            specs: specs.clone(),
+        });
+        renderer.set_render_graph(graph).unwrap();
+
+        let mesh = Primitive::CUBE.uuid().unwrap();
+        renderer.begin();
+        renderer.begin_render_pass("main".to_string());
+        renderer.draw(SendDrawData {
+            mesh,
+            material: FALLBACK_MATERIAL,
+            uniforms: Vec::new(),
+            textures: Vec::new(),
+        });
+        renderer.flush_render_graph();
+        renderer.end();
+
+        let mut core = renderer.core();
+        let recorder = core.backend_mut().recorder().unwrap();
+        let frame = recorder.last_frame()
+            .iter()
+            .filter(|c| matches!(c, RecordedCommand::BindRenderTarget(..) | RecordedCommand::Draw(_) | RecordedCommand::Present))
+            .collect::<Vec<_>>();
+
+        match frame.as_slice() {
+            [RecordedCommand::BindRenderTarget(Some(target), bound), RecordedCommand::Draw(draw), RecordedCommand::Present] => {
+                assert!(recorder.target_specs(*target).is_some());
+                assert_eq!(bound, &specs);
+                assert_eq!(draw.mesh, mesh);
+                assert_eq!(draw.material, FALLBACK_MATERIAL);
+                assert_eq!(draw.instance_count, 1);
+                assert!(draw.index_count > 0);
+            },
+            frame => panic!("Unexpected frame {:?}", frame),
+        }
+
+        drop(core);
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
@@ -0,0 +1,345 @@
+mod rasterizer;
+pub mod shaders;
+
+use std::{collections::HashMap, sync::Arc};
+use rasterizer::{draw_line, draw_point, draw_triangle, ClipVertex, Framebuffer, RasterState};
+use shaders::{BpBrdf, ErrorShader, PostProcessing, Sampler, SoftwareShader, UniformBlocks, VertexInput};
+use sllog::warn;
+use crate::{glm, lg_core::{asset_manager::AssetManager, renderer::{mesh::Topology, render_state::{BlendMode, CullMode, PolygonMode}, render_target::{FramebufferFormat, RenderTargetSpecs}}, uuid::UUID}, profile_function, StdError};
+use super::{DrawCall, InstanceData, RenderBackend, RenderTargetId, TextureBinding};
+
+/// Name of the fallback fragment shader, see AssetManager::get_material_or_fallback.
+const ERROR_SHADER: &str = "error_f";
+
+#[derive(Debug, Default)]
+struct SoftwareTarget {
+    specs: RenderTargetSpecs,
+    framebuffer: Framebuffer,
+}
+impl SoftwareTarget {
+    fn new(specs: RenderTargetSpecs) -> Self {
+        let framebuffer = Framebuffer::new(specs.viewport.2.max(0) as u32, specs.viewport.3.max(0) as u32);
+
+        Self { specs, framebuffer }
+    }
+
+    fn has_alpha(&self) -> bool {
+        self.specs.color_texture_specs.tex_format.channels() == 4
+    }
+}
+
+/// Pure Rust rasterizer, renders on the CPU without any GPU or OpenGL context.
+///
+/// Materials are drawn with the SoftwareShader registered for the name of their fragment shader,
+/// BP_BRDF_f, post_processing_f, final_pass_f and error_f are registered by default, materials without one use error_f.
+/// ImGui is not rendered.
+pub struct SoftwareBackend {
+    backbuffer: SoftwareTarget,
+    targets: HashMap<RenderTargetId, SoftwareTarget>,
+    next_target: RenderTargetId,
+
+    /// None is the backbuffer.
+    bound: Option<RenderTargetId>,
+    state: RasterState,
+    backbuffer_srgb: bool,
+
+    shaders: HashMap<String, Arc<dyn SoftwareShader>>,
+    /// Material -> fragment shader name.
+    materials: HashMap<UUID, String>,
+}
+// Public
+impl SoftwareBackend {
+    pub fn new(size: (u32, u32)) -> Self {
+        let mut result = Self {
+            backbuffer: SoftwareTarget::new(Self::backbuffer_specs(size)),
+            targets: HashMap::default(),
+            next_target: 1,
+
+            bound: None,
+            state: RasterState {
+                viewport: (0, 0, size.0 as i32, size.1 as i32),
+                depth_test: false,
+                depth_write: true,
+                blend: BlendMode::ALPHA,
+                cull: CullMode::NONE,
+                polygon: PolygonMode::FILL,
+                clamp: true,
+            },
+            backbuffer_srgb: false,
+
+            shaders: HashMap::default(),
+            materials: HashMap::default(),
+        };
+
+        result.register_shader("BP_BRDF_f", Arc::new(BpBrdf));
+        result.register_shader("post_processing_f", Arc::new(PostProcessing));
+        result.register_shader("final_pass_f", Arc::new(PostProcessing));
+        result.register_shader(ERROR_SHADER, Arc::new(ErrorShader));
+
+        result
+    }
+
+    /// Replaces the shader used for materials whose fragment shader is named fragment_shader.
+    pub fn register_shader(&mut self, fragment_shader: &str, shader: Arc<dyn SoftwareShader>) {
+        self.shaders.insert(fragment_shader.to_string(), shader);
+    }
+}
+// Private
+impl SoftwareBackend {
+    fn backbuffer_specs(size: (u32, u32)) -> RenderTargetSpecs {
+        RenderTargetSpecs {
+            viewport: (0, 0, size.0 as i32, size.1 as i32),
+            ..Default::default()
+        }
+    }
+
+    fn target(&self, target: Option<RenderTargetId>) -> Result<&SoftwareTarget, StdError> {
+        match target {
+            Some(target) => Ok(self.targets
+                .get(&target)
+                .ok_or(std::format!("Render target {} doesn't exist! (SoftwareBackend)", target))?),
+            None => Ok(&self.backbuffer),
+        }
+    }
+
+    fn shader(&self, material: &UUID) -> Result<Arc<dyn SoftwareShader>, StdError> {
+        let name = self.materials
+            .get(material)
+            .ok_or(std::format!("Material {} wasn't initialized! (SoftwareBackend)", material.get_value()))?;
+
+        Ok(Arc::clone(self.shaders
+            .get(name)
+            .ok_or(std::format!("There is no software shader for {}! (SoftwareBackend)", name))?))
+    }
+
+    /// Attributes of every instance, in location order.
+    fn decode_instances(instances: &InstanceData) -> Vec<Vec<glm::Vec4>> {
+        (0..instances.count as usize)
+            .map(|i| {
+                let base = i * instances.info.stride;
+
+                instances.info.gl_info
+                    .iter()
+                    .map(|&(_, components, offset)| {
+                        let mut value = glm::vec4(0.0, 0.0, 0.0, 1.0);
+                        for c in 0..components.min(4) as usize {
+                            let start = base + offset as usize + c * 4;
+                            if let Some(bytes) = instances.data.get(start..start + 4) {
+                                value[c] = f32::from_ne_bytes(bytes.try_into().unwrap());
+                            }
+                        }
+
+                        value
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn draw_to(&self, fb: &mut Framebuffer, call: &DrawCall, shader: &dyn SoftwareShader) -> Result<(), StdError> {
+        let mut samplers = Vec::with_capacity(call.textures.len());
+        for binding in &call.textures {
+            samplers.push(match binding {
+                TextureBinding::Texture(texture) => Sampler::from_texture(texture, call.material.sampler(samplers.len()).unwrap_or(texture.specs().sampler)),
+                TextureBinding::RenderTarget(target) => {
+                    let target = self.target(Some(*target))
+                        .map_err(|_| std::format!("Render target {} is bound and sampled at the same time! (SoftwareBackend)", target))?;
+
+                    let specs = call.material.sampler(samplers.len()).unwrap_or(target.specs.color_texture_specs.sampler);
+                    Sampler::from_framebuffer(&target.framebuffer, specs)
+                },
+                TextureBinding::Raw(_) => return Err("Raw texture names can't be sampled! (SoftwareBackend)".into()),
+            });
+        }
+
+        let uniforms = UniformBlocks::new(call.uniforms).with_parameters(call.material.parameters());
+
+        // The one of the render pass, with what the material sets.
+        let render_state = call.material.render_state();
+        let state = RasterState {
+            depth_test: render_state.depth_test.unwrap_or(self.state.depth_test),
+            depth_write: render_state.depth_write,
+            blend: render_state.blend,
+            cull: render_state.cull,
+            polygon: render_state.polygon,
+            ..self.state
+        };
+        let instances = match &call.instances {
+            Some(instances) => Self::decode_instances(instances),
+            None => vec![Vec::new()],
+        };
+
+        let vertices = call.mesh.vertices();
+        let indices = call.mesh.indices();
+        let mut clip = Vec::with_capacity(vertices.len());
+
+        for instance in &instances {
+            clip.clear();
+            for v in vertices {
+                let input = VertexInput {
+                    position: v.position,
+                    normal: v.normal,
+                    tex_coord: v.tex_coord,
+                    instance,
+                };
+
+                let mut varyings = vec![0.0; shader.varyings()];
+                let position = shader.vertex(&input, &uniforms, &mut varyings);
+
+                clip.push(ClipVertex { position, varyings });
+            }
+
+            let fragment = |varyings: &[f32]| shader.fragment(varyings, &uniforms, &samplers);
+
+            match call.mesh.topology() {
+                Topology::Triangles => for triangle in indices.chunks_exact(3) {
+                    let triangle = [
+                        clip.get(triangle[0] as usize),
+                        clip.get(triangle[1] as usize),
+                        clip.get(triangle[2] as usize),
+                    ];
+
+                    if let [Some(v0), Some(v1), Some(v2)] = triangle {
+                        draw_triangle(fb, &state, [v0, v1, v2], fragment);
+                    }
+                },
+                Topology::Lines => for line in indices.chunks_exact(2) {
+                    if let [Some(v0), Some(v1)] = [clip.get(line[0] as usize), clip.get(line[1] as usize)] {
+                        draw_line(fb, &state, [v0, v1], fragment);
+                    }
+                },
+                Topology::Points => for point in indices {
+                    if let Some(v) = clip.get(*point as usize) {
+                        draw_point(fb, &state, v, fragment);
+                    }
+                },
+            }
+        }
+
+        Ok(())
+    }
+}
+impl RenderBackend for SoftwareBackend {
+    fn init_assets(&mut self, asset_manager: &mut AssetManager) -> Result<(), StdError> {
+        // Textures and meshes are used as they are.
+        let (_, _, materials) = asset_manager.take_to_init_gl();
+
+        for material_uuid in materials {
+            let [_, fragment] = asset_manager.material_shaders_or_fallback(&material_uuid);
+
+            let name = match self.shaders.contains_key(fragment.name()) {
+                true => fragment.name(),
+                false => {
+                    warn!("There is no software shader for {}, materials using it are drawn with {}! (SoftwareBackend)", fragment.name(), ERROR_SHADER);
+                    ERROR_SHADER
+                },
+            };
+
+            self.materials.insert(material_uuid, name.to_string());
+        }
+
+        Ok(())
+    }
+
+    fn create_render_target(&mut self, specs: &RenderTargetSpecs) -> Result<RenderTargetId, StdError> {
+        let target = self.next_target;
+        self.next_target += 1;
+
+        self.targets.insert(target, SoftwareTarget::new(specs.clone()));
+
+        Ok(target)
+    }
+
+    fn destroy_render_target(&mut self, target: RenderTargetId) {
+        let _ = self.targets.remove(&target);
+    }
+
+    fn bind_render_target(&mut self, target: Option<RenderTargetId>, specs: &RenderTargetSpecs) -> Result<(), StdError> {
+        profile_function!();
+
+        let software_target = match target {
+            Some(id) => self.targets
+                .get_mut(&id)
+                .ok_or(std::format!("Render target {} doesn't exist! (SoftwareBackend)", id))?,
+            None => {
+                self.backbuffer_srgb = specs.framebuffer_format == FramebufferFormat::SRGB;
+                &mut self.backbuffer
+            },
+        };
+
+        self.bound = target;
+        self.state.viewport = specs.viewport;
+        self.state.clamp = target.is_none() || !specs.color_texture_specs.tex_format.is_float();
+
+        // Same as OpenGL, the depth test state only changes when clearing.
+        if specs.clear {
+            self.state.depth_test = specs.depth_test;
+
+            software_target.framebuffer.clear_color(specs.clear_color);
+            if specs.depth_test {
+                software_target.framebuffer.clear_depth(specs.clear_depth as f32);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, call: &DrawCall) -> Result<(), StdError> {
+        profile_function!();
+
+        let shader = self.shader(call.material.uuid())?;
+
+        // Taken out while drawing, so the other targets can be sampled.
+        let mut target = match self.bound {
+            Some(id) => self.targets
+                .remove(&id)
+                .ok_or(std::format!("Render target {} doesn't exist! (SoftwareBackend)", id))?,
+            None => std::mem::take(&mut self.backbuffer),
+        };
+
+        let result = self.draw_to(&mut target.framebuffer, call, shader.as_ref());
+
+        match self.bound {
+            Some(id) => { self.targets.insert(id, target); },
+            None => self.backbuffer = target,
+        }
+
+        result
+    }
+
+    fn read_pixels(&mut self, target: Option<RenderTargetId>) -> Result<image::RgbaImage, StdError> {
+        profile_function!();
+
+        let software_target = self.target(target)?;
+        let srgb = match target {
+            Some(_) => software_target.specs.color_texture_specs.tex_format.is_srgb(),
+            None => self.backbuffer_srgb,
+        };
+
+        Ok(software_target.framebuffer.to_image(srgb, !software_target.has_alpha()))
+    }
+
+    /// Nothing to present, the backbuffer is only read back.
+    fn present(&mut self) -> Result<(), StdError> {
+        Ok(())
+    }
+
+    fn resize(&mut self, new_size: (u32, u32)) -> Result<(), StdError> {
+        self.backbuffer = SoftwareTarget::new(Self::backbuffer_specs(new_size));
+
+        Ok(())
+    }
+
+    fn set_vsync(&mut self, _vsync: bool) -> Result<(), StdError> {
+        Ok(())
+    }
+
+    fn backbuffer_size(&self) -> (u32, u32) {
+        (self.backbuffer.framebuffer.width, self.backbuffer.framebuffer.height)
+    }
+
+    /// Software targets have no texture name.
+    fn texture_id(&self, _target: RenderTargetId) -> Option<u32> {
+        None
+    }
+}
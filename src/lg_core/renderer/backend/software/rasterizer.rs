@@ -0,0 +1,360 @@
+use crate::{glm, lg_core::renderer::render_state::{BlendMode, CullMode, PolygonMode}};
+
+/// Color and depth of a render target, row 0 is the bottom row (like OpenGL).
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Framebuffer {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) color: Vec<glm::Vec4>,
+    pub(crate) depth: Vec<f32>,
+}
+impl Framebuffer {
+    pub(crate) fn new(width: u32, height: u32) -> Self {
+        let len = (width * height) as usize;
+
+        Self {
+            width,
+            height,
+            color: vec![glm::Vec4::zeros(); len],
+            depth: vec![1.0; len],
+        }
+    }
+
+    pub(crate) fn clear_color(&mut self, color: glm::Vec4) {
+        self.color.fill(color);
+    }
+
+    pub(crate) fn clear_depth(&mut self, depth: f32) {
+        self.depth.fill(depth);
+    }
+
+    /// Top row first, like images are stored.
+    pub(crate) fn to_image(&self, srgb: bool, opaque: bool) -> image::RgbaImage {
+        let mut image = image::RgbaImage::new(self.width, self.height);
+
+        for (x, y, pixel) in image.enumerate_pixels_mut() {
+            let color = self.color[((self.height - 1 - y) * self.width + x) as usize];
+
+            let encode = |c: f32| {
+                let c = if srgb { linear_to_srgb(c) } else { c };
+                (c.clamp(0.0, 1.0) * 255.0).round() as u8
+            };
+
+            *pixel = image::Rgba([
+                encode(color.x),
+                encode(color.y),
+                encode(color.z),
+                if opaque { 255 } else { (color.w.clamp(0.0, 1.0) * 255.0).round() as u8 },
+            ]);
+        }
+
+        image
+    }
+}
+
+/// Output of the vertex stage.
+#[derive(Debug, Clone)]
+pub(crate) struct ClipVertex {
+    pub(crate) position: glm::Vec4,
+    pub(crate) varyings: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RasterState {
+    /// x, y, width, height.
+    pub(crate) viewport: (i32, i32, i32, i32),
+    /// Depth func LESS.
+    pub(crate) depth_test: bool,
+    /// Only when the depth test is enabled, like in OpenGL.
+    pub(crate) depth_write: bool,
+    pub(crate) blend: BlendMode,
+    /// Front faces are counter-clockwise.
+    pub(crate) cull: CullMode,
+    pub(crate) polygon: PolygonMode,
+    /// Colors are clamped to [0, 1], false for float targets.
+    pub(crate) clamp: bool,
+}
+
+struct ScreenVertex<'a> {
+    x: f32,
+    y: f32,
+    z: f32,
+    inv_w: f32,
+    varyings: &'a [f32],
+}
+
+pub(crate) fn draw_triangle<F>(
+    fb: &mut Framebuffer,
+    state: &RasterState,
+    triangle: [&ClipVertex; 3],
+    mut fragment: F
+)
+    where F: FnMut(&[f32]) -> glm::Vec4
+{
+    let polygon = clip_polygon(triangle.iter().map(|v| (*v).clone()).collect());
+    if polygon.len() < 3 { return; }
+
+    let screen = polygon.iter()
+        .map(|v| to_screen(v, state))
+        .collect::<Vec<_>>();
+
+    // Twice the signed area, positive if counter-clockwise.
+    let area = (0..screen.len())
+        .map(|i| {
+            let (a, b) = (&screen[i], &screen[(i + 1) % screen.len()]);
+            a.x * b.y - b.x * a.y
+        })
+        .sum::<f32>();
+    let culled = match state.cull {
+        CullMode::NONE => false,
+        CullMode::FRONT => area > 0.0,
+        CullMode::BACK => area < 0.0,
+        CullMode::FRONT_AND_BACK => true,
+    };
+    if culled { return; }
+
+    match state.polygon {
+        // Fan triangulation of the clipped polygon.
+        PolygonMode::FILL => for i in 1..screen.len() - 1 {
+            rasterize(fb, state, [&screen[0], &screen[i], &screen[i + 1]], &mut fragment);
+        },
+        PolygonMode::LINE => for i in 0..screen.len() {
+            rasterize_line(fb, state, &screen[i], &screen[(i + 1) % screen.len()], &mut fragment);
+        },
+        PolygonMode::POINT => for v in &screen {
+            shade(fb, state, v.x.floor() as i32, v.y.floor() as i32, v.z.clamp(0.0, 1.0), v.varyings, &mut fragment);
+        },
+    }
+}
+
+/// One pixel wide, like glLineWidth(1.0).
+pub(crate) fn draw_line<F>(
+    fb: &mut Framebuffer,
+    state: &RasterState,
+    line: [&ClipVertex; 2],
+    mut fragment: F
+)
+    where F: FnMut(&[f32]) -> glm::Vec4
+{
+    let [a, b] = match clip_segment(line[0].clone(), line[1].clone()) {
+        Some(line) => line,
+        None => return,
+    };
+    let (a, b) = (to_screen(&a, state), to_screen(&b, state));
+
+    rasterize_line(fb, state, &a, &b, &mut fragment);
+}
+
+/// One pixel, like glPointSize(1.0).
+pub(crate) fn draw_point<F>(
+    fb: &mut Framebuffer,
+    state: &RasterState,
+    point: &ClipVertex,
+    mut fragment: F
+)
+    where F: FnMut(&[f32]) -> glm::Vec4
+{
+    let outside = CLIP_PLANES.iter().any(|plane| plane(&point.position) < 0.0);
+    if outside || point.position.w <= f32::EPSILON { return; }
+
+    let v = to_screen(point, state);
+    shade(fb, state, v.x.floor() as i32, v.y.floor() as i32, v.z.clamp(0.0, 1.0), v.varyings, &mut fragment);
+}
+
+pub(crate) fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+}
+
+pub(crate) fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 { c * 12.92 } else { 1.055 * c.powf(1.0 / 2.4) - 0.055 }
+}
+
+/// Near and far.
+const CLIP_PLANES: [fn(&glm::Vec4) -> f32; 2] = [
+    |p| p.z + p.w,
+    |p| p.w - p.z,
+];
+
+/// Clips against the near and far planes, x and y are handled by the viewport bounds.
+fn clip_polygon(mut polygon: Vec<ClipVertex>) -> Vec<ClipVertex> {
+    for plane in CLIP_PLANES {
+        if polygon.is_empty() { break; }
+
+        let mut result = Vec::with_capacity(polygon.len() + 1);
+        for i in 0..polygon.len() {
+            let current = &polygon[i];
+            let next = &polygon[(i + 1) % polygon.len()];
+            let (d_current, d_next) = (plane(&current.position), plane(&next.position));
+
+            if d_current >= 0.0 {
+                result.push(current.clone());
+            }
+            if (d_current >= 0.0) != (d_next >= 0.0) {
+                let t = d_current / (d_current - d_next);
+                result.push(lerp_vertex(current, next, t));
+            }
+        }
+
+        polygon = result;
+    }
+
+    polygon.retain(|v| v.position.w > f32::EPSILON);
+    polygon
+}
+
+fn clip_segment(mut a: ClipVertex, mut b: ClipVertex) -> Option<[ClipVertex; 2]> {
+    for plane in CLIP_PLANES {
+        let (d_a, d_b) = (plane(&a.position), plane(&b.position));
+
+        if d_a < 0.0 && d_b < 0.0 { return None; }
+        if d_a < 0.0 {
+            a = lerp_vertex(&a, &b, d_a / (d_a - d_b));
+        }
+        else if d_b < 0.0 {
+            b = lerp_vertex(&a, &b, d_a / (d_a - d_b));
+        }
+    }
+
+    (a.position.w > f32::EPSILON && b.position.w > f32::EPSILON).then_some([a, b])
+}
+
+fn lerp_vertex(a: &ClipVertex, b: &ClipVertex, t: f32) -> ClipVertex {
+    ClipVertex {
+        position: a.position + (b.position - a.position) * t,
+        varyings: a.varyings.iter()
+            .zip(&b.varyings)
+            .map(|(a, b)| a + (b - a) * t)
+            .collect(),
+    }
+}
+
+fn to_screen<'a>(v: &'a ClipVertex, state: &RasterState) -> ScreenVertex<'a> {
+    let inv_w = 1.0 / v.position.w;
+    let ndc = v.position.xyz() * inv_w;
+    let (vx, vy, vw, vh) = state.viewport;
+
+    ScreenVertex {
+        x: vx as f32 + (ndc.x + 1.0) * 0.5 * vw as f32,
+        y: vy as f32 + (ndc.y + 1.0) * 0.5 * vh as f32,
+        z: (ndc.z + 1.0) * 0.5,
+        inv_w,
+        varyings: &v.varyings,
+    }
+}
+
+fn edge(a: &ScreenVertex, b: &ScreenVertex, x: f32, y: f32) -> f32 {
+    (b.x - a.x) * (y - a.y) - (b.y - a.y) * (x - a.x)
+}
+
+/// Edges shared by two triangles only belong to one of them, so blended pixels aren't drawn twice.
+fn is_top_left(a: &ScreenVertex, b: &ScreenVertex) -> bool {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+
+    dy < 0.0 || (dy == 0.0 && dx < 0.0)
+}
+
+/// Shared by draw_line and the wireframe of draw_triangle.
+fn rasterize_line<F>(fb: &mut Framebuffer, state: &RasterState, a: &ScreenVertex, b: &ScreenVertex, fragment: &mut F)
+    where F: FnMut(&[f32]) -> glm::Vec4
+{
+    let steps = (b.x - a.x).abs().max((b.y - a.y).abs()).ceil().max(1.0) as u32;
+    let mut varyings = vec![0.0; a.varyings.len()];
+
+    for step in 0..=steps {
+        let t = step as f32 / steps as f32;
+
+        // Perspective correct interpolation.
+        let persp = [(1.0 - t) * a.inv_w, t * b.inv_w];
+        let inv_sum = 1.0 / (persp[0] + persp[1]);
+        for (i, out) in varyings.iter_mut().enumerate() {
+            *out = (persp[0] * a.varyings[i] + persp[1] * b.varyings[i]) * inv_sum;
+        }
+
+        let (x, y) = (a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t);
+        let z = (a.z + (b.z - a.z) * t).clamp(0.0, 1.0);
+        shade(fb, state, x.floor() as i32, y.floor() as i32, z, &varyings, fragment);
+    }
+}
+
+fn rasterize<F>(fb: &mut Framebuffer, state: &RasterState, triangle: [&ScreenVertex; 3], fragment: &mut F)
+    where F: FnMut(&[f32]) -> glm::Vec4
+{
+    let [v0, mut v1, mut v2] = triangle;
+
+    let mut area = edge(v0, v1, v2.x, v2.y);
+    if area == 0.0 { return; }
+    // Culled by draw_triangle, clockwise triangles are flipped.
+    if area < 0.0 {
+        std::mem::swap(&mut v1, &mut v2);
+        area = -area;
+    }
+
+    let (vx, vy, vw, vh) = state.viewport;
+    let min_x = (v0.x.min(v1.x).min(v2.x).floor() as i32).max(vx).max(0);
+    let min_y = (v0.y.min(v1.y).min(v2.y).floor() as i32).max(vy).max(0);
+    let max_x = (v0.x.max(v1.x).max(v2.x).ceil() as i32).min(vx + vw).min(fb.width as i32);
+    let max_y = (v0.y.max(v1.y).max(v2.y).ceil() as i32).min(vy + vh).min(fb.height as i32);
+
+    let edges = [(v1, v2), (v2, v0), (v0, v1)];
+    let top_left = edges.map(|(a, b)| is_top_left(a, b));
+    let mut varyings = vec![0.0; v0.varyings.len()];
+
+    for y in min_y..max_y {
+        for x in min_x..max_x {
+            let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+
+            let w = edges.map(|(a, b)| edge(a, b, px, py));
+            let inside = w.iter()
+                .zip(top_left)
+                .all(|(w, top_left)| *w > 0.0 || (*w == 0.0 && top_left));
+            if !inside { continue; }
+
+            let b = w.map(|w| w / area);
+
+            // Depth is affine in screen space.
+            let z = (b[0] * v0.z + b[1] * v1.z + b[2] * v2.z).clamp(0.0, 1.0);
+
+            // Perspective correct interpolation.
+            let persp = [b[0] * v0.inv_w, b[1] * v1.inv_w, b[2] * v2.inv_w];
+            let inv_sum = 1.0 / (persp[0] + persp[1] + persp[2]);
+            for (i, out) in varyings.iter_mut().enumerate() {
+                *out = (persp[0] * v0.varyings[i] + persp[1] * v1.varyings[i] + persp[2] * v2.varyings[i]) * inv_sum;
+            }
+
+            shade(fb, state, x, y, z, &varyings, fragment);
+        }
+    }
+}
+
+/// Depth test, fragment and blending of one pixel, nothing if it is outside of the viewport.
+fn shade<F>(fb: &mut Framebuffer, state: &RasterState, x: i32, y: i32, z: f32, varyings: &[f32], fragment: &mut F)
+    where F: FnMut(&[f32]) -> glm::Vec4
+{
+    let (vx, vy, vw, vh) = state.viewport;
+    if x < vx.max(0) || y < vy.max(0) || x >= (vx + vw).min(fb.width as i32) || y >= (vy + vh).min(fb.height as i32) {
+        return;
+    }
+
+    let index = (y as u32 * fb.width + x as u32) as usize;
+    if state.depth_test && z >= fb.depth[index] {
+        return;
+    }
+
+    let src = fragment(varyings);
+    let dst = fb.color[index];
+
+    let color = match state.blend {
+        BlendMode::OPAQUE => src,
+        BlendMode::ALPHA => src * src.w + dst * (1.0 - src.w),
+        BlendMode::PREMULTIPLIED => src + dst * (1.0 - src.w),
+        BlendMode::ADDITIVE => src * src.w + dst,
+        BlendMode::MULTIPLY => src.component_mul(&dst),
+    };
+
+    fb.color[index] = if state.clamp {
+        color.map(|c| c.clamp(0.0, 1.0))
+    } else { color };
+    if state.depth_test && state.depth_write {
+        fb.depth[index] = z;
+    }
+}
@@ -0,0 +1,314 @@
+use std::collections::BTreeMap;
+use crate::{glm, lg_core::renderer::{material::UniformValue, sampler::{SamplerSpecs, WrapMode}, texture::{Texture, TextureFilter, TextureFormat, TextureType}, uniform::Uniform}};
+use super::rasterizer::{srgb_to_linear, Framebuffer};
+
+pub struct VertexInput<'a> {
+    pub position: glm::Vec3,
+    pub normal: glm::Vec3,
+    pub tex_coord: glm::Vec2,
+    /// Per instance attributes in location order, empty when not instanced.
+    /// Missing components default to (0, 0, 0, 1) like in OpenGL.
+    pub instance: &'a [glm::Vec4],
+}
+
+static NO_PARAMETERS: BTreeMap<String, UniformValue> = BTreeMap::new();
+
+/// Uniform blocks by binding, read with the std140 layout, and the parameters of the material.
+pub struct UniformBlocks<'a> {
+    uniforms: &'a [Uniform],
+    parameters: &'a BTreeMap<String, UniformValue>,
+}
+impl<'a> UniformBlocks<'a> {
+    pub fn new(uniforms: &'a [Uniform]) -> Self {
+        Self { uniforms, parameters: &NO_PARAMETERS }
+    }
+
+    pub fn with_parameters(mut self, parameters: &'a BTreeMap<String, UniformValue>) -> Self {
+        self.parameters = parameters;
+        self
+    }
+
+    /// Default value of a plain uniform, see Material::parameters.
+    pub fn parameter(&self, name: &str) -> Option<&'a UniformValue> {
+        self.parameters.get(name)
+    }
+
+    pub fn block(&self, binding: usize) -> Option<&'a [u8]> {
+        self.uniforms
+            .iter()
+            .find(|u| u.binding() == binding)
+            .map(|u| u.buffer.bytes())
+    }
+
+    pub fn float(&self, binding: usize, offset: usize) -> Option<f32> {
+        let bytes = self.block(binding)?.get(offset..offset + 4)?;
+
+        Some(f32::from_ne_bytes(bytes.try_into().ok()?))
+    }
+
+    pub fn vec3(&self, binding: usize, offset: usize) -> Option<glm::Vec3> {
+        Some(glm::vec3(
+            self.float(binding, offset)?,
+            self.float(binding, offset + 4)?,
+            self.float(binding, offset + 8)?,
+        ))
+    }
+
+    /// Column major.
+    pub fn mat4(&self, binding: usize, offset: usize) -> Option<glm::Mat4> {
+        let mut values = [0.0; 16];
+        for (i, v) in values.iter_mut().enumerate() {
+            *v = self.float(binding, offset + i * 4)?;
+        }
+
+        Some(glm::make_mat4(&values))
+    }
+}
+
+enum SamplerSource<'a> {
+    Texture(TextureFormat, &'a [u8]),
+    Framebuffer(&'a [glm::Vec4]),
+}
+
+/// A texture bound to a slot. Only the first layer of cube maps and arrays is sampled.
+///
+/// There are no mip maps, mag_filter is used for minification too and the anisotropy and LOD settings are ignored.
+pub struct Sampler<'a> {
+    width: u32,
+    height: u32,
+    specs: SamplerSpecs,
+    srgb: bool,
+    source: SamplerSource<'a>,
+}
+impl<'a> Sampler<'a> {
+    pub fn from_texture(texture: &'a Texture, specs: SamplerSpecs) -> Self {
+        Self {
+            width: texture.width(),
+            height: texture.height(),
+            specs,
+            srgb: texture.specs().tex_format.is_srgb(),
+            source: SamplerSource::Texture(texture.specs().tex_format, texture.layer(0)),
+        }
+    }
+
+    pub(crate) fn from_framebuffer(fb: &'a Framebuffer, specs: SamplerSpecs) -> Self {
+        Self {
+            width: fb.width,
+            height: fb.height,
+            specs,
+            srgb: false,
+            source: SamplerSource::Framebuffer(&fb.color),
+        }
+    }
+
+    pub fn sample(&self, uv: glm::Vec2) -> glm::Vec4 {
+        if self.width == 0 || self.height == 0 {
+            return glm::vec4(0.0, 0.0, 0.0, 1.0);
+        }
+
+        let x = uv.x * self.width as f32;
+        let y = uv.y * self.height as f32;
+
+        match self.specs.mag_filter {
+            TextureFilter::NEAREST => self.texel(x.floor() as i64, y.floor() as i64),
+            TextureFilter::LINEAR => {
+                let (x, y) = (x - 0.5, y - 0.5);
+                let (x0, y0) = (x.floor(), y.floor());
+                let (fx, fy) = (x - x0, y - y0);
+                let (x0, y0) = (x0 as i64, y0 as i64);
+
+                let top = glm::lerp(&self.texel(x0, y0), &self.texel(x0 + 1, y0), fx);
+                let bottom = glm::lerp(&self.texel(x0, y0 + 1), &self.texel(x0 + 1, y0 + 1), fx);
+
+                glm::lerp(&top, &bottom, fy)
+            },
+        }
+    }
+}
+impl Sampler<'_> {
+    fn texel(&self, x: i64, y: i64) -> glm::Vec4 {
+        let (Some(x), Some(y)) = (wrap(x, self.width, self.specs.wrap[0]), wrap(y, self.height, self.specs.wrap[1])) else {
+            return glm::Vec4::from(self.specs.border_color);
+        };
+        let index = y * self.width as usize + x;
+
+        let texel = match &self.source {
+            SamplerSource::Framebuffer(color) => return color[index],
+            SamplerSource::Texture(format, bytes) => {
+                let channels = format.channels();
+                let size = format.texture_type().size();
+                let bpp = channels * size;
+                let Some(p) = bytes.get(index * bpp..(index + 1) * bpp) else {
+                    return glm::vec4(0.0, 0.0, 0.0, 1.0);
+                };
+
+                let c = |i: usize| {
+                    let b = &p[i * size..(i + 1) * size];
+                    match format.texture_type() {
+                        TextureType::UNSIGNED_BYTE => b[0] as f32 / 255.0,
+                        TextureType::UNSIGNED_SHORT => u16::from_ne_bytes([b[0], b[1]]) as f32 / 65535.0,
+                        TextureType::HALF_FLOAT => half::f16::from_ne_bytes([b[0], b[1]]).to_f32(),
+                        TextureType::FLOAT => f32::from_ne_bytes([b[0], b[1], b[2], b[3]]),
+                    }
+                };
+
+                // Missing components like in OpenGL.
+                match channels {
+                    1 => glm::vec4(c(0), 0.0, 0.0, 1.0),
+                    2 => glm::vec4(c(0), c(1), 0.0, 1.0),
+                    3 => glm::vec4(c(0), c(1), c(2), 1.0),
+                    _ => glm::vec4(c(0), c(1), c(2), c(3)),
+                }
+            },
+        };
+
+        if self.srgb {
+            glm::vec4(srgb_to_linear(texel.x), srgb_to_linear(texel.y), srgb_to_linear(texel.z), texel.w)
+        } else { texel }
+    }
+}
+
+/// None is outside of the texture, with CLAMP_TO_BORDER.
+fn wrap(coord: i64, size: u32, mode: WrapMode) -> Option<usize> {
+    let size = size as i64;
+
+    let coord = match mode {
+        WrapMode::REPEAT => coord.rem_euclid(size),
+        WrapMode::MIRRORED_REPEAT => {
+            let coord = coord.rem_euclid(2 * size);
+            if coord < size { coord } else { 2 * size - 1 - coord }
+        },
+        WrapMode::CLAMP_TO_EDGE => coord.clamp(0, size - 1),
+        WrapMode::CLAMP_TO_BORDER => if (0..size).contains(&coord) { coord } else { return None; },
+    };
+
+    Some(coord as usize)
+}
+
+/// CPU version of a GLSL program, vertex and fragment stages.
+pub trait SoftwareShader: Send + Sync {
+    /// How many floats vertex() writes to varyings.
+    fn varyings(&self) -> usize;
+
+    /// Returns the clip space position.
+    fn vertex(&self, input: &VertexInput, uniforms: &UniformBlocks, varyings: &mut [f32]) -> glm::Vec4;
+
+    /// varyings are already interpolated (perspective correct).
+    fn fragment(&self, varyings: &[f32], uniforms: &UniformBlocks, samplers: &[Sampler]) -> glm::Vec4;
+}
+
+/// Mirrors BP_BRDF_v.vert and BP_BRDF_f.frag (Blinn-Phong).
+///
+/// Bindings: 0 Camera { view, proj, dir }, 1 LightProperties { position, color }.
+/// Instance attributes: row_0, row_1, row_2 of the model matrix, identity when not instanced.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BpBrdf;
+impl SoftwareShader for BpBrdf {
+    fn varyings(&self) -> usize {
+        // normal, world position, tex_coord
+        8
+    }
+
+    fn vertex(&self, input: &VertexInput, uniforms: &UniformBlocks, varyings: &mut [f32]) -> glm::Vec4 {
+        let model = match input.instance {
+            [row_0, row_1, row_2, ..] => glm::mat4(
+                row_0.x, row_0.y, row_0.z, row_0.w,
+                row_1.x, row_1.y, row_1.z, row_1.w,
+                row_2.x, row_2.y, row_2.z, row_2.w,
+                0.0, 0.0, 0.0, 1.0,
+            ),
+            _ => glm::Mat4::identity(),
+        };
+        let view = uniforms.mat4(0, 0).unwrap_or_default();
+        let proj = uniforms.mat4(0, 64).unwrap_or_default();
+
+        let world_position = model * glm::vec4(input.position.x, input.position.y, input.position.z, 1.0);
+        let normal = (glm::mat4_to_mat3(&glm::transpose(&glm::inverse(&model))) * input.normal).normalize();
+
+        varyings[0..3].copy_from_slice(normal.as_slice());
+        varyings[3..6].copy_from_slice(world_position.xyz().as_slice());
+        varyings[6..8].copy_from_slice(input.tex_coord.as_slice());
+
+        proj * view * world_position
+    }
+
+    fn fragment(&self, varyings: &[f32], uniforms: &UniformBlocks, _samplers: &[Sampler]) -> glm::Vec4 {
+        const MATERIAL_COLOR: glm::Vec3 = glm::Vec3::new(0.2, 0.2, 1.0);
+        const SHININESS: f32 = 42.0;
+
+        let camera_direction = uniforms.vec3(0, 128).unwrap_or_default();
+        let light_position = uniforms.vec3(1, 0).unwrap_or_default();
+        let light_color = uniforms.vec3(1, 16).unwrap_or_default();
+
+        let normal = glm::make_vec3(&varyings[0..3]).normalize();
+        let position = glm::make_vec3(&varyings[3..6]);
+        let light_dir = (light_position - position).normalize();
+        let camera_dir = camera_direction.normalize();
+
+        // Lambertian Diffuse
+        let incident_angle = light_dir.dot(&normal).max(0.0);
+        let diffuse = (light_color * incident_angle).component_mul(&MATERIAL_COLOR);
+
+        // Specular Highlight
+        let half_vector = (camera_dir + light_dir).normalize();
+        let specular_intensity = normal.dot(&half_vector).max(0.0).powf(SHININESS);
+
+        let final_color = diffuse.add_scalar(specular_intensity);
+
+        glm::vec4(final_color.x, final_color.y, final_color.z, 1.0)
+    }
+}
+
+/// Mirrors the error shaders of the fallback material: flat magenta.
+///
+/// Bindings: 0 { view, proj }. Instance attributes: row_0, row_1, row_2 of the model matrix, identity when not instanced.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ErrorShader;
+impl SoftwareShader for ErrorShader {
+    fn varyings(&self) -> usize {
+        0
+    }
+
+    fn vertex(&self, input: &VertexInput, uniforms: &UniformBlocks, _varyings: &mut [f32]) -> glm::Vec4 {
+        let model = match input.instance {
+            [row_0, row_1, row_2, ..] => glm::mat4(
+                row_0.x, row_0.y, row_0.z, row_0.w,
+                row_1.x, row_1.y, row_1.z, row_1.w,
+                row_2.x, row_2.y, row_2.z, row_2.w,
+                0.0, 0.0, 0.0, 1.0,
+            ),
+            _ => glm::Mat4::identity(),
+        };
+        let view = uniforms.mat4(0, 0).unwrap_or_default();
+        let proj = uniforms.mat4(0, 64).unwrap_or_default();
+
+        proj * view * model * glm::vec4(input.position.x, input.position.y, input.position.z, 1.0)
+    }
+
+    fn fragment(&self, _varyings: &[f32], _uniforms: &UniformBlocks, _samplers: &[Sampler]) -> glm::Vec4 {
+        glm::vec4(1.0, 0.0, 1.0, 1.0)
+    }
+}
+
+/// Mirrors final_pass_v.vert with post_processing_f.frag: full screen quad sampling slot 0.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PostProcessing;
+impl SoftwareShader for PostProcessing {
+    fn varyings(&self) -> usize {
+        // tex_coord
+        2
+    }
+
+    fn vertex(&self, input: &VertexInput, _uniforms: &UniformBlocks, varyings: &mut [f32]) -> glm::Vec4 {
+        varyings.copy_from_slice(input.tex_coord.as_slice());
+
+        glm::vec4(input.position.x, input.position.y, input.position.z, 1.0)
+    }
+
+    fn fragment(&self, varyings: &[f32], _uniforms: &UniformBlocks, samplers: &[Sampler]) -> glm::Vec4 {
+        match samplers.first() {
+            Some(sampler) => sampler.sample(glm::make_vec2(varyings)),
+            None => glm::vec4(0.0, 0.0, 0.0, 1.0),
+        }
+    }
+}
@@ -39,6 +39,10 @@ impl Buffer {
         self.data = Vec::from(bytes);
     }
 
+    pub fn bytes(&self) -> &[u8] {
+        &self.data
+    }
+
     pub fn data_size(&self) -> usize {
         self.data.len() * std::mem::size_of::<u8>()
     }
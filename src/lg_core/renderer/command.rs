@@ -22,7 +22,14 @@ pub enum TextureOption {
     UUID(UUID),
     LG_TEXTURE(Texture),
     GL_TEXTURE(gl::types::GLuint),
+    /// Texture asset imported as a cube map (e.g. a skybox).
+    CUBE_MAP(UUID),
+    /// Texture asset imported as a 2D array, indexed by layer in the shader.
+    TEXTURE_ARRAY(UUID),
+    /// The pass executed right before the current one.
     PREVIOUS_PASS,
+    /// Any pass executed earlier, it has to be declared as a read of the current pass.
+    PASS(String),
 }
 
 #[derive(Debug, PartialEq)]
@@ -31,4 +38,5 @@ pub(super) enum RendererCommand {
     _RESIZE_DONE,
     _SHUTDOWN_DONE,
     _END_DONE,
+    _CAPTURE_DONE(Result<image::RgbaImage, String>),
 }
\ No newline at end of file
@@ -5,12 +5,18 @@ use sllog::error;
 
 use crate::StdError;
 
-pub struct ImGuiCore {
+/// Everything ImGui needs to talk to the window and to OpenGL, absent when running headless.
+struct ImGuiBackend {
     _gl_glow_context: glow::Context,
     _imgui_texture_map: imgui_glow_renderer::SimpleTextureMap,
-    imgui_context: imgui::Context,
-    imgui_winit: imgui_winit_support::WinitPlatform,
+    /// None when rendering offscreen.
+    imgui_winit: Option<imgui_winit_support::WinitPlatform>,
     imgui_renderer: imgui_glow_renderer::Renderer,
+}
+
+pub struct ImGuiCore {
+    imgui_context: imgui::Context,
+    backend: Option<ImGuiBackend>,
     fonts: HashMap<String, imgui::FontId>,
 }
 // Public
@@ -39,7 +45,9 @@ impl ImGuiCore {
 // Public(crate)
 impl ImGuiCore {
     pub(crate) fn handle_event<T>(&mut self, window: &winit::window::Window, event: &winit::event::Event<T>) {
-        self.imgui_winit.handle_event(self.imgui_context.io_mut(), window, event);
+        if let Some(imgui_winit) = self.backend.as_mut().and_then(|b| b.imgui_winit.as_mut()) {
+            imgui_winit.handle_event(self.imgui_context.io_mut(), window, event);
+        }
     }
     
     pub(crate) fn new_frame(&mut self) -> *mut imgui::Ui {
@@ -47,7 +55,9 @@ impl ImGuiCore {
     }
     
     pub(crate) fn prepare_to_render(&mut self, ui: &mut imgui::Ui, window: &winit::window::Window) {
-        self.imgui_winit.prepare_render(ui, window);
+        if let Some(imgui_winit) = self.backend.as_mut().and_then(|b| b.imgui_winit.as_mut()) {
+            imgui_winit.prepare_render(ui, window);
+        }
     }
 }
 
@@ -57,16 +67,31 @@ impl ImGuiCore {
         _gl_glow_context: glow::Context,
         _imgui_texture_map: imgui_glow_renderer::SimpleTextureMap,
         imgui_context: imgui::Context,
-        imgui_winit: imgui_winit_support::WinitPlatform,
+        imgui_winit: Option<imgui_winit_support::WinitPlatform>,
         imgui_renderer: imgui_glow_renderer::Renderer,
     ) -> Self 
     {
         Self {
-            _gl_glow_context,
-            _imgui_texture_map,
             imgui_context,
-            imgui_winit,
-            imgui_renderer,
+            backend: Some(ImGuiBackend {
+                _gl_glow_context,
+                _imgui_texture_map,
+                imgui_winit,
+                imgui_renderer,
+            }),
+            fonts: HashMap::default(),
+        }
+    }
+
+    /// No window and no OpenGL, frames are built and thrown away.
+    pub(super) fn new_headless(mut imgui_context: imgui::Context, display_size: (u32, u32)) -> Self {
+        imgui_context.set_ini_filename(None);
+        imgui_context.io_mut().display_size = [display_size.0 as f32, display_size.1 as f32];
+        imgui_context.fonts().build_rgba32_texture();
+
+        Self {
+            imgui_context,
+            backend: None,
             fonts: HashMap::default(),
         }
     }
@@ -87,22 +112,35 @@ impl ImGuiCore {
     }
 
     pub(super) fn prepare_frame(&mut self, window: &winit::window::Window) {
-        self.imgui_winit
-            .prepare_frame(self.imgui_context.io_mut(), window)
-            .unwrap();
+        if let Some(imgui_winit) = self.backend.as_mut().and_then(|b| b.imgui_winit.as_mut()) {
+            imgui_winit
+                .prepare_frame(self.imgui_context.io_mut(), window)
+                .unwrap();
+        }
+    }
+
+    /// Only needed when there is no window to take the size from.
+    pub(super) fn set_display_size(&mut self, size: (u32, u32)) {
+        if self.backend.as_ref().is_some_and(|b| b.imgui_winit.is_some()) {
+            return;
+        }
+
+        self.imgui_context.io_mut().display_size = [size.0 as f32, size.1 as f32];
     }
     
     pub(super) fn render_imgui(&mut self) {
         let dd = self.imgui_context.render();
 
-        if dd.total_vtx_count > 0 {
-            self.imgui_renderer
-                .render(
-                    &self._gl_glow_context,
-                    &self._imgui_texture_map,
-                    dd,
-                )
-                .unwrap(); 
+        if let Some(backend) = &mut self.backend {
+            if dd.total_vtx_count > 0 {
+                backend.imgui_renderer
+                    .render(
+                        &backend._gl_glow_context,
+                        &backend._imgui_texture_map,
+                        dd,
+                    )
+                    .unwrap(); 
+            }
         }
     }
     
@@ -110,6 +148,12 @@ impl ImGuiCore {
         let fonts = self.imgui_context.fonts();
         let atlas_texture = fonts.build_rgba32_texture();
 
+        let backend = match &mut self.backend {
+            Some(backend) => backend,
+            // Building the atlas is enough when there is nothing to upload it to.
+            None => return Ok(()),
+        };
+
         let mut gl_texture = 0;
         unsafe {
             gl::GenTextures(1, &mut gl_texture);
@@ -132,7 +176,7 @@ impl ImGuiCore {
 
         let tex = glow::NativeTexture(std::num::NonZeroU32::new(gl_texture).unwrap());
 
-        fonts.tex_id = self._imgui_texture_map
+        fonts.tex_id = backend._imgui_texture_map
             .register(tex)
             .ok_or_else(|| format!("Failed to register texture"))?;
 
@@ -146,14 +190,7 @@ impl ImGuiCore {
 }
 
 pub(super) fn imgui_init(window: &winit::window::Window) -> (imgui::Context, imgui_winit_support::WinitPlatform) {
-    let mut imgui_context = imgui::Context::create();
-
-    // .ini file
-    imgui_context.set_ini_filename(None);
-    
-    if let Ok(ini_contents) = std::fs::read_to_string("imgui.ini") {
-        imgui_context.load_ini_settings(&ini_contents);
-    }
+    let mut imgui_context = imgui_context_init();
 
     let mut imgui_winit = imgui_winit_support::WinitPlatform::init(&mut imgui_context);
     imgui_winit.attach_window(
@@ -164,4 +201,25 @@ pub(super) fn imgui_init(window: &winit::window::Window) -> (imgui::Context, img
     imgui_context.io_mut().font_global_scale = (1.0 / imgui_winit.hidpi_factor()) as f32;
     
     (imgui_context, imgui_winit)
+}
+
+/// For offscreen rendering, there is no window to attach to.
+pub(super) fn imgui_init_windowless(size: (u32, u32)) -> imgui::Context {
+    let mut imgui_context = imgui_context_init();
+    imgui_context.io_mut().display_size = [size.0 as f32, size.1 as f32];
+
+    imgui_context
+}
+
+fn imgui_context_init() -> imgui::Context {
+    let mut imgui_context = imgui::Context::create();
+
+    // .ini file
+    imgui_context.set_ini_filename(None);
+    
+    if let Ok(ini_contents) = std::fs::read_to_string("imgui.ini") {
+        imgui_context.load_ini_settings(&ini_contents);
+    }
+
+    imgui_context
 }
\ No newline at end of file
@@ -1,8 +1,20 @@
-use std::hash::Hash;
+use std::{collections::BTreeMap, hash::Hash};
+use serde::{Deserialize, Serialize};
 
 use crate::{lg_core::uuid::UUID, StdError};
 
-use super::{opengl::{gl_program::GlProgram, gl_shader::GlShader}, shader::Shader, uniform::Uniform};
+use super::{opengl::{gl_program::GlProgram, gl_shader::GlShader}, render_state::RenderState, sampler::SamplerSpecs, shader::Shader, uniform::Uniform};
+
+/// Value of a plain (not block) uniform, written as a number or a list of 2, 3, 4 or 16 (column major) numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum UniformValue {
+    FLOAT(f32),
+    VEC2([f32; 2]),
+    VEC3([f32; 3]),
+    VEC4([f32; 4]),
+    MAT4([f32; 16]),
+}
 
 #[derive(Debug)]
 pub struct Material {
@@ -10,6 +22,13 @@ pub struct Material {
     name: String,
     shaders: Vec<UUID>,
     textures: Vec<UUID>,
+    /// By texture slot, None uses the sampler of the bound texture.
+    samplers: Vec<Option<SamplerSpecs>>,
+    /// By texture slot, the sampler uniform bound to it.
+    slot_names: Vec<String>,
+    /// Set by name before each draw.
+    parameters: BTreeMap<String, UniformValue>,
+    render_state: RenderState,
     pub uniforms: Vec<Uniform>,
     
     pub(crate) gl_program: Option<GlProgram>,
@@ -21,12 +40,41 @@ impl Material {
             name: String::from(name),
             shaders,
             textures,
+            samplers: Vec::new(),
+            slot_names: Vec::new(),
+            parameters: BTreeMap::new(),
+            render_state: RenderState::default(),
             uniforms,
             
             gl_program: None
         }
     }
 
+    pub fn with_samplers(mut self, samplers: Vec<Option<SamplerSpecs>>) -> Self {
+        self.samplers = samplers;
+
+        self
+    }
+
+    /// Sampler uniforms by texture slot, e.g. "albedo" for slot 0. Empty names are not bound by name.
+    pub fn with_slot_names(mut self, slot_names: Vec<String>) -> Self {
+        self.slot_names = slot_names;
+
+        self
+    }
+
+    pub fn with_parameters(mut self, parameters: BTreeMap<String, UniformValue>) -> Self {
+        self.parameters = parameters;
+
+        self
+    }
+
+    pub fn with_render_state(mut self, render_state: RenderState) -> Self {
+        self.render_state = render_state;
+
+        self
+    }
+
     pub fn uuid(&self) -> &UUID {
         &self.uuid
     }
@@ -35,6 +83,35 @@ impl Material {
         &self.textures
     }
 
+    /// Sampler of the texture bound to slot, if the material overrides it.
+    pub fn sampler(&self, slot: usize) -> Option<SamplerSpecs> {
+        self.samplers.get(slot).copied().flatten()
+    }
+
+    pub fn samplers(&self) -> &[Option<SamplerSpecs>] {
+        &self.samplers
+    }
+
+    /// Name of the sampler uniform of slot, if it has one.
+    pub fn slot_name(&self, slot: usize) -> Option<&str> {
+        self.slot_names
+            .get(slot)
+            .map(String::as_str)
+            .filter(|name| !name.is_empty())
+    }
+
+    pub fn slot_names(&self) -> &[String] {
+        &self.slot_names
+    }
+
+    pub fn parameters(&self) -> &BTreeMap<String, UniformValue> {
+        &self.parameters
+    }
+
+    pub fn render_state(&self) -> &RenderState {
+        &self.render_state
+    }
+
     pub fn shaders(&self) -> &[UUID] {
         &self.shaders
     }
@@ -49,17 +126,38 @@ impl Material {
     pub(crate) fn init_opengl(&mut self, shaders: &[&Shader]) -> Result<(), StdError> {
         if self.gl_program.is_some() { return Ok(()); }
 
-        let gl_shaders = shaders.iter()
-            .map(|s| GlShader::new(s.src_code(), s.stage().to_gl_stage()).unwrap())
-            .collect::<Vec<_>>();
+        self.gl_program = Some(Self::create_program(shaders)?);
+
+        Ok(())
+    }
 
+    /// The current program is only replaced if the new one compiles and links.
+    pub(crate) fn rebuild_opengl(&mut self, shaders: &[&Shader]) -> Result<(), StdError> {
+        self.gl_program = Some(Self::create_program(shaders)?);
+
+        Ok(())
+    }
+}
+// Private
+impl Material {
+    fn create_program(shaders: &[&Shader]) -> Result<GlProgram, StdError> {
+        let mut gl_shaders = Vec::with_capacity(shaders.len());
+        for s in shaders {
+            match GlShader::new(s.src_code(), s.stage().to_gl_stage()) {
+                Ok(gl_shader) => gl_shaders.push(gl_shader),
+                Err(e) => {
+                    gl_shaders.iter().for_each(GlShader::delete);
+                    return Err(std::format!("{}: {e}", s.name()).into());
+                },
+            }
+        }
+
+        // Dropping the program deletes its shaders.
         let mut gl_program = GlProgram::new()?;
         gl_program.set_shaders(gl_shaders)?;
         gl_program.link()?;
 
-        self.gl_program = Some(gl_program);
-
-        Ok(())
+        Ok(gl_program)
     }
 }
 
@@ -1,8 +1,39 @@
-use std::hash::Hash;
-use crate::{lg_core::uuid::UUID, StdError};
+use std::{hash::Hash, ops::Range};
+use crate::{glm, lg_core::uuid::UUID, StdError};
 
-use super::{opengl::gl_vertex_array::GlVertexArray, vertex::{LgVertex, Vertex}};
+use super::{mesh_processing::{self, Aabb, BoundingSphere}, opengl::gl_vertex_array::GlVertexArray, vertex::{GlVertex, Vertex, VertexInfo}};
 
+/// How the indices are assembled.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Topology {
+    /// Every 3 indices.
+    #[default]
+    Triangles,
+    /// Every 2 indices.
+    Lines,
+    Points,
+}
+
+/// A change of the vertices or indices, see Renderer::update_mesh.
+#[derive(Debug, Clone)]
+pub enum MeshUpdate {
+    /// Everything, the sub-meshes are replaced by one covering every index.
+    Replace { vertices: Vec<Vertex>, indices: Vec<u32> },
+    /// Overwrites the vertices from first on, the count can't grow.
+    Vertices { first: usize, vertices: Vec<Vertex> },
+    /// Overwrites the indices from first on, the count can't grow.
+    Indices { first: usize, indices: Vec<u32> },
+}
+
+/// A range of the indices, e.g. one model of an OBJ file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubMesh {
+    pub name: String,
+    pub first_index: u32,
+    pub index_count: u32,
+    /// Imported with the mesh, e.g. from its .mtl file.
+    pub material: Option<UUID>,
+}
 
 #[derive(Debug)]
 pub struct Mesh {
@@ -10,6 +41,16 @@ pub struct Mesh {
     name: String, // TODO: Placeholder
     vertices: Vec<Vertex>,
     indices: Vec<u32>,
+    sub_meshes: Vec<SubMesh>,
+    /// Empty until generate_tangents is called.
+    tangents: Vec<glm::Vec4>,
+    aabb: Aabb,
+    bounding_sphere: BoundingSphere,
+    topology: Topology,
+    /// Uploaded with DYNAMIC_DRAW, set by the first update.
+    dynamic: bool,
+    /// Changed since the last upload.
+    pending: PendingUpload,
     
     pub(crate) gl_vao: Option<GlVertexArray>
 }
@@ -20,12 +61,38 @@ impl Mesh {
         vertices: Vec<Vertex>,
         indices: Vec<u32>,
     ) -> Self
+    {
+        let sub_meshes = vec![SubMesh {
+            name: String::from(name),
+            first_index: 0,
+            index_count: indices.len() as u32,
+            material: None,
+        }];
+
+        Self::with_sub_meshes(uuid, name, vertices, indices, sub_meshes)
+    }
+
+    /// The sub-meshes index into indices.
+    pub fn with_sub_meshes(
+        uuid: UUID,
+        name: &str,
+        vertices: Vec<Vertex>,
+        indices: Vec<u32>,
+        sub_meshes: Vec<SubMesh>,
+    ) -> Self
     {
         Self {
             uuid,
             name: String::from(name),
+            aabb: Aabb::from_vertices(&vertices),
+            bounding_sphere: BoundingSphere::from_vertices(&vertices),
             vertices,
             indices,
+            sub_meshes,
+            tangents: Vec::new(),
+            topology: Topology::default(),
+            dynamic: false,
+            pending: PendingUpload::default(),
             
             gl_vao: None,
         }
@@ -38,7 +105,89 @@ impl Mesh {
     pub fn indices(&self) -> &[u32] {
         &self.indices
     }
+
+    pub fn sub_meshes(&self) -> &[SubMesh] {
+        &self.sub_meshes
+    }
+
+    /// One per vertex, see mesh_processing::tangents.
+    pub fn tangents(&self) -> &[glm::Vec4] {
+        &self.tangents
+    }
+
+    pub fn aabb(&self) -> &Aabb {
+        &self.aabb
+    }
+
+    pub fn bounding_sphere(&self) -> &BoundingSphere {
+        &self.bounding_sphere
+    }
     
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    /// Uploaded with DYNAMIC_DRAW instead of STATIC_DRAW, for meshes that are updated often.
+    pub fn with_dynamic(mut self, dynamic: bool) -> Self {
+        self.dynamic = dynamic;
+        self
+    }
+
+    pub fn is_dynamic(&self) -> bool {
+        self.dynamic
+    }
+
+    /// Layout of Vertex, the same for every mesh.
+    pub fn vertex_info() -> VertexInfo {
+        VertexInfo {
+            stride: std::mem::size_of::<Vertex>(),
+            gl_info: unsafe { Vertex::gl_info() },
+        }
+    }
+
+    /// The GPU copy is updated by the next AssetManager::init_gl_vao, the mesh becomes dynamic.
+    pub fn update(&mut self, update: MeshUpdate) -> Result<(), StdError> {
+        match update {
+            MeshUpdate::Replace { vertices, indices } => {
+                check_indices(&indices, vertices.len(), &self.name)?;
+
+                self.sub_meshes = vec![SubMesh {
+                    name: self.name.clone(),
+                    first_index: 0,
+                    index_count: indices.len() as u32,
+                    material: self.sub_meshes.first().and_then(|s| s.material),
+                }];
+                self.vertices = vertices;
+                self.indices = indices;
+                self.pending.full = true;
+            },
+            MeshUpdate::Vertices { first, vertices } => {
+                let range = patch_range(first, vertices.len(), self.vertices.len(), &self.name)?;
+
+                self.vertices[range.clone()].copy_from_slice(&vertices);
+                self.pending.vertices = merge(self.pending.vertices.take(), range);
+            },
+            MeshUpdate::Indices { first, indices } => {
+                let range = patch_range(first, indices.len(), self.indices.len(), &self.name)?;
+                check_indices(&indices, self.vertices.len(), &self.name)?;
+
+                self.indices[range.clone()].copy_from_slice(&indices);
+                self.pending.indices = merge(self.pending.indices.take(), range);
+            },
+        }
+
+        self.dynamic = true;
+        // Tangents depend on the indices too.
+        self.vertices_changed();
+
+        Ok(())
+    }
+
     pub fn uuid(&self) -> &UUID {
         &self.uuid
     }
@@ -48,32 +197,167 @@ impl Mesh {
     }
 }
 
+// Public
+/// Processing, better done before the mesh is uploaded since every change uploads all of it again.
+impl Mesh {
+    pub fn smooth_normals(&mut self) {
+        mesh_processing::smooth_normals(&mut self.vertices, &self.indices);
+        self.vertices_changed();
+        self.pending.full = true;
+    }
+
+    /// Every face gets its own vertices, the sub-meshes keep their ranges.
+    pub fn flat_normals(&mut self) {
+        (self.vertices, self.indices) = mesh_processing::flat_normals(&self.vertices, &self.indices);
+        self.vertices_changed();
+        self.pending.full = true;
+    }
+
+    pub fn generate_tangents(&mut self) {
+        self.tangents = mesh_processing::tangents(&self.vertices, &self.indices);
+    }
+
+    /// Merges the vertices whose attributes are all within epsilon, the sub-meshes keep their ranges.
+    pub fn weld(&mut self, epsilon: f32) {
+        (self.vertices, self.indices) = mesh_processing::weld(&self.vertices, &self.indices, epsilon);
+        self.vertices_changed();
+        self.pending.full = true;
+    }
+
+    /// Reorders the triangles inside every SubMesh.
+    pub fn optimize_vertex_cache(&mut self) {
+        for sub_mesh in &self.sub_meshes {
+            let range = sub_mesh.first_index as usize..(sub_mesh.first_index + sub_mesh.index_count) as usize;
+            let optimized = mesh_processing::optimize_vertex_cache(&self.indices[range.clone()], self.vertices.len());
+
+            self.indices[range].copy_from_slice(&optimized);
+        }
+
+        self.pending.full = true;
+    }
+}
+
 // Public(crate)
 impl Mesh {
+    /// Uploads the pending changes if the GlVertexArray exists already.
     pub(crate) fn init_opengl(&mut self) -> Result<(), StdError> {
-        if self.gl_vao.is_some() { return Ok(()); }
+        if self.gl_vao.is_some() { return self.update_opengl(); }
 
         let vao = GlVertexArray::new()?;
         vao.bind()?;
 
         // Vertices
-        let vertex_info = self.vertices()[0].vertex_info();
+        let vertex_info = Self::vertex_info();
         vao.vertex_buffer().bind()?;
-        vao.vertex_buffer().set_data(self.vertices(), gl::STATIC_DRAW)?;
+        vao.vertex_buffer().set_data(self.vertices(), self.gl_usage())?;
         for info in &vertex_info.gl_info {
             vao.set_attribute(info.0, info.1, vertex_info.stride, info.2)?;
         }
         
         // Indices
         vao.index_buffer().bind()?;
-        vao.index_buffer().set_data(self.indices(), gl::STATIC_DRAW)?;
+        vao.index_buffer().set_data(self.indices(), self.gl_usage())?;
         vao.unbind_buffers()?;
         vao.unbind()?;
         
         self.gl_vao = Some(vao);
+        self.pending = PendingUpload::default();
+
+        Ok(())
+    }
+
+    pub(crate) fn gl_topology(&self) -> gl::types::GLenum {
+        match self.topology {
+            Topology::Triangles => gl::TRIANGLES,
+            Topology::Lines => gl::LINES,
+            Topology::Points => gl::POINTS,
+        }
+    }
+}
+
+// Private
+impl Mesh {
+    fn gl_usage(&self) -> gl::types::GLenum {
+        match self.dynamic {
+            true => gl::DYNAMIC_DRAW,
+            false => gl::STATIC_DRAW,
+        }
+    }
+
+    fn update_opengl(&mut self) -> Result<(), StdError> {
+        let pending = std::mem::take(&mut self.pending);
+        let vao = match &self.gl_vao {
+            Some(vao) => vao,
+            None => return Ok(()),
+        };
+
+        vao.bind()?;
+        vao.vertex_buffer().bind()?;
+        vao.index_buffer().bind()?;
+
+        match pending.full {
+            // The size may have changed.
+            true => {
+                vao.vertex_buffer().set_data(&self.vertices, self.gl_usage())?;
+                vao.index_buffer().set_data(&self.indices, self.gl_usage())?;
+            },
+            false => {
+                if let Some(range) = pending.vertices {
+                    vao.vertex_buffer().set_sub_data(range.start, &self.vertices[range])?;
+                }
+                if let Some(range) = pending.indices {
+                    vao.index_buffer().set_sub_data(range.start, &self.indices[range])?;
+                }
+            },
+        }
+
+        vao.unbind_buffers()?;
+        vao.unbind()?;
 
         Ok(())
     }
+
+    /// Bounds are recomputed, tangents too if they were generated.
+    fn vertices_changed(&mut self) {
+        let had_tangents = !self.tangents.is_empty();
+        self.tangents.clear();
+        if had_tangents {
+            self.generate_tangents();
+        }
+
+        self.aabb = Aabb::from_vertices(&self.vertices);
+        self.bounding_sphere = BoundingSphere::from_vertices(&self.vertices);
+    }
+}
+
+/// Ranges of the buffers to upload again, everything if full.
+#[derive(Debug, Default)]
+struct PendingUpload {
+    full: bool,
+    vertices: Option<Range<usize>>,
+    indices: Option<Range<usize>>,
+}
+
+fn check_indices(indices: &[u32], vertex_count: usize, mesh: &str) -> Result<(), StdError> {
+    match indices.iter().find(|i| **i as usize >= vertex_count) {
+        Some(i) => Err(std::format!("Index {} is out of bounds, {} has {} vertices! (Mesh)", i, mesh, vertex_count).into()),
+        None => Ok(()),
+    }
+}
+
+fn patch_range(first: usize, count: usize, len: usize, mesh: &str) -> Result<Range<usize>, StdError> {
+    match first.checked_add(count) {
+        Some(end) if end <= len => Ok(first..end),
+        _ => Err(std::format!("Patch {}..{} doesn't fit in {} ({})! (Mesh)", first, first.saturating_add(count), mesh, len).into()),
+    }
+}
+
+/// The smallest range covering both.
+fn merge(a: Option<Range<usize>>, b: Range<usize>) -> Option<Range<usize>> {
+    Some(match a {
+        Some(a) => a.start.min(b.start)..a.end.max(b.end),
+        None => b,
+    })
 }
 impl Hash for Mesh {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
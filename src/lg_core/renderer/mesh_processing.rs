@@ -0,0 +1,618 @@
+//! CPU only processing of vertices and indices, used by the importers and by Mesh.
+//!
+//! Indices are triangle lists.
+use std::collections::HashMap;
+use crate::glm;
+use super::vertex::Vertex;
+
+/// Vertices the post transform cache is assumed to hold.
+const VERTEX_CACHE_SIZE: usize = 32;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: glm::Vec3,
+    pub max: glm::Vec3,
+}
+impl Aabb {
+    /// Zero sized at the origin if there are no vertices.
+    pub fn from_vertices(vertices: &[Vertex]) -> Self {
+        let mut positions = vertices.iter().map(|v| v.position);
+
+        let first = match positions.next() {
+            Some(first) => first,
+            None => return Self::default(),
+        };
+
+        positions.fold(Self { min: first, max: first }, |aabb, p| Self {
+            min: glm::min2(&aabb.min, &p),
+            max: glm::max2(&aabb.max, &p),
+        })
+    }
+
+    pub fn center(&self) -> glm::Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    /// Half of the size.
+    pub fn extents(&self) -> glm::Vec3 {
+        (self.max - self.min) * 0.5
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct BoundingSphere {
+    pub center: glm::Vec3,
+    pub radius: f32,
+}
+impl BoundingSphere {
+    /// Centered on the Aabb, not the smallest sphere but never smaller than the vertices.
+    pub fn from_vertices(vertices: &[Vertex]) -> Self {
+        let center = Aabb::from_vertices(vertices).center();
+        let radius = vertices
+            .iter()
+            .map(|v| glm::distance(&{ v.position }, &center))
+            .fold(0.0, f32::max);
+
+        Self { center, radius }
+    }
+}
+
+/// Area weighted average of the faces around every vertex, vertices are not merged.
+pub fn smooth_normals(vertices: &mut [Vertex], indices: &[u32]) {
+    let mut normals = vec![glm::Vec3::zeros(); vertices.len()];
+
+    for face in indices.chunks_exact(3) {
+        let normal = face_normal(vertices, face);
+
+        for i in face {
+            normals[*i as usize] += normal;
+        }
+    }
+
+    for (vertex, normal) in vertices.iter_mut().zip(normals) {
+        vertex.normal = normalize_or(normal, glm::vec3(0.0, 1.0, 0.0));
+    }
+}
+
+/// Every face gets its own vertices, the index at position i is i.
+pub fn flat_normals(vertices: &[Vertex], indices: &[u32]) -> (Vec<Vertex>, Vec<u32>) {
+    let mut result = Vec::with_capacity(indices.len());
+
+    for face in indices.chunks_exact(3) {
+        let normal = normalize_or(face_normal(vertices, face), glm::vec3(0.0, 1.0, 0.0));
+
+        for i in face {
+            result.push(Vertex {
+                normal,
+                ..vertices[*i as usize]
+            });
+        }
+    }
+
+    let indices = (0..result.len() as u32).collect();
+
+    (result, indices)
+}
+
+/// Placeholder UVs for meshes without any, every vertex is projected on the side of the Aabb its normal faces most.
+///
+/// Every side of the Aabb spans 0 to 1, so the normals have to be set first.
+pub fn box_uvs(vertices: &mut [Vertex]) {
+    let aabb = Aabb::from_vertices(vertices);
+    // Flat axes don't divide by zero.
+    let size = (aabb.max - aabb.min).map(|s| if s > f32::EPSILON { s } else { 1.0 });
+
+    for vertex in vertices.iter_mut() {
+        let p = ({ vertex.position } - aabb.min).component_div(&size);
+        let n = glm::abs(&{ vertex.normal });
+
+        vertex.tex_coord = match (n.x >= n.y && n.x >= n.z, n.y >= n.z) {
+            (true, _) => glm::vec2(p.z, p.y),
+            (false, true) => glm::vec2(p.x, p.z),
+            (false, false) => glm::vec2(p.x, p.y),
+        };
+    }
+}
+
+/// Per vertex tangent for normal mapping, w is the handedness of the bitangent: cross(normal, tangent) * w.
+///
+/// Like MikkTSpace, tangents are accumulated from the faces, orthogonalized against the normal and get the sign of the
+/// bitangent. Vertices without usable UVs get any tangent perpendicular to their normal.
+pub fn tangents(vertices: &[Vertex], indices: &[u32]) -> Vec<glm::Vec4> {
+    let mut tangents = vec![glm::Vec3::zeros(); vertices.len()];
+    let mut bitangents = vec![glm::Vec3::zeros(); vertices.len()];
+
+    for face in indices.chunks_exact(3) {
+        let [v0, v1, v2] = [0, 1, 2].map(|i| vertices[face[i] as usize]);
+
+        let edge_1 = v1.position - v0.position;
+        let edge_2 = v2.position - v0.position;
+        let delta_1 = v1.tex_coord - v0.tex_coord;
+        let delta_2 = v2.tex_coord - v0.tex_coord;
+
+        let determinant = delta_1.x * delta_2.y - delta_2.x * delta_1.y;
+        if determinant.abs() <= f32::EPSILON { continue; }
+
+        let tangent = (edge_1 * delta_2.y - edge_2 * delta_1.y) / determinant;
+        let bitangent = (edge_2 * delta_1.x - edge_1 * delta_2.x) / determinant;
+
+        for i in face {
+            tangents[*i as usize] += tangent;
+            bitangents[*i as usize] += bitangent;
+        }
+    }
+
+    vertices
+        .iter()
+        .zip(tangents.iter().zip(&bitangents))
+        .map(|(vertex, (tangent, bitangent))| {
+            let normal = vertex.normal;
+
+            // Gram-Schmidt.
+            let tangent = normalize_or(tangent - normal * glm::dot(&normal, tangent), perpendicular(&normal));
+            let handedness = match glm::dot(&glm::cross(&normal, &tangent), bitangent) < 0.0 {
+                true => -1.0,
+                false => 1.0,
+            };
+
+            glm::vec4(tangent.x, tangent.y, tangent.z, handedness)
+        })
+        .collect()
+}
+
+/// Merges the vertices whose attributes are all within epsilon, unused vertices are dropped.
+///
+/// The index at every position still refers to the same vertex, so index ranges stay valid.
+pub fn weld(vertices: &[Vertex], indices: &[u32], epsilon: f32) -> (Vec<Vertex>, Vec<u32>) {
+    let epsilon = epsilon.max(f32::EPSILON);
+    let quantize = |value: f32| (value / epsilon).round() as i64;
+
+    let mut welded = Vec::new();
+    let mut remap = HashMap::new();
+
+    let indices = indices
+        .iter()
+        .map(|i| {
+            let vertex = vertices[*i as usize];
+            let (p, n, tc) = (vertex.position, vertex.normal, vertex.tex_coord);
+            let key = [p.x, p.y, p.z, n.x, n.y, n.z, tc.x, tc.y].map(quantize);
+
+            *remap.entry(key).or_insert_with(|| {
+                welded.push(vertex);
+                welded.len() as u32 - 1
+            })
+        })
+        .collect();
+
+    (welded, indices)
+}
+
+/// Reorders the triangles so consecutive ones share vertices (Forsyth's algorithm), the triangles are not changed.
+pub fn optimize_vertex_cache(indices: &[u32], vertex_count: usize) -> Vec<u32> {
+    let triangle_count = indices.len() / 3;
+
+    let mut vertex_triangles = vec![Vec::new(); vertex_count];
+    for (t, face) in indices.chunks_exact(3).enumerate() {
+        for i in face {
+            vertex_triangles[*i as usize].push(t);
+        }
+    }
+
+    let mut cache_positions = vec![None; vertex_count];
+    let mut vertex_scores = vertex_triangles
+        .iter()
+        .map(|t| vertex_score(None, t.len()))
+        .collect::<Vec<_>>();
+    let mut triangle_scores = indices
+        .chunks_exact(3)
+        .map(|face| face.iter().map(|i| vertex_scores[*i as usize]).sum::<f32>())
+        .collect::<Vec<_>>();
+    let mut added = vec![false; triangle_count];
+
+    let mut cache: Vec<u32> = Vec::with_capacity(VERTEX_CACHE_SIZE + 3);
+    let mut result = Vec::with_capacity(triangle_count * 3);
+    // Next triangle to try when no triangle in the cache is left.
+    let mut cursor = 0;
+    let mut best = None;
+
+    while result.len() < triangle_count * 3 {
+        let triangle = match best.take() {
+            Some(triangle) => triangle,
+            None => {
+                while added[cursor] { cursor += 1; }
+                cursor
+            },
+        };
+
+        added[triangle] = true;
+        let face = &indices[triangle * 3..triangle * 3 + 3];
+        result.extend_from_slice(face);
+
+        for i in face {
+            vertex_triangles[*i as usize].retain(|t| *t != triangle);
+        }
+
+        // Most recent first, the ones pushed out lose their position.
+        let mut new_cache = face.to_vec();
+        new_cache.extend(cache.iter().filter(|v| !face.contains(v)));
+        for evicted in new_cache.iter().skip(VERTEX_CACHE_SIZE) {
+            cache_positions[*evicted as usize] = None;
+        }
+        let touched = new_cache.clone();
+        new_cache.truncate(VERTEX_CACHE_SIZE);
+        cache = new_cache;
+
+        for (position, v) in cache.iter().enumerate() {
+            cache_positions[*v as usize] = Some(position);
+        }
+
+        for v in &touched {
+            let v = *v as usize;
+            let score = vertex_score(cache_positions[v], vertex_triangles[v].len());
+            let delta = score - vertex_scores[v];
+            vertex_scores[v] = score;
+
+            for t in &vertex_triangles[v] {
+                triangle_scores[*t] += delta;
+            }
+        }
+
+        best = cache
+            .iter()
+            .flat_map(|v| vertex_triangles[*v as usize].iter())
+            .copied()
+            .max_by(|a, b| triangle_scores[*a].total_cmp(&triangle_scores[*b]));
+    }
+
+    result
+}
+
+/// Cache misses per triangle of a FIFO cache, 0.5 is the best a large regular mesh can get and 3 the worst.
+pub fn average_cache_miss_ratio(indices: &[u32], cache_size: usize) -> f32 {
+    let triangle_count = indices.len() / 3;
+    if triangle_count == 0 { return 0.0; }
+
+    let mut cache = std::collections::VecDeque::with_capacity(cache_size);
+    let mut misses = 0;
+
+    for i in indices {
+        if cache.contains(i) { continue; }
+
+        misses += 1;
+        if cache.len() == cache_size {
+            cache.pop_front();
+        }
+        cache.push_back(*i);
+    }
+
+    misses as f32 / triangle_count as f32
+}
+
+fn vertex_score(cache_position: Option<usize>, remaining_triangles: usize) -> f32 {
+    if remaining_triangles == 0 { return -1.0; }
+
+    let cache_score = match cache_position {
+        None => 0.0,
+        // The last triangle, it doesn't matter which of its vertices.
+        Some(position) if position < 3 => 0.75,
+        Some(position) => (1.0 - (position - 3) as f32 / (VERTEX_CACHE_SIZE - 3) as f32).powf(1.5),
+    };
+
+    // Vertices with few triangles left are finished first.
+    cache_score + 2.0 * (remaining_triangles as f32).powf(-0.5)
+}
+
+/// Not normalized, its length is twice the area.
+fn face_normal(vertices: &[Vertex], face: &[u32]) -> glm::Vec3 {
+    let [a, b, c] = [0, 1, 2].map(|i| vertices[face[i] as usize].position);
+
+    glm::cross(&(b - a), &(c - a))
+}
+
+fn normalize_or(v: glm::Vec3, fallback: glm::Vec3) -> glm::Vec3 {
+    match v.norm() > f32::EPSILON {
+        true => v.normalize(),
+        false => fallback,
+    }
+}
+
+fn perpendicular(normal: &glm::Vec3) -> glm::Vec3 {
+    let axis = match normal.x.abs() < 0.9 {
+        true => glm::vec3(1.0, 0.0, 0.0),
+        false => glm::vec3(0.0, 1.0, 0.0),
+    };
+
+    normalize_or(glm::cross(normal, &axis), glm::vec3(1.0, 0.0, 0.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vertex(x: f32, y: f32, z: f32, u: f32, v: f32) -> Vertex {
+        Vertex {
+            position: glm::vec3(x, y, z),
+            normal: glm::vec3(0.0, 0.0, 1.0),
+            tex_coord: glm::vec2(u, v),
+        }
+    }
+
+    /// Two triangles in the z = 0 plane, facing +z.
+    fn quad() -> (Vec<Vertex>, Vec<u32>) {
+        let vertices = vec![
+            vertex(0.0, 0.0, 0.0, 0.0, 0.0),
+            vertex(1.0, 0.0, 0.0, 1.0, 0.0),
+            vertex(1.0, 1.0, 0.0, 1.0, 1.0),
+            vertex(0.0, 1.0, 0.0, 0.0, 1.0),
+        ];
+
+        (vertices, vec![0, 1, 2, 0, 2, 3])
+    }
+
+    /// n x n quads in the z = 0 plane.
+    fn grid(n: u32) -> (Vec<Vertex>, Vec<u32>) {
+        let vertices = (0..=n)
+            .flat_map(|y| (0..=n).map(move |x| vertex(x as f32, y as f32, 0.0, x as f32, y as f32)))
+            .collect();
+
+        let mut indices = Vec::new();
+        for y in 0..n {
+            for x in 0..n {
+                let i = y * (n + 1) + x;
+                indices.extend_from_slice(&[i, i + 1, i + n + 2, i, i + n + 2, i + n + 1]);
+            }
+        }
+
+        (vertices, indices)
+    }
+
+    fn approx(a: glm::Vec3, b: glm::Vec3) -> bool {
+        glm::distance(&a, &b) < 1e-5
+    }
+
+    /// The triangles as sets of positions, to compare meshes with different vertices.
+    fn triangles(vertices: &[Vertex], indices: &[u32]) -> Vec<[[i32; 3]; 3]> {
+        let mut result = indices
+            .chunks_exact(3)
+            .map(|face| {
+                let mut triangle = [0, 1, 2].map(|i| {
+                    let p = vertices[face[i] as usize].position;
+                    [p.x, p.y, p.z].map(|c| (c * 1000.0).round() as i32)
+                });
+                triangle.sort();
+                triangle
+            })
+            .collect::<Vec<_>>();
+        result.sort();
+
+        result
+    }
+
+    #[test]
+    fn smooth_normals_of_a_plane_face_up() {
+        let (mut vertices, indices) = quad();
+        for v in &mut vertices {
+            v.normal = glm::Vec3::zeros();
+        }
+
+        smooth_normals(&mut vertices, &indices);
+
+        for v in &vertices {
+            assert!(approx(v.normal, glm::vec3(0.0, 0.0, 1.0)));
+        }
+    }
+
+    #[test]
+    fn smooth_normals_average_the_faces() {
+        // Two faces at a right angle sharing the edge from vertex 0 to 1.
+        let mut vertices = vec![
+            vertex(0.0, 0.0, 0.0, 0.0, 0.0),
+            vertex(1.0, 0.0, 0.0, 0.0, 0.0),
+            vertex(0.0, 1.0, 0.0, 0.0, 0.0),
+            vertex(0.0, 0.0, 1.0, 0.0, 0.0),
+        ];
+        let indices = [0, 1, 2, 0, 3, 1];
+
+        smooth_normals(&mut vertices, &indices);
+
+        let expected = glm::vec3(0.0, 1.0, 1.0).normalize();
+        assert!(approx(vertices[0].normal, expected));
+        assert!(approx(vertices[1].normal, expected));
+        assert!(approx(vertices[2].normal, glm::vec3(0.0, 0.0, 1.0)));
+        assert!(approx(vertices[3].normal, glm::vec3(0.0, 1.0, 0.0)));
+    }
+
+    #[test]
+    fn smooth_normals_of_unused_vertices_are_valid() {
+        let mut vertices = vec![vertex(0.0, 0.0, 0.0, 0.0, 0.0)];
+
+        smooth_normals(&mut vertices, &[]);
+
+        assert!(approx(vertices[0].normal, glm::vec3(0.0, 1.0, 0.0)));
+    }
+
+    #[test]
+    fn flat_normals_split_the_vertices() {
+        let vertices = vec![
+            vertex(0.0, 0.0, 0.0, 0.0, 0.0),
+            vertex(1.0, 0.0, 0.0, 0.0, 0.0),
+            vertex(0.0, 1.0, 0.0, 0.0, 0.0),
+            vertex(0.0, 0.0, 1.0, 0.0, 0.0),
+        ];
+        let indices = [0, 1, 2, 0, 3, 1];
+
+        let (flat, flat_indices) = flat_normals(&vertices, &indices);
+
+        assert_eq!(flat.len(), 6);
+        assert_eq!(flat_indices, vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(triangles(&flat, &flat_indices), triangles(&vertices, &indices));
+        for v in &flat[0..3] {
+            assert!(approx(v.normal, glm::vec3(0.0, 0.0, 1.0)));
+        }
+        for v in &flat[3..6] {
+            assert!(approx(v.normal, glm::vec3(0.0, 1.0, 0.0)));
+        }
+    }
+
+    #[test]
+    fn box_uvs_project_on_the_faced_side() {
+        let vertices = vec![
+            vertex(0.0, 0.0, 0.0, 0.0, 0.0),
+            vertex(1.0, 0.0, 0.0, 0.0, 0.0),
+            vertex(0.0, 1.0, 0.0, 0.0, 0.0),
+            vertex(0.0, 0.0, 1.0, 0.0, 0.0),
+        ];
+        // Facing +z, then facing +y.
+        let (mut flat, _) = flat_normals(&vertices, &[0, 1, 2, 0, 3, 1]);
+
+        box_uvs(&mut flat);
+
+        let uvs = flat.iter().map(|v| v.tex_coord).collect::<Vec<_>>();
+        assert_eq!(uvs, vec![
+            glm::vec2(0.0, 0.0), glm::vec2(1.0, 0.0), glm::vec2(0.0, 1.0),
+            glm::vec2(0.0, 0.0), glm::vec2(0.0, 1.0), glm::vec2(1.0, 0.0),
+        ]);
+    }
+
+    #[test]
+    fn tangents_follow_u() {
+        let (vertices, indices) = quad();
+
+        let tangents = tangents(&vertices, &indices);
+
+        assert_eq!(tangents.len(), vertices.len());
+        for t in &tangents {
+            assert!(approx(t.xyz(), glm::vec3(1.0, 0.0, 0.0)));
+            assert_eq!(t.w, 1.0);
+        }
+    }
+
+    #[test]
+    fn tangents_of_mirrored_uvs_are_left_handed() {
+        let (mut vertices, indices) = quad();
+        for v in &mut vertices {
+            let tex_coord = v.tex_coord;
+            v.tex_coord = glm::vec2(tex_coord.x, 1.0 - tex_coord.y);
+        }
+
+        let tangents = tangents(&vertices, &indices);
+
+        for t in &tangents {
+            assert!(approx(t.xyz(), glm::vec3(1.0, 0.0, 0.0)));
+            assert_eq!(t.w, -1.0);
+        }
+    }
+
+    #[test]
+    fn tangents_without_uvs_are_perpendicular() {
+        let (mut vertices, indices) = quad();
+        for v in &mut vertices {
+            v.tex_coord = glm::Vec2::zeros();
+        }
+
+        for (t, v) in tangents(&vertices, &indices).iter().zip(&vertices) {
+            assert!((t.xyz().norm() - 1.0).abs() < 1e-5);
+            assert!(glm::dot(&t.xyz(), &{ v.normal }).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn weld_merges_duplicates() {
+        let (vertices, indices) = quad();
+        // Every triangle with its own vertices, one of them slightly off.
+        let (mut split, split_indices) = flat_normals(&vertices, &indices);
+        for v in &mut split {
+            v.normal = glm::vec3(0.0, 0.0, 1.0);
+        }
+        split[3].position = { split[3].position } + glm::vec3(1e-6, 0.0, 0.0);
+
+        let (welded, welded_indices) = weld(&split, &split_indices, 1e-4);
+
+        assert_eq!(welded.len(), 4);
+        assert_eq!(welded_indices.len(), 6);
+        assert_eq!(triangles(&welded, &welded_indices), triangles(&vertices, &indices));
+    }
+
+    #[test]
+    fn weld_keeps_different_attributes() {
+        let (mut vertices, _) = quad();
+        vertices[1] = vertices[0];
+        vertices[1].tex_coord = glm::vec2(0.5, 0.5);
+
+        let (welded, welded_indices) = weld(&vertices, &[0, 1, 2], 1e-4);
+
+        assert_eq!(welded.len(), 3);
+        assert_eq!(welded_indices, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn weld_drops_unused_vertices() {
+        let (vertices, _) = quad();
+
+        let (welded, welded_indices) = weld(&vertices, &[3, 2, 1], 1e-4);
+
+        assert_eq!(welded.len(), 3);
+        assert_eq!(welded_indices, vec![0, 1, 2]);
+        assert_eq!({ welded[0].position }, { vertices[3].position });
+    }
+
+    #[test]
+    fn vertex_cache_keeps_the_triangles() {
+        let (vertices, indices) = grid(16);
+
+        let optimized = optimize_vertex_cache(&indices, vertices.len());
+
+        assert_eq!(optimized.len(), indices.len());
+        assert_eq!(triangles(&vertices, &optimized), triangles(&vertices, &indices));
+    }
+
+    #[test]
+    fn vertex_cache_reduces_misses() {
+        let (vertices, indices) = grid(32);
+        // Worst case order: every triangle far from the previous one.
+        let mut shuffled = indices.chunks_exact(3).collect::<Vec<_>>();
+        let len = shuffled.len();
+        shuffled = (0..len).map(|i| shuffled[(i * 97) % len]).collect();
+        let shuffled = shuffled.concat();
+
+        let optimized = optimize_vertex_cache(&shuffled, vertices.len());
+
+        let before = average_cache_miss_ratio(&shuffled, 16);
+        let after = average_cache_miss_ratio(&optimized, 16);
+        assert!(after < before);
+        assert!(after < 1.0, "ACMR {after}");
+    }
+
+    #[test]
+    fn vertex_cache_of_nothing() {
+        assert!(optimize_vertex_cache(&[], 0).is_empty());
+        assert_eq!(average_cache_miss_ratio(&[], 16), 0.0);
+    }
+
+    #[test]
+    fn aabb_and_sphere_contain_the_vertices() {
+        let vertices = vec![
+            vertex(-1.0, 2.0, 0.0, 0.0, 0.0),
+            vertex(3.0, -2.0, 1.0, 0.0, 0.0),
+            vertex(0.0, 0.0, -1.0, 0.0, 0.0),
+        ];
+
+        let aabb = Aabb::from_vertices(&vertices);
+        assert_eq!(aabb.min, glm::vec3(-1.0, -2.0, -1.0));
+        assert_eq!(aabb.max, glm::vec3(3.0, 2.0, 1.0));
+        assert_eq!(aabb.center(), glm::vec3(1.0, 0.0, 0.0));
+        assert_eq!(aabb.extents(), glm::vec3(2.0, 2.0, 1.0));
+
+        let sphere = BoundingSphere::from_vertices(&vertices);
+        assert_eq!(sphere.center, aabb.center());
+        for v in &vertices {
+            assert!(glm::distance(&{ v.position }, &sphere.center) <= sphere.radius + 1e-5);
+        }
+    }
+
+    #[test]
+    fn bounds_of_nothing_are_empty() {
+        assert_eq!(Aabb::from_vertices(&[]), Aabb::default());
+        assert_eq!(BoundingSphere::from_vertices(&[]), BoundingSphere::default());
+    }
+}
@@ -1,33 +1,43 @@
-use std::{collections::HashMap, ffi::CString, sync::{mpsc::{Receiver, Sender}, Arc, Mutex, MutexGuard}, thread::JoinHandle};
+use std::{cell::RefCell, collections::HashMap, sync::{mpsc::{Receiver, Sender}, Arc, Mutex, MutexGuard}, thread::JoinHandle};
+use backend::{gl_backend::OpenGlBackend, recording_backend::RecordingBackend, software::SoftwareBackend, DrawCall, InstanceData, RenderBackend, RenderTargetId, TextureBinding};
 use command::{RendererCommand, SendDrawData, SendInstanceDrawData};
-use glutin::{display::GlDisplay, surface::GlSurface};
-use imgui_config::{imgui_init, ImGuiCore};
+use glutin::display::GlDisplay;
+use imgui_config::{imgui_init, imgui_init_windowless, ImGuiCore};
 use material::Material;
-use mesh::Mesh;
-use opengl::{gl_buffer::GlBuffer, gl_init::{init_opengl, init_window}, GlSpecs};
-use render_target::{FramebufferFormat, RenderTarget, RenderTargetSpecs};
+use mesh::{Mesh, MeshUpdate, Topology};
+use opengl::{gl_init::{init_opengl, init_opengl_offscreen, init_window}, GlSpecs};
+use render_graph::{CompiledRenderGraph, RenderGraph, RenderPassDesc};
+use render_target::RenderTargetSpecs;
 use shader::Shader;
 use sllog::{error, warn};
-use texture::{Texture, TextureSpecs};
+use texture::{Texture, TextureKind, TextureSpecs};
 use uniform::Uniform;
-use vertex::{LgVertex, VertexInfo};
+use vertex::{Vertex, VertexInfo};
 
-use crate::{gl_check, gl_check_and_print, glm, lg_types::no_check_option::NCOption, profile_function, profile_scope, StdError};
-use super::{asset_manager::AssetManager, uuid::UUID, window::LgWindow};
+use crate::{glm, lg_types::no_check_option::NCOption, profile_function, profile_scope, StdError};
+use super::{asset_manager::{Asset, AssetImporter, AssetManager, Handle, LoadBatch}, entity::LgEntityNode, uuid::UUID, window::LgWindow};
 
 pub mod mesh;
+pub mod mesh_processing;
+pub mod primitives;
 pub mod material;
 pub mod texture;
+pub mod sampler;
 pub mod shader;
 pub mod uniform;
 pub mod buffer;
 pub mod vertex;
+pub mod render_state;
 pub mod render_target;
+pub mod render_graph;
+pub mod backend;
 pub mod command;
 mod imgui_config;
 mod opengl;
 
 type Job = Box<dyn FnOnce() -> bool + Send + 'static>;
+/// Called with the finished backbuffer at the end of the frame, see Renderer::capture_backbuffer.
+type BackbufferRequest = Box<dyn FnOnce(Result<&image::RgbaImage, &str>) + Send + 'static>;
 
 const FINAL_PASS_MESH: UUID = UUID::from_u128(252411435688744967694609164507863584779);
 const FINAL_PASS_MATERIAL: UUID = UUID::from_u128(315299335240398778209169027697428014904);
@@ -40,53 +50,79 @@ pub struct CreationWindowInfo<'a> {
     pub vsync: bool,
 }
 
+/// Backbuffer requested with Renderer::capture_backbuffer, ready once the frame ended.
+#[derive(Debug)]
+pub struct BackbufferCapture {
+    receiver: Receiver<Result<image::RgbaImage, String>>,
+}
+impl BackbufferCapture {
+    /// Blocks until the frame ended, so only call it after it was (e.g. in the next update).
+    pub fn wait(self) -> Result<image::RgbaImage, StdError> {
+        self.receiver
+            .recv()
+            .map_err(|_| "The renderer stopped before capturing the backbuffer! (Renderer)")?
+            .map_err(|e| e.into())
+    }
+
+    /// None until the frame ended.
+    pub fn try_get(&self) -> Option<Result<image::RgbaImage, StdError>> {
+        self.receiver
+            .try_recv()
+            .ok()
+            .map(|result| result.map_err(|e| e.into()))
+    }
+}
+
 pub struct Renderer {
     core: NCOption<Arc<Mutex<RendererCore>>>,
     job_sender: Sender<Job>,
     message_sender: Sender<RendererCommand>,
     receiver: Receiver<RendererCommand>,
-    thread: JoinHandle<()>,
+    /// None when headless.
+    thread: Option<JoinHandle<()>>,
+
+    render_graph: RefCell<RenderGraph>,
+    /// Execution order of the compiled render graph.
+    pass_order: RefCell<Vec<String>>,
+    /// Jobs sent after begin_render_pass() are held here and sent in the graph order at the end of the frame.
+    recorded_passes: RefCell<HashMap<String, Vec<Job>>>,
+    recording_pass: RefCell<Option<String>>,
 }
 // Public
 impl Renderer {
-    /// Don't store this pointer, it points to a HashMap!
-    pub fn get_texture(&self, uuid: &UUID) -> Result<*const Texture, StdError> {
+    pub fn get_texture(&self, uuid: &UUID) -> Result<Handle<Texture>, StdError> {
         self.core
             .lock()
             .unwrap()
             .asset_manager
-            .get_texture(uuid)
+            .handle(uuid)
     }
     
-    /// Don't store this pointer, it points to a HashMap!
-    pub fn get_mesh(&self, uuid: &UUID) -> Result<*const Mesh, StdError> {
+    pub fn get_mesh(&self, uuid: &UUID) -> Result<Handle<Mesh>, StdError> {
         self.core
             .lock()
             .unwrap()
             .asset_manager
-            .get_mesh(uuid)
+            .handle(uuid)
     }
     
-    /// Don't store this pointer, it points to a HashMap!
-    pub fn get_shader(&self, uuid: &UUID) -> Result<*const Shader, StdError> {
+    pub fn get_shader(&self, uuid: &UUID) -> Result<Handle<Shader>, StdError> {
         self.core
             .lock()
             .unwrap()
             .asset_manager
-            .get_shader(uuid)
+            .handle(uuid)
     }
     
-    /// Don't store this pointer, it points to a HashMap!
-    pub fn get_material(&self, uuid: &UUID) -> Result<*const Material, StdError> {
+    pub fn get_material(&self, uuid: &UUID) -> Result<Handle<Material>, StdError> {
         self.core
             .lock()
             .unwrap()
             .asset_manager
-            .get_material(uuid)
+            .handle(uuid)
     }
     
-    /// Don't store this pointer, it points to a HashMap!
-    pub fn create_material(&self, uuid: &UUID, name: &str, textures: Vec<String>, shaders: Vec<String>) -> Result<*const Material, StdError> {
+    pub fn create_material(&self, name: &str, textures: Vec<String>, shaders: Vec<String>) -> Result<Handle<Material>, StdError> {
         self.core
             .lock()
             .unwrap()
@@ -94,13 +130,90 @@ impl Renderer {
             .create_material(name, textures, shaders)
     }
     
-    /// Don't store this pointer, it points to a HashMap!
-    pub fn create_texture(&self, name: &str, path: &str, specs: TextureSpecs) -> Result<*const Texture, StdError> {
+    pub fn create_texture(&self, path: &str, specs: TextureSpecs) -> Result<Handle<Texture>, StdError> {
+        self.core
+            .lock()
+            .unwrap()
+            .asset_manager
+            .create_texture(path, specs)
+    }
+
+    /// A triangle mesh built in code, it can be changed with update_mesh.
+    pub fn create_mesh(&self, name: &str, vertices: Vec<Vertex>, indices: Vec<u32>) -> Result<Handle<Mesh>, StdError> {
+        self.create_mesh_with_topology(name, vertices, indices, Topology::Triangles)
+    }
+
+    /// For lines and points, e.g. debug shapes.
+    pub fn create_mesh_with_topology(&self, name: &str, vertices: Vec<Vertex>, indices: Vec<u32>, topology: Topology) -> Result<Handle<Mesh>, StdError> {
+        self.core
+            .lock()
+            .unwrap()
+            .asset_manager
+            .create_mesh(name, vertices, indices, topology)
+    }
+
+    /// Applied on the render thread before the draws of the current frame, errors are logged.
+    pub fn update_mesh(&self, mesh: &UUID, update: MeshUpdate) {
+        let (r_core, _) = self.get_coms_data();
+        let mesh = *mesh;
+
+        self.send_job(Box::new(move || {
+            if let Err(e) = r_core.lock().unwrap().asset_manager.update_mesh(&mesh, update) {
+                error!("Failed to update mesh {}: {e} (Renderer)", mesh);
+            }
+
+            false
+        }));
+    }
+
+    /// The node tree of a glTF file as entities, see AssetManager::spawn_gltf.
+    pub fn spawn_gltf(&self, path: &str) -> Result<Vec<LgEntityNode>, StdError> {
+        self.core
+            .lock()
+            .unwrap()
+            .asset_manager
+            .spawn_gltf(path)
+    }
+
+    /// Files with extension become assets, see AssetManager::register_importer.
+    pub fn register_importer(&self, extension: &str, importer: Arc<dyn AssetImporter>) -> Result<(), StdError> {
+        self.core
+            .lock()
+            .unwrap()
+            .asset_manager
+            .register_importer(extension, importer)
+    }
+
+    /// Starts loading the asset on the rayon pool, poll the handle state or wait with wait_for_loads.
+    pub fn load<T: Asset>(&self, uuid: &UUID) -> Result<Handle<T>, StdError> {
         self.core
             .lock()
             .unwrap()
             .asset_manager
-            .create_texture(name, path, specs)
+            .load_async(uuid)
+    }
+
+    /// Blocks until every load of the batch finished, loaded or failed.
+    /// 
+    /// Finished loads are otherwise picked up at the start of each frame.
+    pub fn wait_for_loads(&self, batch: &LoadBatch) {
+        profile_function!();
+
+        loop {
+            self.core.lock().unwrap().asset_manager.finish_loads();
+            if batch.is_done() { break; }
+
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+    }
+
+    /// Locks the core mutex while f runs, None if the asset isn't loaded.
+    pub fn with_asset<T: Asset, R>(&self, handle: &Handle<T>, f: impl FnOnce(&T) -> R) -> Option<R> {
+        let core = self.core.lock().unwrap();
+
+        core.asset_manager
+            .resolve(handle)
+            .map(f)
     }
 
     /// Locks the core mutex
@@ -108,22 +221,36 @@ impl Renderer {
         self.core.lock().unwrap()
     }
 
+    /// Only outputs (passes nobody reads) are guaranteed to still hold their own image at the end of the frame, 
+    /// intermediate targets are shared between passes.
     pub fn get_prev_frame_color_tex_gl(&self, name: &str) -> Option<gl::types::GLuint> {
-        if let Some(tex) = self.core().render_passes.get(name) {
-            return Some(tex.color_texture);
-        }
+        let core = self.core();
+        let target = core.pass_target(name).ok()?;
 
-        None
+        core.backend.texture_id(target)
     }
     
     pub fn set_vsync(&self, val: bool) {
         let (r_core, _) = self.get_coms_data();
 
-        self.job_sender.send(Box::new(move || {
+        self.send_job(Box::new(move || {
             r_core.lock().unwrap().set_vsync(val);
 
             false
-        })).unwrap();
+        }));
+    }
+
+    /// Reloads the assets changed on disk at the start of a frame, checking every poll_interval.
+    /// 
+    /// None stops watching. Pointers to reloaded assets are invalidated.
+    pub fn set_hot_reload(&self, poll_interval: Option<std::time::Duration>) {
+        let (r_core, _) = self.get_coms_data();
+
+        self.send_job(Box::new(move || {
+            r_core.lock().unwrap().asset_manager.set_hot_reload(poll_interval);
+
+            false
+        }));
     }
 
     /// Can block.
@@ -135,108 +262,177 @@ impl Renderer {
         profile_function!();
         let (r_core, _) = self.get_coms_data();
 
-        self.job_sender.send(Box::new(move || unsafe {
-            r_core.lock().unwrap().resize(new_size);
+        self.send_job(Box::new(move || {
+            r_core.lock().unwrap().resize(new_size).unwrap();
             
             false
-        }))
-        .unwrap();
+        }));
     }
 
-    pub fn create_render_pass(&self, name: String, specs: RenderTargetSpecs) {
+    /// The graph is validated and sorted right away, render targets are (re)allocated on the render thread.
+    pub fn set_render_graph(&self, graph: RenderGraph) -> Result<(), StdError> {
+        profile_function!();
+        let compiled = graph.compile()?;
+
+        *self.pass_order.borrow_mut() = compiled.order.clone();
+        *self.render_graph.borrow_mut() = graph;
+
         let (r_core, _) = self.get_coms_data();
-        
-        self.job_sender.send(Box::new(move || {
-            r_core.lock().unwrap().render_passes.insert(name, RenderTarget::new(specs));
+
+        self.send_job(Box::new(move || {
+            r_core.lock().unwrap().set_render_graph(compiled).unwrap();
             
             false
-        })).unwrap();
+        }));
+
+        Ok(())
+    }
+
+    pub fn render_graph(&self) -> RenderGraph {
+        self.render_graph.borrow().clone()
+    }
+
+    /// Adds a pass that doesn't read any other pass to the render graph, or replaces the one with the same name.
+    pub fn create_render_pass(&self, name: String, specs: RenderTargetSpecs) {
+        let mut graph = self.render_graph();
+        let reads = graph.pass(&name)
+            .map(|p| p.reads.clone())
+            .unwrap_or_default();
+
+        graph.set_pass(RenderPassDesc { name, reads, specs });
+
+        if let Err(e) = self.set_render_graph(graph) {
+            error!("{e}");
+        }
     }
 
     pub fn set_render_pass_size(&self, name: String, new_size: (i32, i32)) {
-        let (r_core, _) = self.get_coms_data();
-        
-        self.job_sender.send(Box::new(move || {
-            let mut r_core = r_core.lock().unwrap();
+        let mut graph = self.render_graph();
 
-            let mut specs = r_core.render_passes.get_mut(&name).unwrap().specs.clone();
-            specs.viewport = (0, 0, new_size.0, new_size.1);
-            r_core.render_passes.insert(name, RenderTarget::new(specs));
-            
-            false
-        }))
-        .unwrap();
+        match graph.pass_mut(&name) {
+            Some(pass) => pass.specs.viewport = (0, 0, new_size.0, new_size.1),
+            None => {
+                error!("{} is an invalid render pass! (Renderer)", name);
+                return;
+            }
+        }
+
+        if let Err(e) = self.set_render_graph(graph) {
+            error!("{e}");
+        }
     }
 
+    /// Every draw until the next begin_render_pass() belongs to this pass.
+    /// 
+    /// They are submitted at the end of the frame, following the render graph order.
     pub fn begin_render_pass(&self, name: String) {
-        let (r_core, _) = self.get_coms_data();
-        
-        self.job_sender.send(Box::new(move || unsafe {
-            let mut r_core = r_core.lock().unwrap();
-            let target = r_core.render_passes.get(&name).unwrap();
-            
-            r_core.set_render_target(target.framebuffer, &target.specs);
+        if !self.pass_order.borrow().contains(&name) {
+            error!("{} is not a pass of the render graph! (Renderer)", name);
+            return;
+        }
 
-            if !r_core.active_pass.is_empty() {
-                let active_pass = std::mem::take(&mut r_core.active_pass);
-                r_core.render_pipeline.push(active_pass);
-            }
-            
-            r_core.active_pass = name;
-            
-            false
-        }))
-        .unwrap();
+        self.recorded_passes.borrow_mut().entry(name.clone()).or_default();
+        *self.recording_pass.borrow_mut() = Some(name);
     }
 
     pub fn send_instance_data(&self, instance_data: SendInstanceDrawData) {
         let (r_core, _) = self.get_coms_data();
         
-        self.job_sender.send(Box::new(move || unsafe {
-            r_core.lock().unwrap().send_data(instance_data).unwrap();
+        self.send_pass_job(Box::new(move || {
+            if let Err(e) = r_core.lock().unwrap().send_data(instance_data) {
+                error!("Failed to send instance data: {e} (Renderer)");
+            }
                 
             false
-        }))
-        .unwrap()
+        }));
     }
 
     pub fn draw_instanced(&self) {
         let (r_core, _) = self.get_coms_data();
         
-        self.job_sender.send(Box::new(move || unsafe {
-            r_core.lock().unwrap().draw_instanced().unwrap(); 
+        self.send_pass_job(Box::new(move || {
+            if let Err(e) = r_core.lock().unwrap().draw_instanced() {
+                error!("Failed to draw instanced: {e} (Renderer)");
+            }
             
             false
-        }))
-        .unwrap();
+        }));
     }
 
     pub fn draw(&self, draw_data: SendDrawData) {
         let (r_core, _) = self.get_coms_data();
         
-        self.job_sender.send(Box::new(move || unsafe {
-            r_core.lock().unwrap().draw(draw_data).unwrap();
+        self.send_pass_job(Box::new(move || {
+            if let Err(e) = r_core.lock().unwrap().draw(draw_data) {
+                error!("Failed to draw: {e} (Renderer)");
+            }
             
             false
-        }))
-        .unwrap();
+        }));
     }
 
     pub fn set_fonts(&self) {
         let (r_core, _) = self.get_coms_data();
 
-        self.job_sender.send(Box::new(move || unsafe {
+        self.send_job(Box::new(move || unsafe {
             r_core.lock().unwrap().imgui_core.set_fonts().unwrap();
             
             false
-        }))
-        .unwrap();
+        }));
+    }
+
+    /// True when there is no window and no OpenGL context, jobs run right away against a RecordingBackend.
+    pub fn is_headless(&self) -> bool {
+        self.thread.is_none()
+    }
+
+    /// Reads back the color attachment of a render pass.
+    /// 
+    /// Will always wait(block).
+    pub fn capture_render_pass(&self, name: &str) -> Result<image::RgbaImage, StdError> {
+        let name = name.to_string();
+
+        self.capture(move |r_core| {
+            let target = r_core.pass_target(&name)?;
+
+            r_core.backend.read_pixels(Some(target))
+        })
+    }
+
+    /// Reads back the backbuffer at the end of the current frame, after the final pass and ImGui, before it is presented.
+    /// 
+    /// Doesn't wait, see BackbufferCapture::wait.
+    pub fn capture_backbuffer(&self) -> BackbufferCapture {
+        let (sender, receiver) = std::sync::mpsc::channel();
+
+        self.request_backbuffer(Box::new(move |result| {
+            let _ = sender.send(result.cloned().map_err(str::to_string));
+        }));
+
+        BackbufferCapture { receiver }
+    }
+
+    /// Will always wait(block).
+    pub fn save_render_pass(&self, name: &str, path: impl AsRef<std::path::Path>) -> Result<(), StdError> {
+        save_png(&self.capture_render_pass(name)?, path.as_ref())
+    }
+
+    /// Saved by the render thread at the end of the current frame, like capture_backbuffer. Errors are logged.
+    pub fn save_backbuffer(&self, path: impl AsRef<std::path::Path>) {
+        let path = path.as_ref().to_path_buf();
+
+        self.request_backbuffer(Box::new(move |result| {
+            if let Err(e) = result.map_err(|e| e.into()).and_then(|image| save_png(image, &path)) {
+                error!("Failed to save the backbuffer to {}: {e} (Renderer)", path.display());
+            }
+        }));
     }
 }
 // Public(crate)
 impl Renderer {
     pub(crate) fn new(
-        window_info: CreationWindowInfo, 
+        window_info: CreationWindowInfo,
+        asset_manager: AssetManager,
     ) -> Result<(Self, LgWindow), StdError> 
     {
         profile_function!();
@@ -256,9 +452,9 @@ impl Renderer {
             let (imgui_context, imgui_winit) = imgui_init(&window);
             let renderer_core = Arc::new(Mutex::new(RendererCore::new(
                 specs, 
-                AssetManager::default(),
+                asset_manager,
                 imgui_context,
-                imgui_winit,
+                Some(imgui_winit),
             ).unwrap()));
 
             renderer_core.lock().unwrap().set_vsync(window_info.vsync);
@@ -283,46 +479,134 @@ impl Renderer {
                 job_sender: s_sender,
                 message_sender: r_sender,
                 receiver: r_receiver,
-                thread,
+                thread: Some(thread),
+                render_graph: RefCell::default(),
+                pass_order: RefCell::default(),
+                recorded_passes: RefCell::default(),
+                recording_pass: RefCell::default(),
             },
             window,
         ))
     }
+
+    /// Null renderer, no window, no OpenGL and no render thread.
+    /// 
+    /// Jobs run right away on the calling thread against a RecordingBackend, see RendererCore::backend_mut().
+    pub(crate) fn new_headless(
+        window_info: CreationWindowInfo,
+        asset_manager: AssetManager,
+    ) -> Result<(Self, LgWindow), StdError>
+    {
+        profile_function!();
+
+        let (s_sender, _) = std::sync::mpsc::channel::<Job>();
+        let (r_sender, r_receiver) = std::sync::mpsc::channel();
+
+        let imgui_core = ImGuiCore::new_headless(
+            imgui::Context::create(), 
+            (window_info.width, window_info.height)
+        );
+        let renderer_core = RendererCore::with_backend(
+            Box::new(RecordingBackend::new((window_info.width, window_info.height))),
+            asset_manager,
+            imgui_core
+        )?;
+
+        Ok((
+            Self {
+                core: NCOption::Some(Arc::new(Mutex::new(renderer_core))),
+                job_sender: s_sender,
+                message_sender: r_sender,
+                receiver: r_receiver,
+                thread: None,
+                render_graph: RefCell::default(),
+                pass_order: RefCell::default(),
+                recorded_passes: RefCell::default(),
+                recording_pass: RefCell::default(),
+            },
+            LgWindow::new_headless(window_info.width, window_info.height),
+        ))
+    }
+
+    /// No window, but a real OpenGL context rendering to a pbuffer or a surfaceless context.
+    /// 
+    /// Falls back to the SoftwareBackend when no OpenGL context can be created.
+    pub(crate) fn new_offscreen(
+        window_info: CreationWindowInfo,
+        asset_manager: AssetManager,
+    ) -> Result<(Self, LgWindow), StdError>
+    {
+        Self::new_windowless(window_info, asset_manager, true)
+    }
+
+    /// No window and no GPU, everything is rasterized on the CPU by the SoftwareBackend.
+    pub(crate) fn new_software(
+        window_info: CreationWindowInfo,
+        asset_manager: AssetManager,
+    ) -> Result<(Self, LgWindow), StdError>
+    {
+        Self::new_windowless(window_info, asset_manager, false)
+    }
     
     pub(crate) fn begin(&self) {
         let (r_core, _) = self.get_coms_data();
         
-        self.job_sender.send(Box::new(move || {
-            let mut r_core = r_core.lock().unwrap();
-
+        self.send_job(Box::new(move || {
+            let mut core = r_core.lock().unwrap();
+            core.asset_manager.hot_reload();
+            core.asset_manager.finish_loads();
             // TODO: Maybe don't do this
-            r_core.asset_manager.init_gl_program().unwrap();
-            r_core.asset_manager.init_gl_vao().unwrap();                                
-            r_core.asset_manager.init_gl_texture().unwrap();                                
+            core.init_assets().unwrap();
             
             false
-        }))
-        .unwrap();
+        }));
+    }
+
+    /// Sends the jobs recorded for each pass, in the render graph order.
+    pub(crate) fn flush_render_graph(&self) {
+        profile_function!();
+        *self.recording_pass.borrow_mut() = None;
+
+        let mut recorded = std::mem::take(&mut *self.recorded_passes.borrow_mut());
+        let order = self.pass_order.borrow().clone();
+
+        // Passes without draws are still bound and cleared, so they don't keep the last frame and PREVIOUS_PASS follows the graph.
+        for name in order {
+            let jobs = recorded.remove(&name).unwrap_or_default();
+
+            let (r_core, _) = self.get_coms_data();
+            self.send_job(Box::new(move || {
+                r_core.lock().unwrap().bind_pass(name).unwrap();
+
+                false
+            }));
+
+            for job in jobs {
+                self.send_job(job);
+            }
+        }
     }
 
     /// Will always wait(block).
     pub(crate) fn end(&self) {
         profile_function!();
+
         let (r_core, message_sender) = self.get_coms_data();
         
-        self.job_sender.send(Box::new(move || unsafe {
+        self.send_job(Box::new(move || {
             let mut r_core = r_core.lock().unwrap();
 
-            r_core.render_pipeline.clear();
-            r_core.swap_buffers().unwrap();
+            r_core.active_pass.clear();
+            r_core.previous_pass.clear();
+            r_core.finish_backbuffer_requests();
+            r_core.backend.present().unwrap();
 
             r_core.asset_manager.to_destroy();
 
             message_sender.send(RendererCommand::_END_DONE).unwrap();
             
             false
-        }))
-        .unwrap();
+        }));
 
         while let Ok(msg) = self.receiver.recv() {
             match msg {
@@ -335,23 +619,24 @@ impl Renderer {
     pub(crate) fn draw_imgui(&self) {
         let (r_core, message_sender) = self.get_coms_data();
         
-        self.job_sender.send(Box::new(move || {
+        self.send_job(Box::new(move || {
             r_core.lock().unwrap().render_imgui();
             message_sender.send(RendererCommand::_IMGUI_DONE).unwrap();
             
             false
-        }))
-        .unwrap();
+        }));
     }
 
     pub(crate) fn draw_backbuffer(&self) {
         let (r_core, _) = self.get_coms_data();
         
-        self.job_sender.send(Box::new(move || unsafe {
-            r_core.lock().unwrap().draw_backbuffer().unwrap();
+        self.send_job(Box::new(move || {
+            if let Err(e) = r_core.lock().unwrap().draw_backbuffer() {
+                error!("Failed to draw the backbuffer: {e} (Renderer)");
+            }
             
             false
-        })).unwrap();
+        }));
     }
 
     pub(crate) fn update_imgui_delta_time(&self, delta: std::time::Duration) {
@@ -380,19 +665,156 @@ impl Renderer {
     fn get_coms_data(&self) -> (Arc<Mutex<RendererCore>>, Sender<RendererCommand>) {
         (Arc::clone(&self.core), self.message_sender.clone())
     }
+
+    fn new_windowless(
+        window_info: CreationWindowInfo,
+        asset_manager: AssetManager,
+        opengl: bool,
+    ) -> Result<(Self, LgWindow), StdError>
+    {
+        profile_function!();
+
+        let (core_sender, core_receiver) = std::sync::mpsc::channel();
+
+        let (s_sender, s_receiver) = std::sync::mpsc::channel::<Job>();
+        let (r_sender, r_receiver) = std::sync::mpsc::channel();
+
+        let size = (window_info.width, window_info.height);
+        let vsync = window_info.vsync;
+        let thread = std::thread::spawn(move || {
+            optick::register_thread("render_thread");
+
+            let specs = match opengl {
+                true => match init_opengl_offscreen(size.0, size.1) {
+                    Ok(specs) => Some(specs),
+                    Err(e) => {
+                        warn!("{e}, falling back to the SoftwareBackend! (Renderer)");
+                        None
+                    }
+                },
+                false => None,
+            };
+
+            let renderer_core = match specs {
+                Some(specs) => RendererCore::new(
+                    specs, 
+                    asset_manager,
+                    imgui_init_windowless(size),
+                    None,
+                ),
+                None => RendererCore::with_backend(
+                    Box::new(SoftwareBackend::new(size)),
+                    asset_manager,
+                    ImGuiCore::new_headless(imgui::Context::create(), size),
+                ),
+            };
+
+            let renderer_core = match renderer_core {
+                Ok(core) => Arc::new(Mutex::new(core)),
+                Err(e) => {
+                    core_sender.send(Err(e.to_string())).unwrap();
+                    return;
+                }
+            };
+
+            renderer_core.lock().unwrap().set_vsync(vsync);
+
+            // Sending outside this thread.
+            core_sender.send(Ok(renderer_core)).unwrap();
+
+            while let Ok(job) = s_receiver.recv() {
+                if job() {
+                    break; 
+                }
+            }
+        });
+        
+        let core = NCOption::Some(core_receiver.recv()??);
+
+        Ok((
+            Self {
+                core,
+                job_sender: s_sender,
+                message_sender: r_sender,
+                receiver: r_receiver,
+                thread: Some(thread),
+                render_graph: RefCell::default(),
+                pass_order: RefCell::default(),
+                recorded_passes: RefCell::default(),
+                recording_pass: RefCell::default(),
+            },
+            LgWindow::new_headless(size.0, size.1),
+        ))
+    }
+
+    fn request_backbuffer(&self, request: BackbufferRequest) {
+        let (r_core, _) = self.get_coms_data();
+
+        self.send_job(Box::new(move || {
+            r_core.lock().unwrap().backbuffer_requests.push(request);
+
+            false
+        }));
+    }
+
+    /// Will always wait(block).
+    fn capture<F>(&self, func: F) -> Result<image::RgbaImage, StdError> 
+        where F: FnOnce(&mut RendererCore) -> Result<image::RgbaImage, StdError> + Send + 'static
+    {
+        let (r_core, message_sender) = self.get_coms_data();
+
+        self.send_job(Box::new(move || {
+            let result = func(&mut r_core.lock().unwrap())
+                .map_err(|e| e.to_string());
+
+            message_sender.send(RendererCommand::_CAPTURE_DONE(result)).unwrap();
+
+            false
+        }));
+
+        while let Ok(msg) = self.receiver.recv() {
+            if let RendererCommand::_CAPTURE_DONE(result) = msg {
+                return Ok(result?);
+            }
+        }
+
+        Err("Failed to receive the capture! (Renderer)".into())
+    }
+
+    /// Runs the job right away when headless.
+    fn send_job(&self, job: Job) {
+        if self.is_headless() {
+            job();
+            return;
+        }
+
+        self.job_sender.send(job).unwrap();
+    }
+
+    /// Held until flush_render_graph() while a pass is being recorded.
+    fn send_pass_job(&self, job: Job) {
+        match &*self.recording_pass.borrow() {
+            Some(pass) => self.recorded_passes
+                .borrow_mut()
+                .entry(pass.clone())
+                .or_default()
+                .push(job),
+            None => self.send_job(job),
+        }
+    }
     
     /// Will always block.
     fn shutdown(&mut self) {
         let (_, message_sender) = self.get_coms_data();
         let r_core = std::mem::take(&mut self.core);
         
-        self.job_sender.send(Box::new(move || {
+        self.send_job(Box::new(move || {
             r_core.lock().unwrap().imgui_core.shutdown();
             
             message_sender.send(RendererCommand::_SHUTDOWN_DONE).unwrap();
             
             true
-        })).unwrap();
+        }));
         
         while let Ok(msg) = self.receiver.recv() {
             match msg {
@@ -406,31 +828,35 @@ impl Renderer {
 impl Drop for Renderer {
     fn drop(&mut self) {
         self.shutdown();
-        while !self.thread.is_finished() {}
+        if let Some(thread) = &self.thread {
+            while !thread.is_finished() {}
+        }
     }
 }
 
 #[derive(Debug)]
 struct DrawData {
-    uniforms: Vec<GlBuffer>,
+    uniforms: Vec<Uniform>,
     textures: Vec<UUID>,
     instance_data: (u32, VertexInfo, Vec<u8>),
 
-    indices_len: usize,
     first_location: u32,
 }
 pub struct RendererCore {
     imgui_core: ImGuiCore,
 
     asset_manager:AssetManager,
-    gl_specs: GlSpecs,
+    backend: Box<dyn RenderBackend>,
 
     // Material, Mesh, Data
     draw_data: HashMap<UUID, HashMap<UUID, DrawData>>,
-    // Order of Render Passes
-    render_pipeline: Vec<String>,
-    render_passes: HashMap<String, RenderTarget>,
+    render_graph: CompiledRenderGraph,
+    /// Physical targets, shared by the passes of render_graph.
+    render_targets: Vec<RenderTargetId>,
     active_pass: String,
+    previous_pass: String,
+    /// Served by end(), before presenting.
+    backbuffer_requests: Vec<BackbufferRequest>,
     
     vsync: bool
 }
@@ -438,38 +864,30 @@ impl RendererCore {
     pub fn imgui(&mut self) -> &mut ImGuiCore {
         &mut self.imgui_core
     }
+
+    pub fn backend(&self) -> &dyn RenderBackend {
+        self.backend.as_ref()
+    }
+
+    /// backend_mut().recorder() gives access to the recorded commands when headless.
+    pub fn backend_mut(&mut self) -> &mut dyn RenderBackend {
+        self.backend.as_mut()
+    }
 }
 impl RendererCore {
     fn new(
         specs: GlSpecs, 
-        mut asset_manager: AssetManager,
+        asset_manager: AssetManager,
         mut imgui_context: imgui::Context,
-        imgui_winit: imgui_winit_support::WinitPlatform,
+        imgui_winit: Option<imgui_winit_support::WinitPlatform>,
     ) -> Result<Self, StdError> 
     {
-        let mut _symbol_debug = 0;
-
-        gl::load_with(|symbol| {
-            _symbol_debug += 1;
-
-            let symbol = CString::new(symbol).unwrap();
-            specs.gl_display.get_proc_address(symbol.as_c_str()) as *const _
-        });
-        
-        warn!("OpenGL Symbols loaded: {}", _symbol_debug);
-        
-        // Debug
-        gl_check_and_print!(gl::Enable(gl::DEBUG_OUTPUT));
-        gl_check_and_print!(gl::DebugMessageCallback(Some(debug_callback), std::ptr::null()));
-
-        // Depth, Blend
-        gl_check_and_print!(gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA));
-        gl_check_and_print!(gl::Enable(gl::BLEND));
-
         let mut gl_glow = unsafe {
             glow::Context::from_loader_function_cstr(|s| specs.gl_display.get_proc_address(s).cast())
         };
 
+        let backend = OpenGlBackend::new(specs);
+
         let mut simple_textures = imgui_glow_renderer::SimpleTextureMap {};
         let imgui_renderer = imgui_glow_renderer::Renderer::initialize(
             &mut gl_glow, 
@@ -486,17 +904,29 @@ impl RendererCore {
             imgui_renderer,
         );
 
-        asset_manager.init().unwrap();
+        Self::with_backend(Box::new(backend), asset_manager, imgui_core)
+    }
+
+    fn with_backend(
+        backend: Box<dyn RenderBackend>,
+        mut asset_manager: AssetManager,
+        imgui_core: ImGuiCore,
+    ) -> Result<Self, StdError>
+    {
+        asset_manager.init()?;
 
         Ok(Self {
             imgui_core,
             asset_manager,
-            gl_specs: specs,
+            backend,
             draw_data: HashMap::default(),
 
-            render_pipeline: Vec::default(),
-            render_passes: HashMap::default(),
+            render_graph: CompiledRenderGraph::default(),
+            render_targets: Vec::default(),
             active_pass: String::default(),
+            previous_pass: String::default(),
+
+            backbuffer_requests: Vec::new(),
 
             vsync: false,
         })
@@ -505,10 +935,7 @@ impl RendererCore {
     fn render_imgui(&mut self) {
         profile_function!();
 
-        let size = (
-            self.gl_specs.gl_surface.width().unwrap(),
-            self.gl_specs.gl_surface.height().unwrap(),
-        );
+        let size = self.backend.backbuffer_size();
 
         let specs = RenderTargetSpecs {
             clear: true,
@@ -518,7 +945,9 @@ impl RendererCore {
             ..Default::default()
         };
 
-        unsafe { self.set_render_target(0, &specs); }
+        if let Err(e) = self.backend.bind_render_target(None, &specs) {
+            error!("{e}");
+        }
 
         self.imgui_core.render_imgui();        
     }
@@ -527,174 +956,109 @@ impl RendererCore {
         profile_function!();
 
         self.vsync = op;
-        if op {
-            self.gl_specs.gl_surface.set_swap_interval(&self.gl_specs.gl_context, glutin::surface::SwapInterval::Wait(std::num::NonZeroU32::new(1).unwrap())).unwrap();
-        } else {
-            self.gl_specs.gl_surface.set_swap_interval(&self.gl_specs.gl_context, glutin::surface::SwapInterval::DontWait).unwrap();
-        } 
+        self.backend.set_vsync(op).unwrap();
     }
 
-    unsafe fn set_uniforms(&self, uniforms: &[Uniform]) -> Result<Vec<GlBuffer>, StdError> {
-        profile_function!();
-
-        let mut gl_ubos = Vec::with_capacity(uniforms.len());
-
-        for u in uniforms {
-            let buffer = GlBuffer::new(u.u_type().to_opengl())?;
-            
-            buffer.bind()?;
-            buffer.bind_base(u.binding())?;
-            buffer.set_data_full(
-                u.data_size(), 
-                u.get_raw_data(),
-                gl::STATIC_DRAW
-            )?;
-            buffer.unbind()?;
-
-            gl_ubos.push(buffer);
-        }
-        
-        Ok(gl_ubos)
+    fn init_assets(&mut self) -> Result<(), StdError> {
+        self.backend.init_assets(&mut self.asset_manager)
     }
 
-    unsafe fn draw(&mut self, dd: SendDrawData) -> Result<(), StdError> {
+    fn draw(&mut self, dd: SendDrawData) -> Result<(), StdError> {
         profile_function!();
 
-        let mesh = self.asset_manager
-            .get_mesh(&dd.mesh)?
-            .as_ref()
-            .unwrap();
-
-        let material = self.asset_manager
-            .get_material(&dd.material)?
-            .as_ref()
-            .unwrap();
-        
-        // Program
-        self.asset_manager.init_gl_program()?;
-        let program = material.gl_program.as_ref().ok_or("Couldn't find GlProgram in Material!")?;
-        program.use_prog()?;
-
-        // VAO
-        self.asset_manager.init_gl_vao()?;
-        let vao = mesh.gl_vao.as_ref().ok_or("Couldn't find GlVertexArray in Mesh!")?;
-        vao.bind()?;
-        vao.vertex_buffer().bind()?;
-        vao.index_buffer().bind()?;
-
-        let ubos = self.set_uniforms(&dd.uniforms)?;
-        
-        for ubo in &ubos {
-            ubo.bind()?;
-        }
-
-        for (location, tex_op) in dd.textures.iter().enumerate() {
+        // Loaded before initializing, so they are ready for this draw.
+        self.asset_manager.get_mesh_or_fallback(&dd.mesh);
+        self.asset_manager.get_material_or_fallback(&dd.material);
+        for tex_op in &dd.textures {
             match tex_op {
-                command::TextureOption::UUID(_) => todo!(),
-                command::TextureOption::LG_TEXTURE(_) => todo!(),
-                command::TextureOption::GL_TEXTURE(tex) => {
-                    gl_check_and_print!(gl::ActiveTexture(gl::TEXTURE0 + location as u32));
-                    gl_check_and_print!(gl::BindTexture(gl::TEXTURE_2D, *tex));
-                    gl_check_and_print!(gl::Uniform1i(location as i32, location as i32));
-                },
-                command::TextureOption::PREVIOUS_PASS => {
-                    let pass = self.render_pipeline.last().unwrap();
-                    let tex = self.render_passes.get(pass).unwrap()
-                        .color_texture;
-                    
-                    gl_check_and_print!(gl::ActiveTexture(gl::TEXTURE0 + location as u32));
-                    gl_check_and_print!(gl::BindTexture(gl::TEXTURE_2D, tex));
-                    gl_check_and_print!(gl::Uniform1i(location as i32, location as i32));
-                },
+                command::TextureOption::UUID(uuid) => { self.asset_manager.get_texture_or_fallback(uuid); },
+                // Resolved by its UUID, so it gets the same fallback as any other texture.
+                command::TextureOption::LG_TEXTURE(texture) => { self.asset_manager.get_texture_or_fallback(texture.uuid()); },
+                command::TextureOption::CUBE_MAP(uuid) => { self.asset_manager.get_texture_of_kind_or_fallback(uuid, TextureKind::CUBE_MAP); },
+                command::TextureOption::TEXTURE_ARRAY(uuid) => { self.asset_manager.get_texture_of_kind_or_fallback(uuid, TextureKind::TEXTURE_2D_ARRAY); },
+                _ => (),
             }
         }
-        
-        {
-            profile_scope!("DrawElements");
-            gl_check_and_print!(gl::DrawElements(gl::TRIANGLES, mesh.indices().len() as i32, gl::UNSIGNED_INT, std::ptr::null()));
+
+        self.init_assets()?;
+
+        // Nothing is loaded or destroyed anymore until the draw.
+        let assets = &self.asset_manager;
+        let mesh = assets.loaded_mesh_or_fallback(&dd.mesh);
+        let material = assets.loaded_material_or_fallback(&dd.material);
+
+        let mut textures = Vec::with_capacity(dd.textures.len());
+        for tex_op in &dd.textures {
+            textures.push(match tex_op {
+                command::TextureOption::UUID(uuid) => TextureBinding::Texture(assets.loaded_texture_or_fallback(uuid)),
+                command::TextureOption::LG_TEXTURE(texture) => TextureBinding::Texture(assets.loaded_texture_or_fallback(texture.uuid())),
+                command::TextureOption::GL_TEXTURE(tex) => TextureBinding::Raw(*tex),
+                command::TextureOption::CUBE_MAP(uuid) => TextureBinding::Texture(assets.loaded_texture_of_kind_or_fallback(uuid, TextureKind::CUBE_MAP)),
+                command::TextureOption::TEXTURE_ARRAY(uuid) => TextureBinding::Texture(assets.loaded_texture_of_kind_or_fallback(uuid, TextureKind::TEXTURE_2D_ARRAY)),
+                command::TextureOption::PREVIOUS_PASS => TextureBinding::RenderTarget(self.pass_target(&self.previous_pass)?),
+                command::TextureOption::PASS(pass) => {
+                    let reads = self.render_graph.passes
+                        .get(&self.active_pass)
+                        .is_some_and(|p| p.reads.contains(pass));
+
+                    if !reads {
+                        return Err(std::format!("Pass {} samples {} without declaring it as a read! (Renderer)", self.active_pass, pass).into());
+                    }
+
+                    TextureBinding::RenderTarget(self.pass_target(pass)?)
+                },
+            });
         }
-        
-        vao.unbind_buffers()?;
-        vao.unbind()?;
-        program.unuse()?;
 
-        Ok(())
+        self.backend.draw(&DrawCall {
+            mesh,
+            material,
+            uniforms: &dd.uniforms,
+            textures,
+            instances: None,
+        })
     }
 
-    unsafe fn draw_instanced(&mut self) -> Result<(), StdError> {
+    fn draw_instanced(&mut self) -> Result<(), StdError> {
         profile_function!();
 
+        // Loaded before initializing, so they are ready for this draw.
         for (material_uuid, dd) in &self.draw_data {
-            let material = self.asset_manager.get_material(material_uuid)?
-                    .as_ref()
-                    .unwrap();
-            let instance_vbo = GlBuffer::new(gl::ARRAY_BUFFER)?;
+            self.asset_manager.get_material_or_fallback(material_uuid);
 
             for (mesh_uui, d) in dd {
-                let mesh = self.asset_manager.get_mesh(mesh_uui)?
-                    .as_ref()
-                    .unwrap();
-                let textures = d.textures.iter()
-                    .map(|uuid| self.asset_manager.get_texture(uuid).unwrap() )
-                    .collect::<Vec<_>>();
-
-                // Program
-                let program = material.gl_program.as_ref().ok_or("Couldn't find GlProgram in Material!")?;
-                program.use_prog()?;
-
-                // VAO
-                let vao = mesh.gl_vao.as_ref().ok_or("Couldn't find GlVertexArray in Mesh!")?;
-                vao.bind()?;
-                vao.vertex_buffer().bind()?;
-                vao.index_buffer().bind()?;
-                
-                // Textures
-                self.asset_manager.init_gl_texture()?;
-
-                let last_location = d.first_location;
-                instance_vbo.bind()?;
-                instance_vbo.set_data(&d.instance_data.2, gl::STATIC_DRAW)?;
-                
-                for info in &d.instance_data.1.gl_info {
-                    let location = info.0 + last_location + 1;
-                    vao.set_attribute(location, info.1, d.instance_data.1.stride, info.2)?;
-                    
-                    gl_check_and_print!(gl::VertexAttribDivisor(location, 1));
-                }
+                self.asset_manager.get_mesh_or_fallback(mesh_uui);
+                d.textures.iter().for_each(|uuid| { self.asset_manager.get_texture_or_fallback(uuid); });
+            }
+        }
 
-                for ubo in &d.uniforms {
-                    ubo.bind()?;
-                }
+        self.init_assets()?;
 
-                for tex in textures.iter().enumerate() {
-                    let gl_texture = tex.1
-                        .as_ref()
-                        .unwrap()
-                        .gl_texture
-                        .as_ref()
-                        .ok_or("Couldn't find GlTexture in Texture!")?;
+        // Nothing is loaded or destroyed anymore until the draws.
+        let assets = &self.asset_manager;
+        for (material_uuid, dd) in &self.draw_data {
+            let material = assets.loaded_material_or_fallback(material_uuid);
 
-                    gl_texture.activate(tex.0 as u32)?;
-                    gl_texture.bind()?;
-                }
-                
-                {
-                    profile_scope!("DrawElementsInstanced");
-                    gl_check_and_print!(gl::DrawElementsInstanced(
-                        gl::TRIANGLES, 
-                        d.indices_len as i32, 
-                        gl::UNSIGNED_INT, 
-                        std::ptr::null(), 
-                        d.instance_data.0 as i32
-                    ));
-                }
-                
-                vao.unbind_buffers()?;
-                vao.unbind()?;
-                program.unuse()?;
+            for (mesh_uui, d) in dd {
+                let mesh = assets.loaded_mesh_or_fallback(mesh_uui);
+                let textures = d.textures
+                    .iter()
+                    .map(|uuid| TextureBinding::Texture(assets.loaded_texture_or_fallback(uuid)))
+                    .collect();
+
+                self.backend.draw(&DrawCall {
+                    mesh,
+                    material,
+                    uniforms: &d.uniforms,
+                    textures,
+                    instances: Some(InstanceData {
+                        count: d.instance_data.0,
+                        info: &d.instance_data.1,
+                        data: &d.instance_data.2,
+                        first_location: d.first_location,
+                    }),
+                })?;
             }
-            
-            instance_vbo.unbind()?;
         }
         
         self.draw_data.clear();
@@ -702,36 +1066,46 @@ impl RendererCore {
        Ok(())
     }
 
-    unsafe fn set_render_target(&self, fb_target: gl::types::GLuint, specs: &RenderTargetSpecs) {
+    fn pass_target(&self, pass: &str) -> Result<RenderTargetId, StdError> {
+        let target = self.render_graph.passes
+            .get(pass)
+            .ok_or(std::format!("{} is an invalid render pass! (Renderer)", pass))?
+            .target;
+
+        Ok(self.render_targets[target])
+    }
+
+    fn bind_pass(&mut self, name: String) -> Result<(), StdError> {
         profile_function!();
 
-        let viewport = specs.viewport;
-        if specs.framebuffer_format == FramebufferFormat::SRGB {
-            gl_check_and_print!(gl::Enable(gl::FRAMEBUFFER_SRGB));
-        } 
-        else {
-            gl_check_and_print!(gl::Disable(gl::FRAMEBUFFER_SRGB));
+        let specs = &self.render_graph.passes
+            .get(&name)
+            .ok_or(std::format!("{} is an invalid render pass! (Renderer)", name))?
+            .specs;
+        let target = self.pass_target(&name)?;
+
+        self.backend.bind_render_target(Some(target), specs)?;
+
+        self.previous_pass = std::mem::replace(&mut self.active_pass, name);
+
+        Ok(())
+    }
+
+    fn set_render_graph(&mut self, compiled: CompiledRenderGraph) -> Result<(), StdError> {
+        for target in std::mem::take(&mut self.render_targets) {
+            self.backend.destroy_render_target(target);
         }
 
-        gl_check_and_print!(gl::BindFramebuffer(gl::FRAMEBUFFER, fb_target));
-        gl_check_and_print!(gl::Viewport(viewport.0, viewport.1, viewport.2, viewport.3));
-        gl_check_and_print!(gl::ClearColor(specs.clear_color.x, specs.clear_color.y, specs.clear_color.z, specs.clear_color.w));
-        
-        if specs.clear {
-            if specs.depth_test {
-                gl_check_and_print!(gl::Enable(gl::DEPTH_TEST));
-                gl_check_and_print!(gl::DepthFunc(gl::LESS));
-                
-                gl_check_and_print!(gl::ClearDepth(specs.clear_depth));
-                gl_check_and_print!(gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT));
-            } else {
-                gl_check_and_print!(gl::Disable(gl::DEPTH_TEST));
-                gl_check_and_print!(gl::Clear(gl::COLOR_BUFFER_BIT));
-            }
+        for specs in &compiled.targets {
+            let target = self.backend.create_render_target(specs)?;
+            self.render_targets.push(target);
         }
+        self.render_graph = compiled;
+
+        Ok(())
     }
     
-    unsafe fn send_data(&mut self, mut dd: SendInstanceDrawData) -> Result<(), StdError> {
+    fn send_data(&mut self, mut dd: SendInstanceDrawData) -> Result<(), StdError> {
         profile_function!();
 
         let new_data;
@@ -748,23 +1122,15 @@ impl RendererCore {
         if new_data {
             profile_scope!("new_data");
 
-            let material = self.asset_manager.get_material(&dd.material)?
-                .as_ref()
-                .unwrap();
-            
-            // Program
-            self.asset_manager.init_gl_program()?;
-            let program = material.gl_program.as_ref().ok_or("Couldn't find GlProgram in Material!")?;
-            program.use_prog()?;
+            self.init_assets()?;
 
-            let mesh = self.asset_manager.get_mesh(&dd.mesh)?
-                .as_ref()
-                .unwrap();
+            self.asset_manager.get_material_or_fallback(&dd.material);
 
-            // VAO
-            self.asset_manager.init_gl_vao()?;
+            // Loaded now, so it is initialized before draw_instanced.
+            self.asset_manager.get_mesh_or_fallback(&dd.mesh);
 
-            let textures = material
+            let textures = self.asset_manager
+                .loaded_material_or_fallback(&dd.material)
                 .texture()
                 .to_vec();
 
@@ -774,14 +1140,11 @@ impl RendererCore {
                 dd.instance_data.1,
             );
 
-            let uniforms = self.set_uniforms(&dd.uniforms)?;
-
             let draw_data = DrawData {
-                uniforms,
+                uniforms: dd.uniforms,
                 textures,
                 instance_data,
-                indices_len: mesh.indices().len(),
-                first_location: mesh.vertices()[0].vertex_info().gl_info.last().unwrap().0,
+                first_location: Mesh::vertex_info().gl_info.last().unwrap().0,
             };            
 
             let mat_map = self.draw_data.get_mut(&dd.material).unwrap();
@@ -804,13 +1167,10 @@ impl RendererCore {
         Ok(())
     }
 
-    unsafe fn draw_backbuffer(&mut self) -> Result<(), StdError> {
+    fn draw_backbuffer(&mut self) -> Result<(), StdError> {
         profile_function!();
 
-        let size = (
-            self.gl_specs.gl_surface.width().unwrap(),
-            self.gl_specs.gl_surface.height().unwrap(),
-        );
+        let size = self.backend.backbuffer_size();
 
         let specs = RenderTargetSpecs {
             clear: true,
@@ -820,121 +1180,57 @@ impl RendererCore {
             ..Default::default()
         };
 
-        self.set_render_target(0, &specs);
+        self.backend.bind_render_target(None, &specs)?;
 
-        // Program
-        let material = self.asset_manager.get_material(&FINAL_PASS_MATERIAL)?
-            .as_ref()
-            .unwrap();
+        self.asset_manager.get_material_or_fallback(&FINAL_PASS_MATERIAL);
+        self.asset_manager.get_mesh_or_fallback(&FINAL_PASS_MESH);
 
-        self.asset_manager.init_gl_program()?;
-        let program = material.gl_program.as_ref().ok_or("Couldn't find GlProgram in Material!")?;
-        program.use_prog()?;
+        self.init_assets()?;
 
-        // VAO
-        let mesh = self.asset_manager.get_mesh(&FINAL_PASS_MESH)?
-            .as_ref()
-            .unwrap();
+        let last_pass = self.pass_target(&self.active_pass)?;
+        let material = self.asset_manager.loaded_material_or_fallback(&FINAL_PASS_MATERIAL);
+        let mesh = self.asset_manager.loaded_mesh_or_fallback(&FINAL_PASS_MESH);
 
-        self.asset_manager.init_gl_vao()?;
-        let vao = mesh.gl_vao.as_ref().ok_or("Couldn't find GlVertexArray in Mesh!")?;
-        vao.bind()?;
-        vao.vertex_buffer().bind()?;
-        vao.index_buffer().bind()?;
-        
-        let last_pas = self.render_passes.get(&self.active_pass).unwrap();
-        gl_check_and_print!(gl::ActiveTexture(gl::TEXTURE0));
-        gl_check_and_print!(gl::BindTexture(gl::TEXTURE_2D, last_pas.color_texture));
-        
-        gl_check_and_print!(gl::DrawElements(gl::TRIANGLES, mesh.indices().len() as i32, gl::UNSIGNED_INT, std::ptr::null()));
+        self.backend.draw(&DrawCall {
+            mesh,
+            material,
+            uniforms: &[],
+            textures: vec![TextureBinding::RenderTarget(last_pass)],
+            instances: None,
+        })
+    }
 
-        vao.unbind_buffers()?;
-        vao.unbind()?;
-        program.unuse()?;
-        
-        Ok(())
+    /// The backbuffer is read once for every request of the frame.
+    fn finish_backbuffer_requests(&mut self) {
+        if self.backbuffer_requests.is_empty() { return; }
+
+        let image = self.backend
+            .read_pixels(None)
+            .map_err(|e| e.to_string());
+
+        for request in std::mem::take(&mut self.backbuffer_requests) {
+            request(image.as_ref().map_err(String::as_str));
+        }
     }
 
-    unsafe fn swap_buffers(&mut self) -> Result<(), StdError> {
+    fn resize(&mut self, new_size: (u32, u32)) -> Result<(), StdError> {
         profile_function!();
-        self.gl_specs.gl_surface.swap_buffers(&self.gl_specs.gl_context)?;
-        
+        self.backend.resize(new_size)?;
+
+        self.imgui_core.set_display_size(new_size);
+
         Ok(())
     }
-    
-    unsafe fn resize(&self, new_size: (u32, u32)) {
-        profile_function!();
-        self.gl_specs.gl_surface.resize(
-            &self.gl_specs.gl_context, 
-            std::num::NonZeroU32::new(new_size.0).unwrap(), 
-            std::num::NonZeroU32::new(new_size.1).unwrap(), 
-        );
-    }
-}
-impl Drop for RendererCore {
-    fn drop(&mut self) {
-        if cfg!(debug_assertions) {
-            loop {
-                let err = unsafe { gl::GetError() };
-                if err == gl::NO_ERROR {
-                    break;
-                }
-                
-                println!("OpenGL error {:08x}", err)
-            }
-        }
-    }
 }
 unsafe impl Send for RendererCore {}
 unsafe impl Sync for RendererCore {}
 
-extern "system" fn debug_callback(
-    source: gl::types::GLenum,
-    gltype: gl::types::GLenum,
-    id: gl::types::GLuint,
-    severity: gl::types::GLenum,
-    _length: gl::types::GLsizei,
-    message: *const gl::types::GLchar,
-    _user_param: *mut std::ffi::c_void,
-) {
-    let source_str = match source {
-        gl::DEBUG_SOURCE_API => "API",
-        gl::DEBUG_SOURCE_WINDOW_SYSTEM => "Window System",
-        gl::DEBUG_SOURCE_SHADER_COMPILER => "Shader Compiler",
-        gl::DEBUG_SOURCE_THIRD_PARTY => "Third Party",
-        gl::DEBUG_SOURCE_APPLICATION => "Application",
-        _ => "Unknown",
-    };
-
-    let severity_str = match severity {
-        gl::DEBUG_SEVERITY_HIGH => "High",
-        gl::DEBUG_SEVERITY_MEDIUM => "Medium",
-        gl::DEBUG_SEVERITY_LOW => "Low",
-        gl::DEBUG_SEVERITY_NOTIFICATION => "Notification",
-        _ => "Unknown",
-    };
-
-    let gltype_str = match gltype {
-        gl::DEBUG_TYPE_ERROR => "Error",
-        gl::DEBUG_TYPE_DEPRECATED_BEHAVIOR => "Deprecated Behavior",
-        gl::DEBUG_TYPE_UNDEFINED_BEHAVIOR => "Undefined Behavior",
-        gl::DEBUG_TYPE_PORTABILITY => "Portability",
-        gl::DEBUG_TYPE_PERFORMANCE => "Performance",
-        gl::DEBUG_TYPE_OTHER => "Other",
-        gl::DEBUG_TYPE_MARKER => "Marker",
-        gl::DEBUG_TYPE_PUSH_GROUP => "Push Group",
-        gl::DEBUG_TYPE_POP_GROUP => "Pop Group",
-        _ => "Unknown",
-    };
-
-    if severity != gl::DEBUG_SEVERITY_NOTIFICATION {
-        let message_str = unsafe { 
-            std::str::from_utf8(std::ffi::CStr::from_ptr(message).to_bytes()).unwrap() 
-        };
-        error!("{}", message_str);
-        error!(
-            "OpenGL Debug Message:\n  Source: {}\n  Type: {}\n  ID: {}\n  Severity: {}\n  Message: {}",
-            source_str, gltype_str, id, severity_str, message_str
-        );
+fn save_png(image: &image::RgbaImage, path: &std::path::Path) -> Result<(), StdError> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
     }
-}
\ No newline at end of file
+
+    image.save_with_format(path, image::ImageFormat::Png)?;
+
+    Ok(())
+}
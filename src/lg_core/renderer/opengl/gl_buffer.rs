@@ -44,6 +44,19 @@ impl GlBuffer {
             "Failed to set data!"
         )
     }
+    /// Overwrites the elements from first on, the buffer has to be large enough already.
+    pub fn set_sub_data<D>(&self, first: usize, data: &[D]) -> Result<(), GlError> {
+        let (_, data_bytes, _) = unsafe { data.align_to::<u8>() };
+        gl_check!(
+            gl::BufferSubData(
+                self.target,
+                (first * std::mem::size_of::<D>()) as gl::types::GLintptr,
+                data_bytes.len() as gl::types::GLsizeiptr,
+                data_bytes.as_ptr() as *const _
+            ),
+            "Failed to set sub data!"
+        )
+    }
     pub fn set_data_full(
         &self,
         size: usize,
@@ -7,7 +7,7 @@ use glutin::{
 use glutin_winit::GlWindow;
 use raw_window_handle::HasRawWindowHandle;
 use crate::{lg_core::renderer::CreationWindowInfo, StdError};
-use super::GlSpecs;
+use super::{GlSpecs, GlSurface};
 
 pub(crate) fn init_window(window_info: &CreationWindowInfo) ->
 Result<(Option<winit::window::Window>, glutin::config::Config), StdError> {
@@ -60,12 +60,96 @@ pub(crate) fn init_opengl(window: Option<winit::window::Window>, gl_config: glut
     
     Ok((window, GlSpecs{
         gl_context,
-        gl_surface,
+        gl_surface: GlSurface::Window(gl_surface),
         gl_display,
     }))
 }
 
-pub(crate) fn gl_config_picker(configs: Box<dyn Iterator<Item = glutin::config::Config> + '_>) -> glutin::config::Config {
+/// OpenGL without a window, through an EGL device (Mesa's software device works).
+/// 
+/// Renders into a pbuffer when the device has a config for it, surfaceless otherwise.
+#[cfg(target_os = "linux")]
+pub(crate) fn init_opengl_offscreen(width: u32, height: u32) -> Result<GlSpecs, StdError> {
+    use glutin::{api::egl, config::{ConfigSurfaceTypes, ConfigTemplateBuilder}, surface::{PbufferSurface, SurfaceAttributesBuilder}};
+    use crate::lg_core::renderer::render_target::{RenderTarget, RenderTargetSpecs};
+
+    let size = (
+        std::num::NonZeroU32::new(width).ok_or("Offscreen width can't be 0! (OpenGL)")?,
+        std::num::NonZeroU32::new(height).ok_or("Offscreen height can't be 0! (OpenGL)")?,
+    );
+
+    let egl_display = egl::device::Device::query_devices()?
+        .find_map(|device| unsafe { egl::display::Display::with_device(&device, None) }.ok())
+        .ok_or("Failed to create an EGL display from any device! (OpenGL)")?;
+
+    let contex_attributes = glutin::context::ContextAttributesBuilder::new()
+        .with_context_api(glutin::context::ContextApi::OpenGl(None))
+        .with_debug(true)
+        .build(None);
+
+    let pbuffer_template = ConfigTemplateBuilder::new()
+        .with_surface_type(ConfigSurfaceTypes::PBUFFER)
+        .build();
+    let mut pbuffer_configs = unsafe { egl_display.find_configs(pbuffer_template)? }.peekable();
+
+    if pbuffer_configs.peek().is_some() {
+        let gl_config = gl_config_picker(Box::new(pbuffer_configs));
+        let attrs = SurfaceAttributesBuilder::<PbufferSurface>::new().build(size.0, size.1);
+
+        let (gl_context, gl_surface) = unsafe {
+            let gl_surface = egl_display.create_pbuffer_surface(&gl_config, &attrs)?;
+
+            (
+                egl_display.create_context(&gl_config, &contex_attributes)?.make_current(&gl_surface)?,
+                gl_surface
+            )
+        };
+
+        return Ok(GlSpecs {
+            gl_context: glutin::context::PossiblyCurrentContext::Egl(gl_context),
+            gl_surface: GlSurface::Pbuffer(glutin::surface::Surface::Egl(gl_surface)),
+            gl_display: glutin::display::Display::Egl(egl_display),
+        });
+    }
+
+    drop(pbuffer_configs);
+
+    let template = ConfigTemplateBuilder::new()
+        .with_surface_type(ConfigSurfaceTypes::empty())
+        .build();
+    let mut configs = unsafe { egl_display.find_configs(template)? }.peekable();
+    if configs.peek().is_none() {
+        return Err("Couldn't find an EGL config for offscreen rendering! (OpenGL)".into());
+    }
+
+    let gl_config = gl_config_picker(Box::new(configs));
+    let gl_context = unsafe { egl_display.create_context(&gl_config, &contex_attributes)? }
+        .make_current_surfaceless()?;
+
+    // The backbuffer has to be created right away, so the symbols are needed earlier than usual.
+    gl::load_with(|symbol| {
+        let symbol = std::ffi::CString::new(symbol).unwrap();
+        egl_display.get_proc_address(symbol.as_c_str()) as *const _
+    });
+
+    let backbuffer = RenderTarget::new(RenderTargetSpecs {
+        viewport: (0, 0, width as i32, height as i32),
+        ..Default::default()
+    });
+    
+    Ok(GlSpecs {
+        gl_context: glutin::context::PossiblyCurrentContext::Egl(gl_context),
+        gl_surface: GlSurface::Surfaceless(backbuffer),
+        gl_display: glutin::display::Display::Egl(egl_display),
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn init_opengl_offscreen(_width: u32, _height: u32) -> Result<GlSpecs, StdError> {
+    Err("Offscreen OpenGL is only supported on Linux! (OpenGL)".into())
+}
+
+pub(crate) fn gl_config_picker<C: GlConfig>(configs: Box<dyn Iterator<Item = C> + '_>) -> C {
     configs.reduce(|accum, config| {
             if config.num_samples() > accum.num_samples() {
                 config
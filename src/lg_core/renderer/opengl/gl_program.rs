@@ -1,6 +1,6 @@
 use std::ffi::CString;
 
-use crate::{gl_check, profile_function, StdError};
+use crate::{gl_check, lg_core::renderer::material::UniformValue, profile_function, StdError};
 
 use super::{gl_shader::GlShader, GlError};
 
@@ -63,10 +63,61 @@ impl GlProgram {
             
         Ok(location)
     }
+    /// None if the program doesn't use it.
+    pub(crate) fn get_uniform_location(&self, uniform: &str) -> Result<Option<gl::types::GLint>, StdError> {
+        let uniform = CString::new(uniform)?;
+        let location: gl::types::GLint;
+        gl_check!(location = gl::GetUniformLocation(self.id, uniform.as_ptr()), "Failed to get uniform location!")?;
+
+        Ok((location >= 0).then_some(location))
+    }
+    /// The program has to be in use, uniforms it doesn't have are skipped.
+    pub(crate) fn set_uniform(&self, uniform: &str, value: &UniformValue) -> Result<(), StdError> {
+        let location = match self.get_uniform_location(uniform)? {
+            Some(location) => location,
+            None => return Ok(()),
+        };
+
+        match value {
+            UniformValue::FLOAT(v) => gl_check!(gl::Uniform1f(location, *v), "Failed to set float uniform!")?,
+            UniformValue::VEC2(v) => gl_check!(gl::Uniform2fv(location, 1, v.as_ptr()), "Failed to set vec2 uniform!")?,
+            UniformValue::VEC3(v) => gl_check!(gl::Uniform3fv(location, 1, v.as_ptr()), "Failed to set vec3 uniform!")?,
+            UniformValue::VEC4(v) => gl_check!(gl::Uniform4fv(location, 1, v.as_ptr()), "Failed to set vec4 uniform!")?,
+            UniformValue::MAT4(v) => gl_check!(gl::UniformMatrix4fv(location, 1, gl::FALSE, v.as_ptr()), "Failed to set mat4 uniform!")?,
+        }
+
+        Ok(())
+    }
+    /// The sampler uniform reads from texture unit, the program has to be in use.
+    pub(crate) fn set_sampler(&self, uniform: &str, unit: u32) -> Result<(), StdError> {
+        if let Some(location) = self.get_uniform_location(uniform)? {
+            gl_check!(gl::Uniform1i(location, unit as i32), "Failed to set sampler uniform!")?;
+        }
+
+        Ok(())
+    }
     pub(crate) fn link(&self) -> Result<(), GlError>{
         profile_function!();
 
-        gl_check!(gl::LinkProgram(self.id), "Failed to link shader program!")
+        gl_check!(gl::LinkProgram(self.id), "Failed to link shader program!")?;
+
+        let mut status = 0;
+        gl_check!(gl::GetProgramiv(self.id, gl::LINK_STATUS, &mut status), "Failed to get shader program status!")?;
+
+        if status == gl::FALSE as gl::types::GLint {
+            let mut len = 0;
+            unsafe { gl::GetProgramiv(self.id, gl::INFO_LOG_LENGTH, &mut len); }
+
+            let mut log = vec![0u8; len.max(1) as usize];
+            unsafe { gl::GetProgramInfoLog(self.id, len, std::ptr::null_mut(), log.as_mut_ptr() as *mut gl::types::GLchar); }
+
+            return Err(GlError::Error(
+                String::from_utf8_lossy(&log).trim_end_matches('\0').trim().to_string(),
+                "Failed to link shader program!".to_string()
+            ));
+        }
+
+        Ok(())
     }
 }
 impl Drop for GlProgram {
@@ -0,0 +1,61 @@
+use crate::{gl_check, lg_core::renderer::sampler::SamplerSpecs};
+
+use super::GlError;
+
+// Core since OpenGL 4.6, same values as EXT_texture_filter_anisotropic.
+const TEXTURE_MAX_ANISOTROPY: gl::types::GLenum = 0x84FE;
+const MAX_TEXTURE_MAX_ANISOTROPY: gl::types::GLenum = 0x84FF;
+
+#[derive(Debug)]
+pub(crate) struct GlSampler {
+    pub(crate) id: gl::types::GLuint,
+}
+impl GlSampler {
+    pub(crate) fn new(specs: &SamplerSpecs) -> Result<Self, GlError> {
+        let mut id = 0;
+        gl_check!(gl::GenSamplers(1, &mut id), "Failed to generate sampler!")?;
+
+        let sampler = Self { id };
+        sampler.set_specs(specs)?;
+
+        Ok(sampler)
+    }
+    pub(crate) fn bind(&self, location: u32) -> Result<(), GlError> {
+        gl_check!(gl::BindSampler(location, self.id), "Failed to bind sampler!")
+    }
+    /// The parameters of the texture itself are used again.
+    pub(crate) fn unbind(location: u32) -> Result<(), GlError> {
+        gl_check!(gl::BindSampler(location, 0), "Failed to unbind sampler!")
+    }
+}
+// Private
+impl GlSampler {
+    fn set_specs(&self, specs: &SamplerSpecs) -> Result<(), GlError> {
+        let wraps = [gl::TEXTURE_WRAP_S, gl::TEXTURE_WRAP_T, gl::TEXTURE_WRAP_R];
+        for (wrap, mode) in wraps.into_iter().zip(specs.wrap) {
+            gl_check!(gl::SamplerParameteri(self.id, wrap, mode.to_opengl() as i32), "Failed to set sampler wrap mode!")?;
+        }
+
+        gl_check!(gl::SamplerParameteri(self.id, gl::TEXTURE_MIN_FILTER, specs.min_filter_to_opengl() as i32), "Failed to set sampler min filter!")?;
+        gl_check!(gl::SamplerParameteri(self.id, gl::TEXTURE_MAG_FILTER, specs.mag_filter.to_opengl() as i32), "Failed to set sampler mag filter!")?;
+        gl_check!(gl::SamplerParameterf(self.id, gl::TEXTURE_LOD_BIAS, specs.lod_bias), "Failed to set sampler LOD bias!")?;
+        gl_check!(gl::SamplerParameterf(self.id, gl::TEXTURE_MIN_LOD, specs.min_lod), "Failed to set sampler min LOD!")?;
+        gl_check!(gl::SamplerParameterf(self.id, gl::TEXTURE_MAX_LOD, specs.max_lod), "Failed to set sampler max LOD!")?;
+        gl_check!(gl::SamplerParameterfv(self.id, gl::TEXTURE_BORDER_COLOR, specs.border_color.as_ptr()), "Failed to set sampler border color!")?;
+
+        if specs.anisotropy > 1.0 {
+            let mut max = 1.0;
+            gl_check!(gl::GetFloatv(MAX_TEXTURE_MAX_ANISOTROPY, &mut max), "Anisotropic filtering is not supported!")?;
+            gl_check!(gl::SamplerParameterf(self.id, TEXTURE_MAX_ANISOTROPY, specs.anisotropy.min(max)), "Failed to set sampler anisotropy!")?;
+        }
+
+        Ok(())
+    }
+}
+impl Drop for GlSampler {
+    fn drop(&mut self) {
+        if self.id != 0 {
+            gl_check!(gl::DeleteSamplers(1, [self.id].as_ptr()), "Failed to delete sampler!").unwrap();
+        }
+    }
+}
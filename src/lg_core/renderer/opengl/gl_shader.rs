@@ -22,6 +22,16 @@ impl GlShader {
         )?;
         gl_check!(gl::CompileShader(id), "Failed to compile shader!")?;
 
+        let mut status = 0;
+        gl_check!(gl::GetShaderiv(id, gl::COMPILE_STATUS, &mut status), "Failed to get shader status!")?;
+
+        if status == gl::FALSE as gl::types::GLint {
+            let log = Self::info_log(id);
+            gl_check!(gl::DeleteShader(id), "Failed to delete shader!")?;
+
+            return Err(std::format!("Failed to compile shader: {}", log).into());
+        }
+
         Ok(Self { id }) 
     }
     pub(crate) fn id(&self) -> gl::types::GLuint {
@@ -33,4 +43,18 @@ impl GlShader {
             gl_check!(gl::DeleteShader(self.id), "Failed to delete shader!").unwrap();
         }
     }
-}
\ No newline at end of file
+}
+impl GlShader {
+    fn info_log(id: gl::types::GLuint) -> String {
+        let mut len = 0;
+        unsafe { gl::GetShaderiv(id, gl::INFO_LOG_LENGTH, &mut len); }
+
+        let mut log = vec![0u8; len.max(1) as usize];
+        unsafe { gl::GetShaderInfoLog(id, len, std::ptr::null_mut(), log.as_mut_ptr() as *mut gl::types::GLchar); }
+
+        String::from_utf8_lossy(&log)
+            .trim_end_matches('\0')
+            .trim()
+            .to_string()
+    }
+}
@@ -1,44 +1,76 @@
-use crate::{gl_check, lg_core::renderer::texture::Texture, };
+use crate::{gl_check, lg_core::renderer::texture::{Texture, TextureKind}};
 
 use super::GlError;
 
 #[derive(Debug, Default)]
 pub(crate) struct GlTexture {
     pub(crate) id: gl::types::GLuint,
+    pub(crate) kind: TextureKind,
 }
 impl GlTexture {
-    pub(crate) fn new() -> Result<Self, GlError> {
+    pub(crate) fn new(kind: TextureKind) -> Result<Self, GlError> {
         let mut id = 0;
         gl_check!(gl::GenTextures(1, &mut id), "Failed to generate texture!")?;
         
-        Ok(Self { id })
+        Ok(Self { id, kind })
     }
     pub(crate) fn bind(&self) -> Result<(), GlError> {
-        gl_check!(gl::BindTexture(gl::TEXTURE_2D, self.id), "Failed to bind texture! (binding)")
+        gl_check!(gl::BindTexture(self.kind.to_opengl(), self.id), "Failed to bind texture! (binding)")
     }
     pub(crate) fn activate(&self, location: u32) -> Result<(), GlError> {
         gl_check!(gl::ActiveTexture(gl::TEXTURE0 + location), "Failed to activate texture! (binding)")
     }
     pub(crate) fn unbind(&self) -> Result<(), GlError> {
-        gl_check!(gl::BindTexture(gl::TEXTURE_2D, 0), "Failed to unbind texture!")
+        gl_check!(gl::BindTexture(self.kind.to_opengl(), 0), "Failed to unbind texture!")
     }
     pub(crate) fn load(&self, texture: &Texture) -> Result<(), GlError> {
-        gl_check!(
+        // Rows of R, RG and RGB textures aren't always 4 bytes aligned.
+        gl_check!(gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1), "Failed to set unpack alignment!")?;
+
+        let specs = texture.specs();
+        let image_2d = |target: gl::types::GLenum, bytes: &[u8]| gl_check!(
             gl::TexImage2D(
-                gl::TEXTURE_2D, 
+                target, 
                 0, 
-                texture.specs().tex_format.to_opengl() as i32,
+                specs.tex_format.to_opengl() as i32,
                 texture.width() as i32, 
                 texture.height() as i32, 
                 0, 
-                texture.specs().tex_format.to_opengl_internal(),
-                texture.specs().tex_type.to_opengl(), 
-                texture.bytes().as_ptr() as *const _,
+                specs.tex_format.to_opengl_internal(),
+                specs.tex_type.to_opengl(), 
+                bytes.as_ptr() as *const _,
             ),
             "Failed to load texture!"
-        )?;
+        );
+
+        match self.kind {
+            TextureKind::TEXTURE_2D => image_2d(gl::TEXTURE_2D, texture.bytes())?,
+            TextureKind::CUBE_MAP => {
+                for face in 0..6 {
+                    image_2d(gl::TEXTURE_CUBE_MAP_POSITIVE_X + face, texture.layer(face))?;
+                }
+
+                // Filtering across the edges of the faces.
+                gl_check!(gl::Enable(gl::TEXTURE_CUBE_MAP_SEAMLESS), "Failed to enable seamless cube maps!")?;
+            },
+            TextureKind::TEXTURE_2D_ARRAY => gl_check!(
+                gl::TexImage3D(
+                    gl::TEXTURE_2D_ARRAY,
+                    0,
+                    specs.tex_format.to_opengl() as i32,
+                    texture.width() as i32,
+                    texture.height() as i32,
+                    texture.layers() as i32,
+                    0,
+                    specs.tex_format.to_opengl_internal(),
+                    specs.tex_type.to_opengl(),
+                    texture.bytes().as_ptr() as *const _,
+                ),
+                "Failed to load texture array!"
+            )?,
+        }
 
-        gl_check!(gl::GenerateMipmap(gl::TEXTURE_2D), "Failed to generate mip map!")
+        gl_check!(gl::GenerateMipmap(self.kind.to_opengl()), "Failed to generate mip map!")
         // TODO: What does the function below does?
         // gl_check!(gl::GenerateTextureMipmap(self.id), "Failed to generate mip map for texture!")
     }
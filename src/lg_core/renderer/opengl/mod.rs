@@ -3,9 +3,14 @@ pub(crate) mod gl_init;
 pub(crate) mod gl_buffer;
 pub(crate) mod gl_shader;
 pub(crate) mod gl_texture;
+pub(crate) mod gl_sampler;
 pub(crate) mod gl_vertex_array;
 pub(crate) mod gl_program;
 
+use glutin::{config::GetGlConfig, context::PossiblyCurrentGlContext, display::GlDisplay, surface::{GlSurface as _, PbufferSurface, SurfaceAttributesBuilder}};
+use crate::StdError;
+use super::render_target::RenderTarget;
+
 #[derive(Clone, Debug)]
 pub enum GlError {
     Error(String, String),
@@ -20,7 +25,96 @@ impl std::fmt::Display for GlError {
 impl std::error::Error for GlError {}
 
 pub struct GlSpecs {
-    pub gl_surface: glutin::surface::Surface<glutin::surface::WindowSurface>,
+    pub gl_surface: GlSurface,
     pub gl_display: glutin::display::Display, 
     pub gl_context: glutin::context::PossiblyCurrentContext,
+}
+
+pub enum GlSurface {
+    Window(glutin::surface::Surface<glutin::surface::WindowSurface>),
+    Pbuffer(glutin::surface::Surface<glutin::surface::PbufferSurface>),
+    /// There is no default framebuffer, the backbuffer is an offscreen RenderTarget.
+    Surfaceless(RenderTarget),
+}
+impl GlSurface {
+    pub fn width(&self) -> Option<u32> {
+        match self {
+            GlSurface::Window(surface) => surface.width(),
+            GlSurface::Pbuffer(surface) => surface.width(),
+            GlSurface::Surfaceless(target) => Some(target.specs.viewport.2 as u32),
+        }
+    }
+
+    pub fn height(&self) -> Option<u32> {
+        match self {
+            GlSurface::Window(surface) => surface.height(),
+            GlSurface::Pbuffer(surface) => surface.height(),
+            GlSurface::Surfaceless(target) => Some(target.specs.viewport.3 as u32),
+        }
+    }
+
+    /// The framebuffer that stands for the backbuffer, 0 unless surfaceless.
+    pub fn framebuffer(&self) -> gl::types::GLuint {
+        match self {
+            GlSurface::Surfaceless(target) => target.framebuffer,
+            _ => 0,
+        }
+    }
+
+    /// The buffer that should be read from when capturing the backbuffer.
+    pub(crate) fn read_buffer(&self) -> gl::types::GLenum {
+        match self {
+            GlSurface::Surfaceless(_) => gl::COLOR_ATTACHMENT0,
+            _ => gl::BACK,
+        }
+    }
+
+    pub fn swap_buffers(&self, context: &glutin::context::PossiblyCurrentContext) -> glutin::error::Result<()> {
+        match self {
+            GlSurface::Window(surface) => surface.swap_buffers(context),
+            GlSurface::Pbuffer(surface) => surface.swap_buffers(context),
+            GlSurface::Surfaceless(_) => Ok(()),
+        }
+    }
+
+    pub fn set_swap_interval(
+        &self, 
+        context: &glutin::context::PossiblyCurrentContext, 
+        interval: glutin::surface::SwapInterval
+    ) -> glutin::error::Result<()> 
+    {
+        match self {
+            GlSurface::Window(surface) => surface.set_swap_interval(context, interval),
+            // Nothing is presented, so there is nothing to wait for.
+            GlSurface::Pbuffer(_) | GlSurface::Surfaceless(_) => Ok(()),
+        }
+    }
+
+    /// Pbuffers can't be resized, a new one of the new size is made current instead.
+    pub fn resize(
+        &mut self, 
+        display: &glutin::display::Display,
+        context: &glutin::context::PossiblyCurrentContext, 
+        width: std::num::NonZeroU32, 
+        height: std::num::NonZeroU32
+    ) -> Result<(), StdError> {
+        match self {
+            GlSurface::Window(surface) => surface.resize(context, width, height),
+            GlSurface::Pbuffer(surface) => {
+                let attrs = SurfaceAttributesBuilder::<PbufferSurface>::new().build(width, height);
+                let pbuffer = unsafe { display.create_pbuffer_surface(&context.config(), &attrs)? };
+                context.make_current(&pbuffer)?;
+
+                *surface = pbuffer;
+            },
+            GlSurface::Surfaceless(target) => {
+                let mut specs = target.specs.clone();
+                specs.viewport = (0, 0, width.get() as i32, height.get() as i32);
+
+                *target = RenderTarget::new(specs);
+            },
+        }
+
+        Ok(())
+    }
 }
\ No newline at end of file
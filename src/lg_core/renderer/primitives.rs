@@ -0,0 +1,343 @@
+//! Meshes generated in code, centered at the origin with +Y up and counter-clockwise front faces.
+use std::f32::consts::{PI, TAU};
+use crate::{glm, lg_core::uuid::UUID, StdError};
+use super::{mesh::Mesh, vertex::Vertex};
+
+/// Shape and subdivisions of a generated mesh, too few subdivisions are raised to the minimum of the shape.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Primitive {
+    /// In the XZ plane facing +Y.
+    Plane { size: f32, subdivisions: u32 },
+    /// Every face is subdivided.
+    Cube { size: f32, subdivisions: u32 },
+    /// segments around Y, rings from pole to pole.
+    UvSphere { radius: f32, segments: u32, rings: u32 },
+    /// Subdivided icosahedron, every subdivision splits each triangle in four.
+    Icosphere { radius: f32, subdivisions: u32 },
+    /// segments around Y, rings along the height, with caps.
+    Cylinder { radius: f32, height: f32, segments: u32, rings: u32 },
+    /// Apex at +Y, with a cap.
+    Cone { radius: f32, height: f32, segments: u32, rings: u32 },
+    /// height is between the centers of the hemispheres, rings are per hemisphere.
+    Capsule { radius: f32, height: f32, segments: u32, rings: u32 },
+    /// Around Y, segments around the ring and tube_segments around the tube.
+    Torus { radius: f32, tube_radius: f32, segments: u32, tube_segments: u32 },
+}
+impl Primitive {
+    pub const PLANE: Self = Self::Plane { size: 1.0, subdivisions: 1 };
+    pub const CUBE: Self = Self::Cube { size: 1.0, subdivisions: 1 };
+    pub const UV_SPHERE: Self = Self::UvSphere { radius: 0.5, segments: 32, rings: 16 };
+    pub const ICOSPHERE: Self = Self::Icosphere { radius: 0.5, subdivisions: 2 };
+    pub const CYLINDER: Self = Self::Cylinder { radius: 0.5, height: 1.0, segments: 32, rings: 1 };
+    pub const CONE: Self = Self::Cone { radius: 0.5, height: 1.0, segments: 32, rings: 1 };
+    pub const CAPSULE: Self = Self::Capsule { radius: 0.5, height: 1.0, segments: 32, rings: 8 };
+    pub const TORUS: Self = Self::Torus { radius: 0.5, tube_radius: 0.2, segments: 32, tube_segments: 16 };
+
+    /// The ones the AssetManager registers on creation.
+    pub const DEFAULTS: [Self; 8] = [
+        Self::PLANE,
+        Self::CUBE,
+        Self::UV_SPHERE,
+        Self::ICOSPHERE,
+        Self::CYLINDER,
+        Self::CONE,
+        Self::CAPSULE,
+        Self::TORUS,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Plane { .. } => "plane",
+            Self::Cube { .. } => "cube",
+            Self::UvSphere { .. } => "uv_sphere",
+            Self::Icosphere { .. } => "icosphere",
+            Self::Cylinder { .. } => "cylinder",
+            Self::Cone { .. } => "cone",
+            Self::Capsule { .. } => "capsule",
+            Self::Torus { .. } => "torus",
+        }
+    }
+
+    /// The same for the same shape and parameters, e.g. primitive://uv_sphere(0.5,32,16).
+    pub fn uuid(&self) -> Result<UUID, StdError> {
+        UUID::from_string(&self.to_string())
+    }
+
+    pub fn mesh(&self) -> Result<Mesh, StdError> {
+        let mut builder = MeshBuilder::default();
+
+        match *self {
+            Self::Plane { size, subdivisions } => builder.plane(size, subdivisions),
+            Self::Cube { size, subdivisions } => builder.cube(size, subdivisions),
+            Self::UvSphere { radius, segments, rings } => builder.uv_sphere(radius, segments, rings),
+            Self::Icosphere { radius, subdivisions } => builder.icosphere(radius, subdivisions),
+            Self::Cylinder { radius, height, segments, rings } => builder.cylinder(radius, height, segments, rings),
+            Self::Cone { radius, height, segments, rings } => builder.cone(radius, height, segments, rings),
+            Self::Capsule { radius, height, segments, rings } => builder.capsule(radius, height, segments, rings),
+            Self::Torus { radius, tube_radius, segments, tube_segments } => builder.torus(radius, tube_radius, segments, tube_segments),
+        }
+
+        Ok(Mesh::new(self.uuid()?, self.name(), builder.vertices, builder.indices))
+    }
+}
+impl std::fmt::Display for Primitive {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = self.name();
+
+        match *self {
+            Self::Plane { size, subdivisions } |
+            Self::Cube { size, subdivisions } => write!(f, "primitive://{name}({size},{subdivisions})"),
+            Self::UvSphere { radius, segments, rings } => write!(f, "primitive://{name}({radius},{segments},{rings})"),
+            Self::Icosphere { radius, subdivisions } => write!(f, "primitive://{name}({radius},{subdivisions})"),
+            Self::Cylinder { radius, height, segments, rings } |
+            Self::Cone { radius, height, segments, rings } |
+            Self::Capsule { radius, height, segments, rings } => write!(f, "primitive://{name}({radius},{height},{segments},{rings})"),
+            Self::Torus { radius, tube_radius, segments, tube_segments } => write!(f, "primitive://{name}({radius},{tube_radius},{segments},{tube_segments})"),
+        }
+    }
+}
+
+#[derive(Default)]
+struct MeshBuilder {
+    vertices: Vec<Vertex>,
+    indices: Vec<u32>,
+}
+impl MeshBuilder {
+    fn plane(&mut self, size: f32, subdivisions: u32) {
+        let half = size * 0.5;
+
+        self.surface(subdivisions.max(1), subdivisions.max(1), |s, t| vertex(
+            glm::vec3(size * s - half, 0.0, half - size * t),
+            glm::vec3(0.0, 1.0, 0.0),
+            glm::vec2(s, t),
+        ));
+    }
+
+    fn cube(&mut self, size: f32, subdivisions: u32) {
+        let half = size * 0.5;
+        let (x, y, z) = (glm::Vec3::x(), glm::Vec3::y(), glm::Vec3::z());
+
+        // (normal, right, up) of every face, right x up = normal.
+        let faces = [(x, -z, y), (-x, z, y), (y, x, -z), (-y, x, z), (z, x, y), (-z, -x, y)];
+
+        for (normal, right, up) in faces {
+            self.surface(subdivisions.max(1), subdivisions.max(1), |s, t| vertex(
+                (normal + right * (2.0 * s - 1.0) + up * (2.0 * t - 1.0)) * half,
+                normal,
+                glm::vec2(s, t),
+            ));
+        }
+    }
+
+    fn uv_sphere(&mut self, radius: f32, segments: u32, rings: u32) {
+        self.surface(segments.max(3), rings.max(2), |s, t| {
+            let normal = sphere_direction(s, PI * (1.0 - t));
+
+            vertex(normal * radius, normal, glm::vec2(s, t))
+        });
+    }
+
+    fn icosphere(&mut self, radius: f32, subdivisions: u32) {
+        let t = (1.0 + 5.0_f32.sqrt()) * 0.5;
+        let mut positions = [
+            (-1.0, t, 0.0), (1.0, t, 0.0), (-1.0, -t, 0.0), (1.0, -t, 0.0),
+            (0.0, -1.0, t), (0.0, 1.0, t), (0.0, -1.0, -t), (0.0, 1.0, -t),
+            (t, 0.0, -1.0), (t, 0.0, 1.0), (-t, 0.0, -1.0), (-t, 0.0, 1.0),
+        ]
+        .map(|(x, y, z)| glm::vec3(x, y, z).normalize())
+        .to_vec();
+
+        let mut faces = vec![
+            [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+            [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+            [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+            [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+        ];
+
+        for _ in 0..subdivisions {
+            let mut midpoints = std::collections::HashMap::new();
+            let mut midpoint = |a: u32, b: u32| *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                positions.push((positions[a as usize] + positions[b as usize]).normalize());
+                positions.len() as u32 - 1
+            });
+
+            faces = faces
+                .iter()
+                .flat_map(|&[a, b, c]| {
+                    let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+
+                    [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+                })
+                .collect();
+        }
+
+        // Spherical UVs, the triangles crossing the seam get copies of their vertices with u + 1.
+        let first = self.vertices.len() as u32;
+        self.vertices.extend(positions.iter().map(|p| {
+            let v = 0.5 + p.y.clamp(-1.0, 1.0).asin() / PI;
+
+            vertex(p * radius, *p, glm::vec2(u_of(p), v))
+        }));
+
+        for face in faces {
+            let us = face.map(|i| u_of(&positions[i as usize]));
+            let crosses_seam = us.iter().cloned().fold(f32::MIN, f32::max) - us.iter().cloned().fold(f32::MAX, f32::min) > 0.5;
+
+            for (i, u) in face.iter().zip(us) {
+                let index = match crosses_seam && u < 0.5 {
+                    true => {
+                        let mut copy = self.vertices[(first + i) as usize];
+                        copy.tex_coord = { copy.tex_coord } - glm::vec2(1.0, 0.0);
+                        self.vertices.push(copy);
+
+                        self.vertices.len() as u32 - 1
+                    },
+                    false => first + i,
+                };
+
+                self.indices.push(index);
+            }
+        }
+    }
+
+    fn cylinder(&mut self, radius: f32, height: f32, segments: u32, rings: u32) {
+        let half = height * 0.5;
+
+        self.surface(segments.max(3), rings.max(1), |s, t| {
+            let normal = sphere_direction(s, PI * 0.5);
+
+            vertex(normal * radius + glm::vec3(0.0, height * t - half, 0.0), normal, glm::vec2(s, t))
+        });
+
+        self.cap(radius, half, segments.max(3), true);
+        self.cap(radius, -half, segments.max(3), false);
+    }
+
+    fn cone(&mut self, radius: f32, height: f32, segments: u32, rings: u32) {
+        let half = height * 0.5;
+        // Perpendicular to the slope.
+        let slope = glm::vec2(height, radius).normalize();
+
+        self.surface(segments.max(3), rings.max(1), |s, t| {
+            let around = sphere_direction(s, PI * 0.5);
+            let normal = around * slope.x + glm::vec3(0.0, slope.y, 0.0);
+
+            vertex(around * radius * (1.0 - t) + glm::vec3(0.0, height * t - half, 0.0), normal, glm::vec2(s, t))
+        });
+
+        self.cap(radius, -half, segments.max(3), false);
+    }
+
+    fn capsule(&mut self, radius: f32, height: f32, segments: u32, rings: u32) {
+        let (segments, rings) = (segments.max(3), rings.max(1));
+        let half = height * 0.5;
+        // v follows the length of the profile, from the bottom pole.
+        let length = PI * radius + height;
+        let arc = PI * 0.5 * radius / length;
+
+        self.surface(segments, rings, |s, t| {
+            let normal = sphere_direction(s, PI * (1.0 - 0.5 * t));
+            vertex(normal * radius - glm::vec3(0.0, half, 0.0), normal, glm::vec2(s, arc * t))
+        });
+        self.surface(segments, 1, |s, t| {
+            let normal = sphere_direction(s, PI * 0.5);
+            vertex(normal * radius + glm::vec3(0.0, height * t - half, 0.0), normal, glm::vec2(s, arc + (1.0 - 2.0 * arc) * t))
+        });
+        self.surface(segments, rings, |s, t| {
+            let normal = sphere_direction(s, PI * 0.5 * (1.0 - t));
+            vertex(normal * radius + glm::vec3(0.0, half, 0.0), normal, glm::vec2(s, 1.0 - arc * (1.0 - t)))
+        });
+    }
+
+    fn torus(&mut self, radius: f32, tube_radius: f32, segments: u32, tube_segments: u32) {
+        self.surface(segments.max(3), tube_segments.max(3), |s, t| {
+            let around = sphere_direction(s, PI * 0.5);
+            // The tube starts inside, so the outside faces out.
+            let angle = TAU * t + PI;
+            let normal = around * angle.cos() + glm::vec3(0.0, angle.sin(), 0.0);
+
+            vertex(around * radius + normal * tube_radius, normal, glm::vec2(s, t))
+        });
+    }
+
+    /// A grid of (columns + 1) x (rows + 1) vertices, s and t go from 0 to 1.
+    ///
+    /// The faces point to d(position)/ds x d(position)/dt.
+    fn surface(&mut self, columns: u32, rows: u32, f: impl Fn(f32, f32) -> Vertex) {
+        let first = self.vertices.len() as u32;
+
+        for row in 0..=rows {
+            for column in 0..=columns {
+                self.vertices.push(f(column as f32 / columns as f32, row as f32 / rows as f32));
+            }
+        }
+
+        for row in 0..rows {
+            for column in 0..columns {
+                let i = first + row * (columns + 1) + column;
+                let above = i + columns + 1;
+
+                self.triangle([i, i + 1, above + 1]);
+                self.triangle([i, above + 1, above]);
+            }
+        }
+    }
+
+    /// Skipped if it has no area, e.g. at the poles of a sphere.
+    fn triangle(&mut self, face: [u32; 3]) {
+        let [a, b, c] = face.map(|i| self.vertices[i as usize].position);
+        let longest = (b - a).norm().max((c - b).norm()).max((a - c).norm());
+
+        // Relative to the size of the triangle, so small primitives keep theirs.
+        if glm::cross(&(b - a), &(c - a)).norm() > 1e-6 * longest * longest {
+            self.indices.extend_from_slice(&face);
+        }
+    }
+
+    /// Disc at height y facing +Y if up, -Y otherwise.
+    fn cap(&mut self, radius: f32, y: f32, segments: u32, up: bool) {
+        let normal = glm::vec3(0.0, if up { 1.0 } else { -1.0 }, 0.0);
+        let center = self.vertices.len() as u32;
+
+        self.vertices.push(vertex(glm::vec3(0.0, y, 0.0), normal, glm::vec2(0.5, 0.5)));
+        for segment in 0..=segments {
+            let around = sphere_direction(segment as f32 / segments as f32, PI * 0.5);
+
+            self.vertices.push(vertex(
+                around * radius + glm::vec3(0.0, y, 0.0),
+                normal,
+                glm::vec2(0.5 + around.x * 0.5, 0.5 - around.z * 0.5),
+            ));
+        }
+
+        for segment in 0..segments {
+            let (a, b) = (center + 1 + segment, center + 2 + segment);
+
+            match up {
+                true => self.indices.extend_from_slice(&[center, a, b]),
+                false => self.indices.extend_from_slice(&[center, b, a]),
+            }
+        }
+    }
+}
+
+/// Same UV convention as the imported meshes, uv starts at the bottom left of the texture.
+fn vertex(position: glm::Vec3, normal: glm::Vec3, uv: glm::Vec2) -> Vertex {
+    Vertex {
+        position,
+        normal,
+        tex_coord: glm::vec2(-uv.x, 1.0 - uv.y),
+    }
+}
+
+/// s goes counter-clockwise around +Y starting at +X, polar is the angle from +Y.
+fn sphere_direction(s: f32, polar: f32) -> glm::Vec3 {
+    let azimuth = TAU * s;
+
+    glm::vec3(polar.sin() * azimuth.cos(), polar.cos(), -polar.sin() * azimuth.sin())
+}
+
+/// Inverse of sphere_direction for s.
+fn u_of(direction: &glm::Vec3) -> f32 {
+    ((-direction.z).atan2(direction.x) / TAU).rem_euclid(1.0)
+}
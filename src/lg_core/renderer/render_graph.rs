@@ -0,0 +1,333 @@
+use std::collections::HashMap;
+use super::render_target::RenderTargetSpecs;
+
+/// Every pass writes to a single attachment (color + depth) that carries the pass name.
+///
+/// reads: names of the passes whose attachments are sampled by this one.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RenderPassDesc {
+    pub name: String,
+    pub reads: Vec<String>,
+    pub specs: RenderTargetSpecs,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RenderGraphError {
+    DuplicatePass(String),
+    /// (pass, input)
+    MissingInput(String, String),
+    /// Passes that are part of, or depend on, a cycle.
+    Cycle(Vec<String>),
+}
+impl std::fmt::Display for RenderGraphError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DuplicatePass(pass) => write!(f, "RenderGraphError: pass {} was declared more than once", pass),
+            Self::MissingInput(pass, input) => write!(f, "RenderGraphError: pass {} reads {}, which is not a pass of the graph", pass, input),
+            Self::Cycle(passes) => write!(f, "RenderGraphError: cycle between passes [{}]", passes.join(", ")),
+        }
+    }
+}
+impl std::error::Error for RenderGraphError {}
+
+/// Declarative description of the frame, passes are executed in dependency order, not in declaration order.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RenderGraph {
+    passes: Vec<RenderPassDesc>,
+}
+// Public
+impl RenderGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_pass(&mut self, desc: RenderPassDesc) -> &mut Self {
+        self.passes.push(desc);
+        self
+    }
+
+    /// Replaces the pass with the same name, or adds it.
+    pub fn set_pass(&mut self, desc: RenderPassDesc) -> &mut Self {
+        match self.passes.iter_mut().find(|p| p.name == desc.name) {
+            Some(pass) => *pass = desc,
+            None => self.passes.push(desc),
+        }
+
+        self
+    }
+
+    pub fn remove_pass(&mut self, name: &str) -> Option<RenderPassDesc> {
+        let index = self.passes.iter().position(|p| p.name == name)?;
+
+        Some(self.passes.remove(index))
+    }
+
+    pub fn pass(&self, name: &str) -> Option<&RenderPassDesc> {
+        self.passes.iter().find(|p| p.name == name)
+    }
+
+    pub fn pass_mut(&mut self, name: &str) -> Option<&mut RenderPassDesc> {
+        self.passes.iter_mut().find(|p| p.name == name)
+    }
+
+    pub fn passes(&self) -> &[RenderPassDesc] {
+        &self.passes
+    }
+
+    /// Sorts the passes and assigns them render targets.
+    ///
+    /// Attachments that are read by another pass are intermediate, their targets get reused once the last reader is done.
+    /// Attachments nobody reads are outputs and always get their own target.
+    pub fn compile(&self) -> Result<CompiledRenderGraph, RenderGraphError> {
+        let indices = self.validate()?;
+        let order = self.sort(&indices)?;
+
+        // Execution position of the last pass reading each pass, None for outputs.
+        let mut last_reader: Vec<Option<usize>> = vec![None; self.passes.len()];
+        for (position, &pass_index) in order.iter().enumerate() {
+            for input in &self.passes[pass_index].reads {
+                let input_index = indices[input.as_str()];
+                last_reader[input_index] = Some(last_reader[input_index].map_or(position, |p| p.max(position)));
+            }
+        }
+
+        let mut targets: Vec<RenderTargetSpecs> = Vec::new();
+        // Position after which the target is free again, None while owned by an output.
+        let mut target_free_after: Vec<Option<usize>> = Vec::new();
+        let mut passes = HashMap::with_capacity(self.passes.len());
+
+        for (position, &pass_index) in order.iter().enumerate() {
+            let desc = &self.passes[pass_index];
+
+            let reusable = targets.iter()
+                .zip(&target_free_after)
+                .position(|(specs, free_after)| {
+                    free_after.is_some_and(|p| p < position) && Self::can_alias(specs, &desc.specs)
+                });
+
+            let target = match reusable {
+                Some(target) => target,
+                None => {
+                    targets.push(desc.specs.clone());
+                    target_free_after.push(None);
+                    targets.len() - 1
+                }
+            };
+            target_free_after[target] = last_reader[pass_index];
+
+            passes.insert(desc.name.clone(), CompiledPass {
+                reads: desc.reads.clone(),
+                specs: desc.specs.clone(),
+                target,
+            });
+        }
+
+        Ok(CompiledRenderGraph {
+            order: order.iter().map(|&i| self.passes[i].name.clone()).collect(),
+            passes,
+            targets,
+        })
+    }
+}
+// Private
+impl RenderGraph {
+    fn validate(&self) -> Result<HashMap<&str, usize>, RenderGraphError> {
+        let mut indices = HashMap::with_capacity(self.passes.len());
+        for (i, pass) in self.passes.iter().enumerate() {
+            if indices.insert(pass.name.as_str(), i).is_some() {
+                return Err(RenderGraphError::DuplicatePass(pass.name.clone()));
+            }
+        }
+
+        for pass in &self.passes {
+            if let Some(input) = pass.reads.iter().find(|r| !indices.contains_key(r.as_str())) {
+                return Err(RenderGraphError::MissingInput(pass.name.clone(), input.clone()));
+            }
+        }
+
+        Ok(indices)
+    }
+
+    /// Kahn's algorithm, ties are broken by declaration order.
+    fn sort(&self, indices: &HashMap<&str, usize>) -> Result<Vec<usize>, RenderGraphError> {
+        let mut in_degree = vec![0usize; self.passes.len()];
+        let mut readers: Vec<Vec<usize>> = vec![Vec::new(); self.passes.len()];
+
+        for (i, pass) in self.passes.iter().enumerate() {
+            for input in &pass.reads {
+                in_degree[i] += 1;
+                readers[indices[input.as_str()]].push(i);
+            }
+        }
+
+        let mut ready = in_degree.iter()
+            .enumerate()
+            .filter(|(_, d)| **d == 0)
+            .map(|(i, _)| i)
+            .collect::<std::collections::BTreeSet<_>>();
+        let mut order = Vec::with_capacity(self.passes.len());
+
+        while let Some(pass) = ready.pop_first() {
+            order.push(pass);
+
+            for &reader in &readers[pass] {
+                in_degree[reader] -= 1;
+                if in_degree[reader] == 0 {
+                    ready.insert(reader);
+                }
+            }
+        }
+
+        if order.len() != self.passes.len() {
+            let stuck = in_degree.iter()
+                .enumerate()
+                .filter(|(_, d)| **d > 0)
+                .map(|(i, _)| self.passes[i].name.clone())
+                .collect();
+
+            return Err(RenderGraphError::Cycle(stuck));
+        }
+
+        Ok(order)
+    }
+
+    /// Same size, textures, depth and clear settings, only the viewport offset may differ.
+    fn can_alias(a: &RenderTargetSpecs, b: &RenderTargetSpecs) -> bool {
+        a.viewport.2 == b.viewport.2
+        && a.viewport.3 == b.viewport.3
+        && a.color_texture_specs == b.color_texture_specs
+        && a.framebuffer_format == b.framebuffer_format
+        && a.depth_test == b.depth_test
+        && a.depth_filter == b.depth_filter
+        && a.clear == b.clear
+        && a.clear_color == b.clear_color
+        && a.clear_depth == b.clear_depth
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CompiledPass {
+    pub(crate) reads: Vec<String>,
+    pub(crate) specs: RenderTargetSpecs,
+    /// Index into CompiledRenderGraph::targets.
+    pub(crate) target: usize,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CompiledRenderGraph {
+    pub(crate) order: Vec<String>,
+    pub(crate) passes: HashMap<String, CompiledPass>,
+    /// Specs used to allocate each physical target.
+    pub(crate) targets: Vec<RenderTargetSpecs>,
+}
+impl CompiledRenderGraph {
+    /// Execution order.
+    pub fn order(&self) -> &[String] {
+        &self.order
+    }
+
+    /// How many render targets are really allocated.
+    pub fn target_count(&self) -> usize {
+        self.targets.len()
+    }
+
+    /// Passes sharing the same index share the same render target.
+    pub fn target_of(&self, pass: &str) -> Option<usize> {
+        self.passes.get(pass).map(|p| p.target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pass(name: &str, reads: &[&str]) -> RenderPassDesc {
+        RenderPassDesc {
+            name: name.to_string(),
+            reads: reads.iter().map(|r| r.to_string()).collect(),
+            specs: RenderTargetSpecs {
+                viewport: (0, 0, 64, 64),
+                ..Default::default()
+            },
+        }
+    }
+
+    fn graph(passes: Vec<RenderPassDesc>) -> RenderGraph {
+        let mut graph = RenderGraph::new();
+        for p in passes {
+            graph.add_pass(p);
+        }
+
+        graph
+    }
+
+    #[test]
+    fn cycles_are_rejected() {
+        // c only depends on the cycle, it can't run either.
+        let graph = graph(vec![pass("a", &["b"]), pass("b", &["a"]), pass("c", &["a"]), pass("d", &[])]);
+
+        assert_eq!(graph.compile(), Err(RenderGraphError::Cycle(vec!["a".into(), "b".into(), "c".into()])));
+    }
+
+    #[test]
+    fn reading_a_missing_pass_is_rejected() {
+        let graph = graph(vec![pass("main", &[]), pass("post", &["main", "bloom"])]);
+
+        assert_eq!(graph.compile(), Err(RenderGraphError::MissingInput("post".into(), "bloom".into())));
+    }
+
+    #[test]
+    fn duplicate_passes_are_rejected() {
+        let graph = graph(vec![pass("main", &[]), pass("main", &[])]);
+
+        assert_eq!(graph.compile(), Err(RenderGraphError::DuplicatePass("main".into())));
+    }
+
+    #[test]
+    fn order_follows_the_reads_then_the_declarations() {
+        let graph = graph(vec![pass("post", &["main"]), pass("ui", &[]), pass("main", &[]), pass("shadow", &[])]);
+
+        let compiled = graph.compile().unwrap();
+
+        assert_eq!(compiled.order(), ["ui", "main", "post", "shadow"]);
+        // Compiling again gives the same order.
+        assert_eq!(graph.compile().unwrap().order(), compiled.order());
+    }
+
+    #[test]
+    fn targets_are_aliased_once_the_last_reader_is_done() {
+        let graph = graph(vec![pass("a", &[]), pass("b", &["a"]), pass("c", &["b"]), pass("d", &["c"])]);
+
+        let compiled = graph.compile().unwrap();
+
+        // a is free after b, so c gets its target. a and b are alive at the same time.
+        assert_eq!(compiled.target_of("a"), compiled.target_of("c"));
+        assert_ne!(compiled.target_of("a"), compiled.target_of("b"));
+        assert_ne!(compiled.target_of("c"), compiled.target_of("d"));
+        assert_eq!(compiled.target_count(), 2);
+    }
+
+    #[test]
+    fn overlapping_targets_are_not_aliased() {
+        // a is read by c, so it is still alive while b and c run.
+        let graph = graph(vec![pass("a", &[]), pass("b", &["a"]), pass("c", &["a", "b"])]);
+
+        let compiled = graph.compile().unwrap();
+
+        assert_ne!(compiled.target_of("a"), compiled.target_of("b"));
+        assert_ne!(compiled.target_of("a"), compiled.target_of("c"));
+        assert_ne!(compiled.target_of("b"), compiled.target_of("c"));
+        assert_eq!(compiled.target_count(), 3);
+    }
+
+    #[test]
+    fn targets_with_other_specs_are_not_aliased() {
+        let mut graph = graph(vec![pass("a", &[]), pass("b", &["a"]), pass("c", &["b"])]);
+        graph.pass_mut("c").unwrap().specs.depth_test = true;
+
+        let compiled = graph.compile().unwrap();
+
+        assert_ne!(compiled.target_of("a"), compiled.target_of("c"));
+        assert_eq!(compiled.target_count(), 3);
+    }
+}
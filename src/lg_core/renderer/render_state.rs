@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum BlendMode {
+    /// Replaces the destination.
+    OPAQUE,
+    /// SRC_ALPHA, ONE_MINUS_SRC_ALPHA.
+    #[default]
+    ALPHA,
+    /// ONE, ONE_MINUS_SRC_ALPHA.
+    PREMULTIPLIED,
+    /// SRC_ALPHA, ONE.
+    ADDITIVE,
+    /// DST_COLOR, ZERO.
+    MULTIPLY,
+}
+impl BlendMode {
+    /// (source, destination) factors, None disables blending.
+    pub fn to_opengl(&self) -> Option<(gl::types::GLenum, gl::types::GLenum)> {
+        match &self {
+            BlendMode::OPAQUE => None,
+            BlendMode::ALPHA => Some((gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA)),
+            BlendMode::PREMULTIPLIED => Some((gl::ONE, gl::ONE_MINUS_SRC_ALPHA)),
+            BlendMode::ADDITIVE => Some((gl::SRC_ALPHA, gl::ONE)),
+            BlendMode::MULTIPLY => Some((gl::DST_COLOR, gl::ZERO)),
+        }
+    }
+}
+
+/// Faces that are not drawn, front faces are counter-clockwise.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum CullMode {
+    #[default]
+    NONE,
+    FRONT,
+    BACK,
+    FRONT_AND_BACK,
+}
+impl CullMode {
+    /// None disables culling.
+    pub fn to_opengl(&self) -> Option<gl::types::GLenum> {
+        match &self {
+            CullMode::NONE => None,
+            CullMode::FRONT => Some(gl::FRONT),
+            CullMode::BACK => Some(gl::BACK),
+            CullMode::FRONT_AND_BACK => Some(gl::FRONT_AND_BACK),
+        }
+    }
+}
+
+/// How the triangles of a mesh are rasterized.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PolygonMode {
+    #[default]
+    FILL,
+    /// Wireframe.
+    LINE,
+    POINT,
+}
+impl PolygonMode {
+    pub fn to_opengl(&self) -> gl::types::GLenum {
+        match &self {
+            PolygonMode::FILL => gl::FILL,
+            PolygonMode::LINE => gl::LINE,
+            PolygonMode::POINT => gl::POINT,
+        }
+    }
+}
+
+/// Fixed function state of a material, set before each of its draws.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RenderState {
+    pub blend: BlendMode,
+    /// None keeps the one of the render pass.
+    pub depth_test: Option<bool>,
+    pub depth_write: bool,
+    pub cull: CullMode,
+    pub polygon: PolygonMode,
+}
+impl Default for RenderState {
+    fn default() -> Self {
+        Self {
+            blend: BlendMode::ALPHA,
+            depth_test: None,
+            depth_write: true,
+            cull: CullMode::NONE,
+            polygon: PolygonMode::FILL,
+        }
+    }
+}
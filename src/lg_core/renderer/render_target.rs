@@ -30,19 +30,21 @@ impl RenderTarget {
                 specs.viewport.3 as i32, 
                 0, 
                 specs.color_texture_specs.tex_format.to_opengl_internal(),
-                specs.color_texture_specs.tex_type.to_opengl(), 
+                specs.color_texture_specs.tex_format.texture_type().to_opengl(), 
                 std::ptr::null()
             );
             gl::TexParameteri(
                 gl::TEXTURE_2D, 
                 gl::TEXTURE_MIN_FILTER, 
-                specs.color_texture_specs.tex_filter.to_opengl() as i32
+                specs.color_texture_specs.sampler.min_filter.to_opengl() as i32
             );
             gl::TexParameteri(
                 gl::TEXTURE_2D, 
                 gl::TEXTURE_MAG_FILTER, 
-               specs.color_texture_specs.tex_filter.to_opengl() as i32
+               specs.color_texture_specs.sampler.mag_filter.to_opengl() as i32
             );
+            // No mip maps, so samplers with a mip filter still work.
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAX_LEVEL, 0);
             gl::FramebufferTexture2D(
                 gl::FRAMEBUFFER, 
                 gl::COLOR_ATTACHMENT0, 
@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+use super::texture::TextureFilter;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum WrapMode {
+    #[default]
+    REPEAT,
+    MIRRORED_REPEAT,
+    CLAMP_TO_EDGE,
+    /// Outside of [0, 1] is SamplerSpecs::border_color.
+    CLAMP_TO_BORDER,
+}
+impl WrapMode {
+    pub fn to_opengl(&self) -> gl::types::GLenum {
+        match &self {
+            WrapMode::REPEAT => gl::REPEAT,
+            WrapMode::MIRRORED_REPEAT => gl::MIRRORED_REPEAT,
+            WrapMode::CLAMP_TO_EDGE => gl::CLAMP_TO_EDGE,
+            WrapMode::CLAMP_TO_BORDER => gl::CLAMP_TO_BORDER,
+        }
+    }
+}
+
+/// How the mip levels are picked, NONE only samples level 0.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum MipmapFilter {
+    NONE,
+    NEAREST,
+    #[default]
+    LINEAR,
+}
+
+/// How a texture is sampled, the same texture can be bound with different ones.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SamplerSpecs {
+    /// u, v and w (cube maps).
+    pub wrap: [WrapMode; 3],
+    pub min_filter: TextureFilter,
+    pub mag_filter: TextureFilter,
+    pub mip_filter: MipmapFilter,
+    /// 1.0 disables it, clamped to what the driver supports.
+    pub anisotropy: f32,
+    pub lod_bias: f32,
+    pub min_lod: f32,
+    pub max_lod: f32,
+    /// RGBA, for WrapMode::CLAMP_TO_BORDER.
+    pub border_color: [f32; 4],
+}
+impl Default for SamplerSpecs {
+    fn default() -> Self {
+        Self {
+            wrap: [WrapMode::REPEAT; 3],
+            min_filter: TextureFilter::LINEAR,
+            mag_filter: TextureFilter::LINEAR,
+            mip_filter: MipmapFilter::LINEAR,
+            anisotropy: 1.0,
+            lod_bias: 0.0,
+            // Same as OpenGL.
+            min_lod: -1000.0,
+            max_lod: 1000.0,
+            border_color: [0.0; 4],
+        }
+    }
+}
+impl SamplerSpecs {
+    /// Default sampler with filter for minification and magnification.
+    pub fn from_filter(filter: TextureFilter) -> Self {
+        Self {
+            min_filter: filter,
+            mag_filter: filter,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_wrap(mut self, wrap: WrapMode) -> Self {
+        self.wrap = [wrap; 3];
+
+        self
+    }
+
+    /// GL_TEXTURE_MIN_FILTER, combines min_filter and mip_filter.
+    pub fn min_filter_to_opengl(&self) -> gl::types::GLenum {
+        match (self.min_filter, self.mip_filter) {
+            (filter, MipmapFilter::NONE) => filter.to_opengl(),
+            (TextureFilter::NEAREST, MipmapFilter::NEAREST) => gl::NEAREST_MIPMAP_NEAREST,
+            (TextureFilter::NEAREST, MipmapFilter::LINEAR) => gl::NEAREST_MIPMAP_LINEAR,
+            (TextureFilter::LINEAR, MipmapFilter::NEAREST) => gl::LINEAR_MIPMAP_NEAREST,
+            (TextureFilter::LINEAR, MipmapFilter::LINEAR) => gl::LINEAR_MIPMAP_LINEAR,
+        }
+    }
+}
@@ -96,6 +96,22 @@ impl Shader {
     pub fn src_code(&self) -> &str {
         &self.src_code
     }
+
+    /// Compiles src_code with shaderc, doesn't need a GPU.
+    pub fn compile_spirv(&self) -> Result<Vec<u8>, StdError> {
+        let compiler = shaderc::Compiler::new()
+            .ok_or("Failed to create shader compiler! (Shader)")?;
+
+        let binary = compiler.compile_into_spirv(
+            &self.src_code,
+            self.stage.to_shaderc_stage()?,
+            &self.name,
+            "main",
+            None
+        )?;
+
+        Ok(binary.as_binary_u8().to_vec())
+    }
 }
 impl Hash for Shader {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
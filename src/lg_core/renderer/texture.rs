@@ -1,11 +1,16 @@
 use std::{hash::Hash, mem::size_of};
-use crate::{lg_core::uuid::UUID, StdError};
+use serde::{Deserialize, Serialize};
+use crate::{glm, lg_core::{asset_manager::{legacy_uuid, AssetPath, Vfs}, uuid::UUID}, StdError};
 
-use super::opengl::gl_texture::GlTexture;
+use super::{opengl::gl_texture::GlTexture, sampler::SamplerSpecs};
 
+/// Type of the components in Texture::bytes, native endian.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TextureType {
     UNSIGNED_BYTE,
+    UNSIGNED_SHORT,
+    HALF_FLOAT,
+    FLOAT,
 }
 impl Default for TextureType {
     fn default() -> Self {
@@ -16,6 +21,9 @@ impl TextureType {
     pub fn from(value: u32) -> Result<Self, StdError> {
         match value {
             0 => Ok(Self::UNSIGNED_BYTE),
+            1 => Ok(Self::UNSIGNED_SHORT),
+            2 => Ok(Self::HALF_FLOAT),
+            3 => Ok(Self::FLOAT),
             _ => Err("Failed to convert from u32! (TextureFormat)".into())
         }
     }
@@ -23,15 +31,42 @@ impl TextureType {
     pub fn to_opengl(&self) -> gl::types::GLenum {
         match &self {
             TextureType::UNSIGNED_BYTE => gl::UNSIGNED_BYTE,
+            TextureType::UNSIGNED_SHORT => gl::UNSIGNED_SHORT,
+            TextureType::HALF_FLOAT => gl::HALF_FLOAT,
+            TextureType::FLOAT => gl::FLOAT,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        match &self {
+            TextureType::UNSIGNED_BYTE => size_of::<u8>(),
+            TextureType::UNSIGNED_SHORT | TextureType::HALF_FLOAT => size_of::<u16>(),
+            TextureType::FLOAT => size_of::<f32>(),
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+/// RGB, RGBA, R and RG have 8 bits per component, the ones ending with 16 are normalized like them.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum TextureFormat {
     RGB,
     RGBA,
     SRGB8,
+    SRGBA8,
+    R,
+    RG,
+    R16,
+    RG16,
+    RGB16,
+    RGBA16,
+    R16F,
+    RG16F,
+    RGB16F,
+    RGBA16F,
+    R32F,
+    RG32F,
+    RGB32F,
+    RGBA32F,
 }
 impl Default for TextureFormat {
     fn default() -> Self {
@@ -44,6 +79,21 @@ impl TextureFormat {
             0 => Ok(Self::RGB),
             1 => Ok(Self::RGBA),
             2 => Ok(Self::SRGB8),
+            3 => Ok(Self::SRGBA8),
+            4 => Ok(Self::R),
+            5 => Ok(Self::RG),
+            6 => Ok(Self::R16),
+            7 => Ok(Self::RG16),
+            8 => Ok(Self::RGB16),
+            9 => Ok(Self::RGBA16),
+            10 => Ok(Self::R16F),
+            11 => Ok(Self::RG16F),
+            12 => Ok(Self::RGB16F),
+            13 => Ok(Self::RGBA16F),
+            14 => Ok(Self::R32F),
+            15 => Ok(Self::RG32F),
+            16 => Ok(Self::RGB32F),
+            17 => Ok(Self::RGBA32F),
             _ => Err("Failed to convert from u32! (TextureFormat)".into())
         }
     }
@@ -52,20 +102,65 @@ impl TextureFormat {
         match &self {
             TextureFormat::RGB => gl::RGB,
             TextureFormat::RGBA => gl::RGBA,
-            TextureFormat::SRGB8 => gl::SRGB8
+            TextureFormat::SRGB8 => gl::SRGB8,
+            TextureFormat::SRGBA8 => gl::SRGB8_ALPHA8,
+            TextureFormat::R => gl::R8,
+            TextureFormat::RG => gl::RG8,
+            TextureFormat::R16 => gl::R16,
+            TextureFormat::RG16 => gl::RG16,
+            TextureFormat::RGB16 => gl::RGB16,
+            TextureFormat::RGBA16 => gl::RGBA16,
+            TextureFormat::R16F => gl::R16F,
+            TextureFormat::RG16F => gl::RG16F,
+            TextureFormat::RGB16F => gl::RGB16F,
+            TextureFormat::RGBA16F => gl::RGBA16F,
+            TextureFormat::R32F => gl::R32F,
+            TextureFormat::RG32F => gl::RG32F,
+            TextureFormat::RGB32F => gl::RGB32F,
+            TextureFormat::RGBA32F => gl::RGBA32F,
         }
     }
 
     pub fn to_opengl_internal(&self) -> gl::types::GLenum {
+        match self.channels() {
+            1 => gl::RED,
+            2 => gl::RG,
+            3 => gl::RGB,
+            _ => gl::RGBA,
+        }
+    }
+
+    pub fn channels(&self) -> usize {
         match &self {
-            TextureFormat::RGB => gl::RGB,
-            TextureFormat::RGBA => gl::RGBA,
-            TextureFormat::SRGB8 => gl::RGB
+            TextureFormat::R | TextureFormat::R16 | TextureFormat::R16F | TextureFormat::R32F => 1,
+            TextureFormat::RG | TextureFormat::RG16 | TextureFormat::RG16F | TextureFormat::RG32F => 2,
+            TextureFormat::RGB | TextureFormat::SRGB8 | TextureFormat::RGB16 | TextureFormat::RGB16F | TextureFormat::RGB32F => 3,
+            TextureFormat::RGBA | TextureFormat::SRGBA8 | TextureFormat::RGBA16 | TextureFormat::RGBA16F | TextureFormat::RGBA32F => 4,
         }
     }
+
+    /// Type of the components when the texture is loaded from an image.
+    pub fn texture_type(&self) -> TextureType {
+        match &self {
+            TextureFormat::RGB | TextureFormat::RGBA | TextureFormat::SRGB8 | TextureFormat::SRGBA8 |
+            TextureFormat::R | TextureFormat::RG => TextureType::UNSIGNED_BYTE,
+            TextureFormat::R16 | TextureFormat::RG16 | TextureFormat::RGB16 | TextureFormat::RGBA16 => TextureType::UNSIGNED_SHORT,
+            TextureFormat::R16F | TextureFormat::RG16F | TextureFormat::RGB16F | TextureFormat::RGBA16F => TextureType::HALF_FLOAT,
+            TextureFormat::R32F | TextureFormat::RG32F | TextureFormat::RGB32F | TextureFormat::RGBA32F => TextureType::FLOAT,
+        }
+    }
+
+    pub fn is_srgb(&self) -> bool {
+        matches!(self, TextureFormat::SRGB8 | TextureFormat::SRGBA8)
+    }
+
+    /// Values are not clamped to [0, 1].
+    pub fn is_float(&self) -> bool {
+        matches!(self.texture_type(), TextureType::HALF_FLOAT | TextureType::FLOAT)
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum TextureFilter {
     LINEAR,
     NEAREST,
@@ -84,11 +179,31 @@ impl TextureFilter {
     }
 }
 
+/// Cube maps have 6 layers (+X, -X, +Y, -Y, +Z, -Z), 2D arrays any number of them.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum TextureKind {
+    #[default]
+    TEXTURE_2D,
+    CUBE_MAP,
+    TEXTURE_2D_ARRAY,
+}
+impl TextureKind {
+    pub fn to_opengl(&self) -> gl::types::GLenum {
+        match &self {
+            TextureKind::TEXTURE_2D => gl::TEXTURE_2D,
+            TextureKind::CUBE_MAP => gl::TEXTURE_CUBE_MAP,
+            TextureKind::TEXTURE_2D_ARRAY => gl::TEXTURE_2D_ARRAY,
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone, Copy, PartialEq)]
 pub struct TextureSpecs {
     pub tex_format: TextureFormat,
     pub tex_type: TextureType,
-    pub tex_filter: TextureFilter,
+    /// Used when a draw doesn't override it.
+    pub sampler: SamplerSpecs,
+    pub tex_kind: TextureKind,
 }
 
 #[derive(Debug)]
@@ -96,6 +211,7 @@ pub struct Texture {
     uuid: UUID,
     width: u32,
     height: u32,
+    layers: u32,
     size: u64,
     mip_level: u32,
     specs: TextureSpecs,
@@ -105,27 +221,71 @@ pub struct Texture {
     pub(crate) gl_texture: Option<GlTexture>,
 }
 impl Texture {
-    pub fn new(name: &str, path: &str, specs: TextureSpecs) -> Result<Self, StdError> {
-        let image = image::ImageReader::open(path)?.decode()?;
+    /// path is read through the Vfs, the UUID is the legacy one of path.
+    pub fn new(name: &str, path: &AssetPath, vfs: &Vfs, specs: TextureSpecs) -> Result<Self, StdError> {
+        Self::from_bytes(legacy_uuid(path)?, name, &vfs.read(path)?, specs)
+    }
 
-        let width = image.width();
-        let height = image.height();
-        let bytes = image.as_bytes().to_vec();
-        let size = (bytes.len() * size_of::<u8>()) as u64;
-        let mip_level = (width.max(height) as f32).log2().floor() as u32 + 1;
+    /// bytes is an encoded image (e.g. png, hdr, exr), see from_image.
+    pub fn from_bytes(uuid: UUID, name: &str, bytes: &[u8], specs: TextureSpecs) -> Result<Self, StdError> {
+        Ok(Self::from_image(uuid, name, &image::load_from_memory(bytes)?, specs))
+    }
 
-        Ok(Self {
-            uuid: UUID::from_string(path)?,
-            name: String::from(name),
-            width,
-            height,
-            bytes,
-            size,
-            mip_level,
-            specs,
-            gl_texture: None,
+    /// The image is converted to specs.tex_format, specs.tex_type is replaced by the type of that format.
+    pub fn from_image(uuid: UUID, name: &str, image: &image::DynamicImage, specs: TextureSpecs) -> Self {
+        Self::from_layers(uuid, name, std::slice::from_ref(image), TextureSpecs {
+            tex_kind: TextureKind::TEXTURE_2D,
+            ..specs
         })
     }
+
+    /// Faces in +X, -X, +Y, -Y, +Z, -Z order, all of them square and with the same size.
+    pub fn cube_map_from_faces(uuid: UUID, name: &str, faces: &[image::DynamicImage; 6], specs: TextureSpecs) -> Result<Self, StdError> {
+        let size = faces[0].width();
+        if faces.iter().any(|f| f.width() != size || f.height() != size) {
+            return Err(std::format!("Faces of cube map {} are not squares of the same size! (Texture)", name).into());
+        }
+
+        Ok(Self::from_layers(uuid, name, faces, TextureSpecs {
+            tex_kind: TextureKind::CUBE_MAP,
+            ..specs
+        }))
+    }
+
+    /// image is a 2:1 panorama, every face is face_size x face_size.
+    pub fn cube_map_from_equirectangular(uuid: UUID, name: &str, image: &image::DynamicImage, face_size: u32, specs: TextureSpecs) -> Result<Self, StdError> {
+        if face_size == 0 || image.width() == 0 || image.height() == 0 {
+            return Err(std::format!("Cube map {} would be empty! (Texture)", name).into());
+        }
+
+        let panorama = image.to_rgba32f();
+        let faces = std::array::from_fn(|face| {
+            let mut result = image::Rgba32FImage::new(face_size, face_size);
+            for (x, y, pixel) in result.enumerate_pixels_mut() {
+                let a = 2.0 * (x as f32 + 0.5) / face_size as f32 - 1.0;
+                let b = 2.0 * (y as f32 + 0.5) / face_size as f32 - 1.0;
+
+                *pixel = sample_equirectangular(&panorama, cube_map_direction(face, a, b));
+            }
+
+            image::DynamicImage::ImageRgba32F(result)
+        });
+
+        Self::cube_map_from_faces(uuid, name, &faces, specs)
+    }
+
+    /// Layers with the same size, the first one is layer 0.
+    pub fn array_from_images(uuid: UUID, name: &str, layers: &[image::DynamicImage], specs: TextureSpecs) -> Result<Self, StdError> {
+        let first = layers.first().ok_or(std::format!("Texture array {} has no layers! (Texture)", name))?;
+        if layers.iter().any(|l| l.width() != first.width() || l.height() != first.height()) {
+            return Err(std::format!("Layers of texture array {} don't have the same size! (Texture)", name).into());
+        }
+
+        Ok(Self::from_layers(uuid, name, layers, TextureSpecs {
+            tex_kind: TextureKind::TEXTURE_2D_ARRAY,
+            ..specs
+        }))
+    }
     
     pub fn gl_id(&self) -> Option<gl::types::GLuint> {
         if let Some(gl_tex) = &self.gl_texture {
@@ -150,10 +310,25 @@ impl Texture {
     pub fn height(&self) -> u32 {
         self.height
     }
+
+    /// 1 for 2D textures, 6 for cube maps.
+    pub fn layers(&self) -> u32 {
+        self.layers
+    }
     
+    /// Every layer, one after the other.
     pub fn bytes(&self) -> &[u8] {
         &self.bytes
     }
+
+    /// Empty if layer doesn't exist.
+    pub fn layer(&self, layer: u32) -> &[u8] {
+        let size = self.bytes.len() / self.layers.max(1) as usize;
+
+        self.bytes
+            .get(layer as usize * size..(layer as usize + 1) * size)
+            .unwrap_or_default()
+    }
     
     pub fn size(&self) -> u64 {
         self.size
@@ -167,6 +342,7 @@ impl Texture {
         self.specs
     }
 
+    /// bytes has every layer, one after the other.
     pub fn construct(
         uuid: UUID,
         name: &str,
@@ -178,10 +354,18 @@ impl Texture {
         specs: TextureSpecs
     ) -> Self 
     {
+        let layer_size = width as usize * height as usize * specs.tex_format.channels() * specs.tex_type.size();
+        let layers = match specs.tex_kind {
+            TextureKind::TEXTURE_2D => 1,
+            TextureKind::CUBE_MAP => 6,
+            TextureKind::TEXTURE_2D_ARRAY => (bytes.len() / layer_size.max(1)) as u32,
+        };
+
         Self {
             uuid,
             width,
             height,
+            layers,
             size,
             mip_level,
             specs,
@@ -197,7 +381,7 @@ impl Texture {
     pub(crate) fn init_opengl(&mut self) -> Result<(), StdError> {
         if self.gl_texture.is_some() { return Ok(()); }
 
-        let gl_tex = GlTexture::new()?;
+        let gl_tex = GlTexture::new(self.specs.tex_kind)?;
         gl_tex.bind()?;
         gl_tex.load(&self)?;
         gl_tex.unbind()?;
@@ -208,6 +392,37 @@ impl Texture {
     }
 }
 
+// Private
+impl Texture {
+    /// Layers have the same size.
+    fn from_layers(uuid: UUID, name: &str, layers: &[image::DynamicImage], specs: TextureSpecs) -> Self {
+        let width = layers[0].width();
+        let height = layers[0].height();
+        let bytes = layers
+            .iter()
+            .flat_map(|l| image_bytes(l, specs.tex_format))
+            .collect::<Vec<_>>();
+        let size = (bytes.len() * size_of::<u8>()) as u64;
+        let mip_level = (width.max(height) as f32).log2().floor() as u32 + 1;
+
+        Self {
+            uuid,
+            name: String::from(name),
+            width,
+            height,
+            layers: layers.len() as u32,
+            bytes,
+            size,
+            mip_level,
+            specs: TextureSpecs {
+                tex_type: specs.tex_format.texture_type(),
+                ..specs
+            },
+            gl_texture: None,
+        }
+    }
+}
+
 impl Hash for Texture {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         self.uuid.hash(state);
@@ -217,4 +432,75 @@ impl PartialEq for Texture {
     fn eq(&self, other: &Self) -> bool {
         self.uuid == other.uuid
     }
-}
\ No newline at end of file
+}
+
+/// Components of image in the layout of format.
+fn image_bytes(image: &image::DynamicImage, format: TextureFormat) -> Vec<u8> {
+    // Of the RGBA conversion.
+    let channels: &[usize] = match (format.channels(), image.color().channel_count()) {
+        (1, _) => &[0],
+        // Gray and alpha.
+        (2, 2) => &[0, 3],
+        (2, _) => &[0, 1],
+        (3, _) => &[0, 1, 2],
+        _ => &[0, 1, 2, 3],
+    };
+
+    match format.texture_type() {
+        TextureType::UNSIGNED_BYTE => select_channels(image.to_rgba8().as_raw(), channels, |c| [c]),
+        TextureType::UNSIGNED_SHORT => select_channels(image.to_rgba16().as_raw(), channels, u16::to_ne_bytes),
+        TextureType::HALF_FLOAT => select_channels(image.to_rgba32f().as_raw(), channels, |c| half::f16::from_f32(c).to_ne_bytes()),
+        TextureType::FLOAT => select_channels(image.to_rgba32f().as_raw(), channels, f32::to_ne_bytes),
+    }
+}
+
+fn select_channels<T: Copy, const N: usize>(rgba: &[T], channels: &[usize], to_bytes: impl Fn(T) -> [u8; N]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(rgba.len() / 4 * channels.len() * N);
+
+    for pixel in rgba.chunks_exact(4) {
+        for c in channels {
+            bytes.extend_from_slice(&to_bytes(pixel[*c]));
+        }
+    }
+
+    bytes
+}
+
+/// Direction of the texel at (a, b) in [-1, 1] of face, b goes down like the rows of an image.
+fn cube_map_direction(face: usize, a: f32, b: f32) -> glm::Vec3 {
+    let direction = match face {
+        0 => glm::vec3(1.0, -b, -a),
+        1 => glm::vec3(-1.0, -b, a),
+        2 => glm::vec3(a, 1.0, b),
+        3 => glm::vec3(a, -1.0, -b),
+        4 => glm::vec3(a, -b, 1.0),
+        _ => glm::vec3(-a, -b, -1.0),
+    };
+
+    direction.normalize()
+}
+
+/// Bilinear, -Z is the center of the panorama and +Y its top.
+fn sample_equirectangular(panorama: &image::Rgba32FImage, direction: glm::Vec3) -> image::Rgba<f32> {
+    let (width, height) = (panorama.width() as i64, panorama.height() as i64);
+
+    let u = 0.5 + direction.x.atan2(-direction.z) / (2.0 * std::f32::consts::PI);
+    let v = direction.y.clamp(-1.0, 1.0).acos() / std::f32::consts::PI;
+
+    let (x, y) = (u * width as f32 - 0.5, v * height as f32 - 0.5);
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+
+    let texel = |x: i64, y: i64| panorama
+        .get_pixel(x.rem_euclid(width) as u32, y.clamp(0, height - 1) as u32)
+        .0;
+    let (x0, y0) = (x0 as i64, y0 as i64);
+    let (t00, t10, t01, t11) = (texel(x0, y0), texel(x0 + 1, y0), texel(x0, y0 + 1), texel(x0 + 1, y0 + 1));
+
+    image::Rgba(std::array::from_fn(|c| {
+        let top = t00[c] + (t10[c] - t00[c]) * fx;
+        let bottom = t01[c] + (t11[c] - t01[c]) * fx;
+
+        top + (bottom - top) * fy
+    }))
+}
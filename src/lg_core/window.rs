@@ -2,21 +2,42 @@ use crate::glm;
 
 #[derive(Debug)]
 pub struct LgWindow {
-    pub(crate) window: winit::window::Window,
+    /// None when running headless.
+    pub(crate) window: Option<winit::window::Window>,
+    headless_size: (u32, u32),
 }
 // Public
 impl LgWindow {
     pub fn size(&self) -> glm::Vec2 {
-        glm::vec2(self.window.inner_size().width as f32, self.window.inner_size().height as f32)
+        match &self.window {
+            Some(window) => glm::vec2(window.inner_size().width as f32, window.inner_size().height as f32),
+            None => glm::vec2(self.headless_size.0 as f32, self.headless_size.1 as f32),
+        }
+    }
+
+    pub fn is_headless(&self) -> bool {
+        self.window.is_none()
     }
 }
 // Public(crate)
 impl LgWindow {
     pub(crate) fn new(window: winit::window::Window) -> Self {
-        Self { window }
+        Self {
+            window: Some(window),
+            headless_size: (0, 0),
+        }
+    }
+
+    pub(crate) fn new_headless(width: u32, height: u32) -> Self {
+        Self {
+            window: None,
+            headless_size: (width, height),
+        }
     }
 
     pub(crate) fn request_redraw(&self) {
-        self.window.request_redraw();
+        if let Some(window) = &self.window {
+            window.request_redraw();
+        }
     }
-}
\ No newline at end of file
+}
@@ -11,6 +11,11 @@ fn main() {
             height: 720,
             vsync: true,
         },
+        headless: None,
+        assets_root: None,
+        asset_packs: Vec::new(),
+        asset_mounts: Vec::new(),
+        asset_importers: Vec::new(),
     }).unwrap();
     
     legion.run().unwrap();